@@ -1,11 +1,12 @@
 //! Launch Bar application
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
-use std::sync::Arc;
-use std::time::Instant;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 use arboard::Clipboard;
 use eframe::egui;
@@ -13,10 +14,53 @@ use egui_cha_ds::icons;
 use egui_cha_ds::Theme;
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 
-use crate::config::{AppState, CommandConfig, Preset, WindowSettings};
-use crate::platform::{open_file, spawn_shell_command};
-use crate::script::{resolve_script_type, run_script, ScriptConfig, ScriptType};
-use crate::ui::{get_icon, palette, parse_hex_color, title_bar_button, vary_color_by_path};
+use crate::command::{
+    candidate_program, check_cwd, exit_message, expand_command, expand_dropped, expand_input,
+    find_command, format_badge, format_elapsed, forward_lines, is_available, is_not_found_exit,
+    open_log, parse_badge, post_webhook, preview_command, render_notification, run_command_probe,
+    run_probe, run_summary, select_candidate, select_hook, Badges, CloseHook, CommandExit,
+    CommandLocks, CommandRemap, EnabledCache, Inbox, InboxEntry, LaunchQueue, OutcomeRule,
+    OutputLine, OutputTail, Polls, Redactor, Retries, StatusLine, WebhookEvent,
+    DEFAULT_RETRY_DELAY_SECS,
+};
+use crate::config::{
+    find_preset_line, merged_commands, AppState, CmdVariant, ColorConfig, CommandConfig,
+    ConfigSources, Preset, ResolvedConfig, WindowSettings, GLOBAL_PRESET_NAME,
+};
+use crate::daemon::{DaemonListener, DaemonMessage, DaemonServer};
+use crate::platform::{
+    find_git_dir, focus_app, git_branch, notify, open_file_at_line, resolve_path_entries,
+    reveal_in_file_manager, run_in_session, spawn_bar, spawn_shell_command,
+    spawn_shell_command_logged, SessionLimits, Shell, ShellOptions, ShellSession,
+};
+use crate::script::{
+    resolve_script_type, run_script, ScriptConfig, ScriptError, ScriptResult, ScriptType,
+};
+#[cfg(feature = "global-hotkey")]
+use crate::ui::GlobalHotkeys;
+use crate::ui::{
+    bar_size, button_at, category_color, command_button, command_icon_text, corner_position,
+    drag_regions, global_hotkey_bindings, hotkey_bindings, interactive_regions, is_over_empty,
+    key_command, lerp, next_repaint, paint_badge, preset_color, result_accent,
+    result_accent_fading, start_command, title_bar_button, title_bar_buttons, vary_color_by_path,
+    Activity, ClickThrough, Fade, Palette, StartPosition, CLICK_THROUGH_RECHECK, COMPACT_SIZE,
+    TITLE_BAR_HEIGHT,
+};
+
+/// Minimum interval between status updates from streamed command output
+const OUTPUT_STATUS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long to wait for a pattern-checked command's output after it exits
+const OUTPUT_DRAIN_GRACE: Duration = Duration::from_secs(2);
+
+/// Duration of the focus/blur opacity fade
+const FOCUS_FADE_DURATION: Duration = Duration::from_millis(200);
+
+/// Duration of the hover opacity transition (hover_opacity)
+const HOVER_FADE_DURATION: Duration = Duration::from_millis(150);
+
+/// How long the pointer must rest on the compact dot before the bar expands
+const COMPACT_HOVER_DELAY: Duration = Duration::from_millis(400);
 
 /// Result from async script execution (internal)
 struct AsyncScriptResult {
@@ -25,6 +69,33 @@ struct AsyncScriptResult {
     message: String,
 }
 
+/// Result from async `enabled_when` evaluation (internal)
+struct EnabledResult {
+    index: usize,
+    generation: u64,
+    enabled: bool,
+}
+
+/// Result from async badge command (internal)
+struct BadgeResult {
+    index: usize,
+    generation: u64,
+    count: Option<u64>,
+}
+
+/// Result from async git branch lookup (internal)
+struct GitBranchResult {
+    dir: PathBuf,
+    branch: Option<String>,
+}
+
+/// Result from async health-check poll (internal)
+struct PollResult {
+    index: usize,
+    generation: u64,
+    success: bool,
+}
+
 /// Process execution result
 #[derive(Clone, Copy, PartialEq)]
 enum ProcessResult {
@@ -32,40 +103,217 @@ enum ProcessResult {
     Failed,
 }
 
+/// Watch a directory (non-recursively), setting `changed` on modifications
+fn watch_dir(
+    dir: &Path,
+    changed: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+) -> Option<RecommendedWatcher> {
+    notify::recommended_watcher(move |res: Result<notify::Event, _>| {
+        if let Ok(event) = res {
+            record_event(&event, &changed, &paused);
+        }
+    })
+    .ok()
+    .and_then(|mut w| {
+        w.watch(dir, RecursiveMode::NonRecursive).ok()?;
+        Some(w)
+    })
+}
+
+/// Set `changed` for a file event unless the watcher is paused
+fn record_event(event: &notify::Event, changed: &AtomicBool, paused: &AtomicBool) {
+    // Ignore metadata-only changes
+    if !paused.load(Ordering::SeqCst) && !matches!(event.kind, notify::EventKind::Access(_)) {
+        changed.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Watch the git directory of `dir`'s repository, setting `changed` when
+/// `HEAD` changes (branch switch)
+fn watch_git_head(dir: &Path, changed: Arc<AtomicBool>) -> Option<RecommendedWatcher> {
+    let git_dir = find_git_dir(dir)?;
+    notify::recommended_watcher(move |res: Result<notify::Event, _>| {
+        if let Ok(event) = res {
+            if event
+                .paths
+                .iter()
+                .any(|path| path.file_name().is_some_and(|name| name == "HEAD"))
+            {
+                changed.store(true, Ordering::SeqCst);
+            }
+        }
+    })
+    .ok()
+    .and_then(|mut w| {
+        w.watch(&git_dir, RecursiveMode::NonRecursive).ok()?;
+        Some(w)
+    })
+}
+
+/// Current time in unix seconds
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 /// Main application state
 pub struct LaunchBarApp {
     commands: Vec<CommandConfig>,
     working_dir: PathBuf,
     working_dir_str: String,
-    last_status: Option<String>,
-    is_error: bool,
+    /// Command shortcuts, most specific first (see `ui::hotkey`)
+    hotkeys: Vec<(egui::KeyboardShortcut, usize)>,
+    /// System-wide `global_hotkey`s; None where the platform refuses them
+    #[cfg(feature = "global-hotkey")]
+    global_hotkeys: Option<GlobalHotkeys>,
+    status: StatusLine,
+    /// Hides secrets in the status line and tooltips
+    redactor: Redactor,
     opacity: f32,
+    blur_opacity: Option<f32>,
+    focus_fade: Fade,
+    hover_opacity: Option<f32>,
+    hover_fade: Fade,
     base_color: egui::Color32,
     border: String,
     title_bar: String,
     accent_line: String,
+    // Color the accent line by the last result (accent_from_result)
+    accent_from_result: bool,
+    // Collapse the status line while several commands run (summary_status)
+    summary_status: bool,
+    // Hovered commands shown expanded (preview_expanded), cached per hover
+    preview_expanded: bool,
+    hover_preview: Option<(usize, String)>,
+    // Editor for the config (window.editor); None uses $EDITOR / the OS default
+    editor: Option<String>,
+    // Results are POSTed here (window.webhook_url)
+    webhook_url: Option<String>,
+    last_result: Option<(ProcessResult, Instant)>,
     saved_position: Option<egui::Pos2>,
     state: AppState,
+    state_save_warned: bool,
     preset_name: Option<String>,
     config_path: PathBuf,
+    sources: ConfigSources,
     script_config: ScriptConfig,
     // Process tracking
     running_processes: HashMap<usize, std::process::Child>,
     process_results: HashMap<usize, ProcessResult>,
     running_scripts: std::collections::HashSet<usize>,
+    started_at: HashMap<usize, Instant>,
+    // Commands waiting for a slot (max_concurrent)
+    launch_queue: LaunchQueue,
+    // Named `lock`s held by running commands
+    locks: CommandLocks,
+    // Failed commands waiting to run again (retries)
+    retries: Retries,
+    // Running command whose elapsed time is shown in the status line
+    status_timer: Option<usize>,
     script_rx: Receiver<AsyncScriptResult>,
     script_tx: Sender<AsyncScriptResult>,
+    output_tail: OutputTail,
+    // Cap on captured output per command (window.max_output_bytes)
+    max_output_bytes: usize,
+    output_rx: Receiver<OutputLine>,
+    output_tx: Sender<OutputLine>,
+    /// Persistent shell for `session` commands of the current preset
+    shell_session: Arc<Mutex<Option<ShellSession>>>,
+    // Set to stop the command running in `shell_session` when it is replaced
+    session_cancel: Arc<AtomicBool>,
+    // Output readers of commands with success/failure patterns, and when they exited
+    output_readers: HashMap<usize, Vec<JoinHandle<()>>>,
+    exited_at: HashMap<usize, Instant>,
+    // Candidate (0 = `cmd`, then `fallback`) running for commands with fallbacks
+    fallback_used: HashMap<usize, usize>,
+    // Modifier variant clicked for running or queued commands
+    variants: HashMap<usize, CmdVariant>,
+    // Files dropped onto running or queued commands ($dropped)
+    dropped: HashMap<usize, Vec<PathBuf>>,
+    // Command waiting for its `prompt` value, and the text typed so far
+    input_pending: Option<usize>,
+    input_buffer: String,
+    // Values entered for running or queued commands ($input)
+    inputs: HashMap<usize, String>,
+    // Enable conditions (enabled_when)
+    enabled_cache: EnabledCache,
+    enabled_rx: Receiver<EnabledResult>,
+    enabled_tx: Sender<EnabledResult>,
+    // Icon badges
+    badges: Badges,
+    badge_interval: Duration,
+    badge_rx: Receiver<BadgeResult>,
+    badge_tx: Sender<BadgeResult>,
+    // Health-check polling (poll_secs)
+    polls: Polls,
+    poll_rx: Receiver<PollResult>,
+    poll_tx: Sender<PollResult>,
     // File watcher for highlight
     file_changed: Arc<AtomicBool>,
     highlight_until: Option<Instant>,
     #[allow(dead_code)]
     watcher: Option<RecommendedWatcher>,
+    // Git branch in the title bar (show_git_branch)
+    show_git_branch: bool,
+    git_branch: Option<String>,
+    git_branch_rx: Receiver<GitBranchResult>,
+    git_branch_tx: Sender<GitBranchResult>,
+    git_head_changed: Arc<AtomicBool>,
+    #[allow(dead_code)]
+    git_watcher: Option<RecommendedWatcher>,
     // Preset switching
     all_presets: Vec<Preset>,
     preset_order: Vec<usize>,
     current_preset_idx: usize,
     max_icons: usize,
+    // Append `[Global]` commands to each preset's (`window.merge_global`)
+    merge_global: bool,
     global_default_script: Option<ScriptType>,
+    login_shell: bool,
+    default_shell: Option<Shell>,
+    path_prepend: Vec<PathBuf>,
+    // window.on_close, taken when it runs so it fires once
+    close_hook: Option<CloseHook>,
+    drag_anywhere: bool,
+    padding: f32,
+    spacing: f32,
+    // Repeat last command (Enter)
+    last_command: Option<usize>,
+    remember_last_command: bool,
+    // Repaint cadence (poll_interval_ms, max_fps)
+    poll_interval: Duration,
+    max_fps: u32,
+    // Colors (theme, high_contrast); "system" is re-resolved every frame
+    theme: String,
+    high_contrast: bool,
+    colors: ColorConfig,
+    // OS light/dark preference seen on the last frame (for theme = "system")
+    system_theme: Option<egui::Theme>,
+    palette: Palette,
+    click_through: Option<ClickThrough>,
+    // Compact mode (collapsed to a single dot)
+    compact: bool,
+    compact_expanded: bool,
+    compact_hover_since: Option<Instant>,
+    // Startup placement, applied once the monitor size is known
+    pending_start: Option<StartPosition>,
+    // Recent directories picker (Ctrl/Cmd+R)
+    recent_picker: Option<Vec<String>>,
+    // Results of commands that finished in the background
+    inbox: Inbox,
+    inbox_open: bool,
+    // `then_preset` of a finished command, switched to once all results of the
+    // frame are handled (they refer to the current command list)
+    pending_preset: Option<String>,
+    // Category dot colors (`[categories]`)
+    categories: HashMap<String, String>,
+    // Directory changes from `launch-bar cd` (--daemon)
+    daemon_rx: Option<Receiver<DaemonMessage>>,
+    // Keeps the daemon socket served; dropped on exit
+    daemon_server: Option<DaemonServer>,
 }
 
 impl LaunchBarApp {
@@ -81,79 +329,261 @@ impl LaunchBarApp {
         script_config: ScriptConfig,
         all_presets: Vec<Preset>,
         detected_preset_idx: Option<usize>,
+        categories: HashMap<String, String>,
+        sources: ConfigSources,
     ) -> Self {
         egui_cha_ds::setup_fonts(&cc.egui_ctx);
-        let working_dir_str = working_dir.to_string_lossy().to_string();
-        let state = AppState::load();
+        let system_theme = cc.egui_ctx.system_theme();
 
         // Set immediate tooltip
         cc.egui_ctx.style_mut(|style| {
             style.interaction.tooltip_delay = 0.0;
         });
 
+        let mut app = Self::with_state(
+            commands,
+            window,
+            base_color,
+            working_dir,
+            preset_name,
+            config_path,
+            script_config,
+            all_presets,
+            detected_preset_idx,
+            categories,
+            sources,
+            AppState::load(),
+        );
+        app.system_theme = system_theme;
+        #[cfg(feature = "global-hotkey")]
+        {
+            app.global_hotkeys = GlobalHotkeys::new(&cc.egui_ctx);
+        }
+        app.register_global_hotkeys();
+
         // Restore saved position
-        if let Some(pos) = state.get_position(&working_dir_str) {
+        if let Some(pos) = app.state.get_position(&app.working_dir_str) {
             cc.egui_ctx
                 .send_viewport_cmd(egui::ViewportCommand::OuterPosition(pos));
         }
 
+        // Restore compact mode
+        if app.compact {
+            cc.egui_ctx
+                .send_viewport_cmd(egui::ViewportCommand::InnerSize(COMPACT_SIZE));
+        }
+
+        app.state.touch_recent(&app.working_dir_str, unix_now());
+        app.save_state();
+
+        app
+    }
+
+    /// Build the app state without touching the egui context
+    #[allow(clippy::too_many_arguments)]
+    fn with_state(
+        commands: Vec<CommandConfig>,
+        window: WindowSettings,
+        base_color: egui::Color32,
+        working_dir: PathBuf,
+        preset_name: Option<String>,
+        config_path: PathBuf,
+        script_config: ScriptConfig,
+        all_presets: Vec<Preset>,
+        detected_preset_idx: Option<usize>,
+        categories: HashMap<String, String>,
+        sources: ConfigSources,
+        state: AppState,
+    ) -> Self {
+        let working_dir_str = working_dir.to_string_lossy().to_string();
+        let compact = state.is_compact(&working_dir_str);
+
         // Set up file watcher
         let file_changed = Arc::new(AtomicBool::new(false));
-        let file_changed_clone = file_changed.clone();
-        let watch_dir = working_dir.clone();
-
-        let watcher = notify::recommended_watcher(move |res: Result<notify::Event, _>| {
-            if let Ok(event) = res {
-                // Ignore metadata-only changes
-                if !matches!(event.kind, notify::EventKind::Access(_)) {
-                    file_changed_clone.store(true, Ordering::SeqCst);
-                }
-            }
-        })
-        .ok()
-        .and_then(|mut w| {
-            w.watch(&watch_dir, RecursiveMode::NonRecursive).ok()?;
-            Some(w)
-        });
+        let watcher = watch_dir(
+            &working_dir,
+            file_changed.clone(),
+            script_config.watcher_paused.clone(),
+        );
 
         let (script_tx, script_rx) = mpsc::channel();
+        let (output_tx, output_rx) = mpsc::channel();
+        let (enabled_tx, enabled_rx) = mpsc::channel();
+        let (badge_tx, badge_rx) = mpsc::channel();
+        let (git_branch_tx, git_branch_rx) = mpsc::channel();
+        let (poll_tx, poll_rx) = mpsc::channel();
 
         // Build preset switching order: detected -> global -> others
         let preset_order = Self::build_preset_order(&all_presets, detected_preset_idx);
         let current_preset_idx = 0;
 
+        let badge_interval = Duration::from_secs(window.badge_interval_secs);
         let max_icons = window.max_icons;
         let global_default_script = window.default_script;
-
-        Self {
+        let login_shell = window.login_shell;
+        let path_prepend = resolve_path_entries(&window.path_prepend, &working_dir);
+        let close_hook = CloseHook::new(
+            window.on_close.as_deref(),
+            &working_dir,
+            ShellOptions {
+                login: login_shell,
+                path_prepend: path_prepend.clone(),
+                shell: window.default_shell,
+                detach: false,
+                elevate: false,
+                nice: None,
+            },
+        );
+        let last_command = window
+            .remember_last_command
+            .then(|| state.get_last_command(&working_dir_str))
+            .flatten()
+            .and_then(|name| find_command(&commands, name));
+        let hotkeys = hotkey_bindings(&commands);
+
+        let mut app = Self {
             commands,
+            hotkeys,
+            #[cfg(feature = "global-hotkey")]
+            global_hotkeys: None,
             working_dir,
             working_dir_str,
-            last_status: None,
-            is_error: false,
+            status: StatusLine::new(),
+            redactor: Redactor::new(&window.redact_patterns),
             opacity: window.opacity,
+            blur_opacity: window.blur_opacity,
+            focus_fade: Fade::new(1.0, FOCUS_FADE_DURATION, Instant::now()),
+            hover_opacity: window.hover_opacity,
+            hover_fade: Fade::new(0.0, HOVER_FADE_DURATION, Instant::now()),
             base_color,
             border: window.border,
             title_bar: window.title_bar,
             accent_line: window.accent_line,
+            accent_from_result: window.accent_from_result,
+            summary_status: window.summary_status,
+            preview_expanded: window.preview_expanded,
+            hover_preview: None,
+            editor: window.editor.clone(),
+            webhook_url: window.webhook_url.clone(),
+            last_result: None,
             saved_position: None,
             state,
+            state_save_warned: false,
             preset_name,
             config_path,
+            sources,
             script_config,
             running_processes: HashMap::new(),
             process_results: HashMap::new(),
             running_scripts: std::collections::HashSet::new(),
+            started_at: HashMap::new(),
+            status_timer: None,
             script_rx,
             script_tx,
+            output_tail: OutputTail::new(),
+            max_output_bytes: window.max_output_bytes,
+            output_rx,
+            output_tx,
+            shell_session: Arc::new(Mutex::new(None)),
+            session_cancel: Arc::default(),
+            output_readers: HashMap::new(),
+            exited_at: HashMap::new(),
+            fallback_used: HashMap::new(),
+            variants: HashMap::new(),
+            dropped: HashMap::new(),
+            input_pending: None,
+            input_buffer: String::new(),
+            inputs: HashMap::new(),
+            enabled_cache: EnabledCache::new(),
+            enabled_rx,
+            enabled_tx,
+            badges: Badges::new(),
+            badge_interval,
+            badge_rx,
+            badge_tx,
+            polls: Polls::new(),
+            launch_queue: LaunchQueue::new(window.max_concurrent),
+            locks: CommandLocks::default(),
+            retries: Retries::default(),
+            poll_rx,
+            poll_tx,
             file_changed,
             highlight_until: None,
             watcher,
+            show_git_branch: window.show_git_branch,
+            git_branch: None,
+            git_branch_rx,
+            git_branch_tx,
+            git_head_changed: Arc::new(AtomicBool::new(false)),
+            git_watcher: None,
             all_presets,
             preset_order,
             current_preset_idx,
             max_icons,
+            merge_global: window.merge_global,
             global_default_script,
+            login_shell,
+            default_shell: window.default_shell,
+            close_hook,
+            path_prepend,
+            drag_anywhere: window.drag_anywhere,
+            padding: window.padding,
+            spacing: window.spacing,
+            last_command,
+            remember_last_command: window.remember_last_command,
+            poll_interval: Duration::from_millis(window.poll_interval_ms),
+            max_fps: window.max_fps,
+            theme: window.theme.clone(),
+            high_contrast: window.high_contrast,
+            system_theme: None,
+            colors: window.colors.clone(),
+            palette: Palette::new(&window.theme, window.high_contrast, None)
+                .with_colors(&window.colors),
+            click_through: window.click_through.then(ClickThrough::new),
+            compact,
+            compact_expanded: false,
+            compact_hover_since: None,
+            pending_start: Some(StartPosition::parse(&window.start_position))
+                .filter(|start| *start != StartPosition::Saved),
+            recent_picker: None,
+            inbox: Inbox::default(),
+            categories,
+            inbox_open: false,
+            pending_preset: None,
+            daemon_rx: None,
+            daemon_server: None,
+        };
+        app.refresh_git_branch();
+        app
+    }
+
+    /// Re-read the git branch and watch `.git/HEAD` if `show_git_branch` is on
+    fn refresh_git_branch(&mut self) {
+        if self.show_git_branch {
+            self.probe_git_branch();
+            self.git_watcher = watch_git_head(&self.working_dir, self.git_head_changed.clone());
+        } else {
+            self.git_branch = None;
+            self.git_watcher = None;
+        }
+    }
+
+    /// Look up the git branch on a background thread (see `check_git_branch`)
+    fn probe_git_branch(&self) {
+        let dir = self.working_dir.clone();
+        let tx = self.git_branch_tx.clone();
+        std::thread::spawn(move || {
+            let branch = git_branch(&dir);
+            let _ = tx.send(GitBranchResult { dir, branch });
+        });
+    }
+
+    fn check_git_branch(&mut self) {
+        while let Ok(result) = self.git_branch_rx.try_recv() {
+            // Ignore lookups for a directory the bar has since left
+            if self.show_git_branch && result.dir == self.working_dir {
+                self.git_branch = result.branch;
+            }
         }
     }
 
@@ -190,24 +620,47 @@ impl LaunchBarApp {
         }
 
         // Move to next preset in order (wrap around)
-        self.current_preset_idx = (self.current_preset_idx + 1) % self.preset_order.len();
-        let preset_idx = self.preset_order[self.current_preset_idx];
+        self.switch_to_order((self.current_preset_idx + 1) % self.preset_order.len());
+    }
+
+    /// Switch to the preset named `name` (a command's `then_preset`, case-insensitive);
+    /// false if there's none
+    fn switch_to_preset_named(&mut self, name: &str) -> bool {
+        let position = self.preset_order.iter().position(|&idx| {
+            self.all_presets
+                .get(idx)
+                .is_some_and(|preset| preset.name.eq_ignore_ascii_case(name))
+        });
+        match position {
+            Some(position) => {
+                self.switch_to_order(position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Switch to the preset at `position` in the cycle order
+    fn switch_to_order(&mut self, position: usize) {
+        self.current_preset_idx = position;
+        let preset_idx = self.preset_order[position];
 
         if let Some(preset) = self.all_presets.get(preset_idx) {
-            // Update commands
-            self.commands = preset
-                .commands
+            // Update commands, with the global ones as on startup
+            let global = self
+                .all_presets
                 .iter()
-                .take(self.max_icons)
-                .cloned()
-                .collect();
+                .find(|p| p.name == GLOBAL_PRESET_NAME);
+            let mut commands = merged_commands(preset, global, self.merge_global);
+            commands.truncate(self.max_icons);
+            self.commands = commands;
 
             // Update base color
             self.base_color = preset
                 .base_color
                 .as_ref()
-                .and_then(|c| parse_hex_color(c))
-                .unwrap_or(palette::BASE_BG);
+                .and_then(|c| preset_color(c, &self.working_dir))
+                .unwrap_or(self.palette.base_bg);
 
             // Update preset name
             self.preset_name = Some(preset.name.clone());
@@ -216,24 +669,658 @@ impl LaunchBarApp {
             self.script_config = ScriptConfig {
                 global_default: self.global_default_script,
                 preset_default: preset.default_script,
+                ai: self.script_config.ai.clone(),
+                read_file_max_bytes: self.script_config.read_file_max_bytes,
+                prelude: preset.script_prelude.clone(),
+                detach: false,
+                watcher_paused: self.script_config.watcher_paused.clone(),
+            };
+
+            let message = format!("Switched to: {}", preset.name);
+            self.reset_command_state();
+            self.set_status(message, false);
+        }
+    }
+
+    /// Clear per-command runtime state after the command list changes
+    fn reset_command_state(&mut self) {
+        if self.watcher_pausers_running(None) {
+            self.script_config
+                .watcher_paused
+                .store(false, Ordering::SeqCst);
+        }
+        self.hotkeys = hotkey_bindings(&self.commands);
+        self.register_global_hotkeys();
+        self.hover_preview = None;
+        self.enabled_cache.clear();
+        self.badges.clear();
+        self.polls.clear();
+        self.running_processes.clear();
+        self.process_results.clear();
+        self.running_scripts.clear();
+        self.started_at.clear();
+        self.output_tail.clear();
+        self.output_readers.clear();
+        self.exited_at.clear();
+        self.fallback_used.clear();
+        self.variants.clear();
+        self.dropped.clear();
+        self.input_pending = None;
+        self.inputs.clear();
+        self.launch_queue.clear();
+        self.retries.clear();
+        self.locks.clear();
+        // Stop a command still running in the old session, then start afresh
+        self.session_cancel.store(true, Ordering::SeqCst);
+        self.session_cancel = Arc::default();
+        self.shell_session = Arc::new(Mutex::new(None));
+        self.last_result = None;
+        self.last_command = None;
+    }
+
+    /// Carry per-command state over to a reloaded command list of the same preset
+    ///
+    /// Results follow unchanged commands to their new position; running state
+    /// is kept only for commands that also stayed in place (see
+    /// `CommandRemap::is_stable`). Everything else is dropped like on a preset switch.
+    fn remap_command_state(&mut self, remap: &CommandRemap) {
+        let stable = |index: usize| remap.is_stable(index);
+        let dropped_running = self
+            .running_processes
+            .keys()
+            .chain(self.running_scripts.iter())
+            .any(|&index| !stable(index));
+        self.hotkeys = hotkey_bindings(&self.commands);
+        self.register_global_hotkeys();
+        self.hover_preview = None;
+        self.enabled_cache.clear();
+        self.badges.clear();
+        self.polls.clear();
+        self.running_processes.retain(|&index, _| stable(index));
+        self.running_scripts.retain(|&index| stable(index));
+        self.started_at.retain(|&index, _| stable(index));
+        self.output_tail.retain(stable);
+        self.output_readers.retain(|&index, _| stable(index));
+        self.exited_at.retain(|&index, _| stable(index));
+        self.fallback_used.retain(|&index, _| stable(index));
+        self.variants.retain(|&index, _| stable(index));
+        self.dropped.retain(|&index, _| stable(index));
+        self.inputs.retain(|&index, _| stable(index));
+        self.input_pending = self.input_pending.filter(|&index| stable(index));
+        self.locks.retain(stable);
+        self.retries.retain(stable);
+        self.status_timer = self.status_timer.filter(|&index| stable(index));
+        remap.apply(&mut self.process_results);
+        self.launch_queue.remap(|index| remap.get(index));
+        self.last_command = self.last_command.and_then(|index| remap.get(index));
+        // A dropped `pause_watcher` command can no longer resume the watcher
+        if dropped_running && !self.watcher_pausers_running(None) {
+            self.script_config
+                .watcher_paused
+                .store(false, Ordering::SeqCst);
+        }
+    }
+
+    /// Run `window.on_close` once, waiting up to its timeout
+    fn run_close_hook(&mut self) {
+        if let Some(hook) = self.close_hook.take() {
+            match hook.run() {
+                Ok(true) => {}
+                Ok(false) => eprintln!("[warn] on_close hook failed"),
+                Err(e) => eprintln!("[warn] on_close hook: {}", e),
+            }
+        }
+    }
+
+    /// Open the config file, at the active preset's section when it can be found
+    fn open_config(&self) {
+        let line = self.preset_name.as_deref().and_then(|name| {
+            let src = std::fs::read_to_string(&self.config_path).ok()?;
+            find_preset_line(&src, name)
+        });
+        open_file_at_line(&self.config_path, line, self.editor.as_deref());
+    }
+
+    /// Re-read and re-resolve the config, keeping the current preset by name
+    ///
+    /// On failure the current config stays active and the error is shown in the status line.
+    pub fn reload_config(&mut self) {
+        let resolved = match self.sources.reload() {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                self.set_status(format!("Reload failed: {}", e), true);
+                return;
+            }
+        };
+        if resolved.presets.is_empty() {
+            self.set_status("Reload failed: no presets defined", true);
+            return;
+        }
+
+        let keep = self.preset_name.clone();
+        let name = self.apply_config(&resolved, keep.as_deref());
+        self.set_status(format!("Reloaded: {}", name), false);
+    }
+
+    /// Rebuild every field as if freshly launched, keeping the window where it is
+    ///
+    /// Broader than `reload_config`: the preset is re-detected and the watchers,
+    /// channels and running state are recreated. Running commands are left to
+    /// finish on their own. On failure the current app stays as it was.
+    pub fn restart(&mut self) -> Result<String, String> {
+        let resolved = self.sources.reload()?;
+        if resolved.presets.is_empty() {
+            return Err("no presets defined".to_string());
+        }
+
+        let mut fresh = Self::with_state(
+            Vec::new(),
+            resolved.window.clone(),
+            Palette::new(
+                &resolved.window.theme,
+                resolved.window.high_contrast,
+                self.system_theme,
+            )
+            .base_bg,
+            self.working_dir.clone(),
+            None,
+            self.sources.active_path(),
+            ScriptConfig::default(),
+            Vec::new(),
+            None,
+            resolved.categories.clone(),
+            self.sources.clone(),
+            std::mem::take(&mut self.state),
+        );
+        fresh.system_theme = self.system_theme;
+        let name = fresh.apply_config(&resolved, None);
+        fresh.pending_start = None;
+        fresh.daemon_rx = self.daemon_rx.take();
+        fresh.daemon_server = self.daemon_server.take();
+        #[cfg(feature = "global-hotkey")]
+        {
+            fresh.global_hotkeys = self.global_hotkeys.take();
+        }
+        fresh.register_global_hotkeys();
+        *self = fresh;
+        Ok(name)
+    }
+
+    /// Move the bar to another directory: re-resolve config, presets and the watcher
+    pub fn switch_working_dir(&mut self, dir: PathBuf) -> Result<(), String> {
+        check_cwd(&dir)?;
+        let previous = std::mem::replace(&mut self.sources.local_path, dir.join("launch-bar.toml"));
+        let resolved = match self.sources.reload() {
+            Ok(resolved) if !resolved.presets.is_empty() => resolved,
+            Ok(_) => {
+                self.sources.local_path = previous;
+                return Err("no presets defined".to_string());
+            }
+            Err(e) => {
+                self.sources.local_path = previous;
+                return Err(e);
+            }
+        };
+
+        self.working_dir = dir;
+        self.working_dir_str = self.working_dir.to_string_lossy().to_string();
+        self.file_changed.store(false, Ordering::SeqCst);
+        self.watcher = watch_dir(
+            &self.working_dir,
+            self.file_changed.clone(),
+            self.script_config.watcher_paused.clone(),
+        );
+        self.highlight_until = None;
+        self.saved_position = None;
+        self.compact = self.state.is_compact(&self.working_dir_str);
+        self.compact_expanded = false;
+
+        let name = self.apply_config(&resolved, None);
+        self.state.touch_recent(&self.working_dir_str, unix_now());
+        self.save_state();
+        self.set_status(format!("{} ({})", self.working_dir_str, name), false);
+        Ok(())
+    }
+
+    /// Switch to a directory and restore its window position and size
+    fn open_working_dir(&mut self, ctx: &egui::Context, dir: PathBuf) {
+        let display = dir.display().to_string();
+        match self.switch_working_dir(dir) {
+            Ok(()) => {
+                if let Some(pos) = self.state.get_position(&self.working_dir_str) {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(pos));
+                }
+                self.resize_for_compact(ctx);
+            }
+            Err(e) => self.set_status(format!("Cannot open {}: {}", display, e), true),
+        }
+    }
+
+    /// Move the window to `start_position` once the viewport info is available
+    fn apply_start_position(&mut self, ctx: &egui::Context) {
+        let Some(start) = self.pending_start else {
+            return;
+        };
+        let (monitor, outer_rect, pointer) = ctx.input(|i| {
+            (
+                i.viewport().monitor_size,
+                i.viewport().outer_rect,
+                i.pointer.latest_pos(),
+            )
+        });
+        let (Some(monitor), Some(outer_rect)) = (monitor, outer_rect) else {
+            return;
+        };
+        self.pending_start = None;
+        let cursor = pointer.map(|pos| outer_rect.min + pos.to_vec2());
+        if let Some(command) = start_command(start, monitor, outer_rect.size(), cursor) {
+            ctx.send_viewport_cmd(command);
+        }
+    }
+
+    /// Follow directories sent by `launch-bar cd` clients
+    pub fn serve_daemon(&mut self, listener: DaemonListener, ctx: &egui::Context) {
+        let (tx, rx) = mpsc::channel();
+        let ctx = ctx.clone();
+        let server = listener.serve(move |message| {
+            if tx.send(message).is_ok() {
+                ctx.request_repaint();
+            }
+        });
+        self.daemon_rx = Some(rx);
+        self.daemon_server = Some(server);
+    }
+
+    /// Register the commands' `global_hotkey`s system-wide, replacing the previous ones
+    fn register_global_hotkeys(&mut self) {
+        let bindings = global_hotkey_bindings(&self.commands);
+        #[cfg(feature = "global-hotkey")]
+        if let Some(ref mut global_hotkeys) = self.global_hotkeys {
+            global_hotkeys.register(&bindings);
+        }
+        #[cfg(not(feature = "global-hotkey"))]
+        if !bindings.is_empty() {
+            eprintln!("[warn] global_hotkey needs a build with the `global-hotkey` feature");
+        }
+    }
+
+    /// Run the commands whose global hotkey was pressed, focused or not
+    #[cfg(feature = "global-hotkey")]
+    fn check_global_hotkeys(&mut self) {
+        let pressed = match self.global_hotkeys {
+            Some(ref global_hotkeys) => global_hotkeys.pressed(),
+            None => return,
+        };
+        for index in pressed {
+            self.run_command(index);
+        }
+    }
+
+    fn check_daemon(&mut self, ctx: &egui::Context) {
+        let messages: Vec<_> = match self.daemon_rx {
+            Some(ref rx) => rx.try_iter().collect(),
+            None => return,
+        };
+        // Only the latest directory matters when several arrive at once
+        if let Some(DaemonMessage::Cd(dir)) = messages.into_iter().last() {
+            if dir != self.working_dir {
+                self.open_working_dir(ctx, dir);
+            }
+        }
+    }
+
+    /// Apply a resolved config in place, returning the active preset's name
+    ///
+    /// The preset named `keep` stays active if it still exists; otherwise the
+    /// preset detected for the working directory is used.
+    fn apply_config(&mut self, resolved: &ResolvedConfig, keep: Option<&str>) -> String {
+        // Window settings
+        let window = &resolved.window;
+        self.opacity = window.opacity;
+        self.blur_opacity = window.blur_opacity;
+        self.hover_opacity = window.hover_opacity;
+        self.border = window.border.clone();
+        self.title_bar = window.title_bar.clone();
+        self.accent_line = window.accent_line.clone();
+        self.accent_from_result = window.accent_from_result;
+        self.summary_status = window.summary_status;
+        self.preview_expanded = window.preview_expanded;
+        self.editor = window.editor.clone();
+        self.webhook_url = window.webhook_url.clone();
+        self.drag_anywhere = window.drag_anywhere;
+        self.show_git_branch = window.show_git_branch;
+        self.refresh_git_branch();
+        self.launch_queue.set_limit(window.max_concurrent);
+        self.remember_last_command = window.remember_last_command;
+        self.poll_interval = Duration::from_millis(window.poll_interval_ms);
+        self.max_fps = window.max_fps;
+        self.theme = window.theme.clone();
+        self.high_contrast = window.high_contrast;
+        self.colors = window.colors.clone();
+        self.palette = Palette::new(&window.theme, window.high_contrast, self.system_theme)
+            .with_colors(&window.colors);
+        self.padding = window.padding;
+        self.spacing = window.spacing;
+        // Reloading is triggered by a click, so passthrough is currently off
+        self.click_through = window.click_through.then(ClickThrough::new);
+        self.max_icons = window.max_icons;
+        self.merge_global = window.merge_global;
+        self.global_default_script = window.default_script;
+        self.badge_interval = Duration::from_secs(window.badge_interval_secs);
+        self.max_output_bytes = window.max_output_bytes;
+        self.login_shell = window.login_shell;
+        self.default_shell = window.default_shell;
+        self.path_prepend = resolve_path_entries(&window.path_prepend, &self.working_dir);
+        self.redactor = Redactor::new(&window.redact_patterns);
+        self.close_hook = CloseHook::new(
+            window.on_close.as_deref(),
+            &self.working_dir,
+            ShellOptions {
+                login: self.login_shell,
+                path_prepend: self.path_prepend.clone(),
+                shell: self.default_shell,
+                detach: false,
+                elevate: false,
+                nice: None,
+            },
+        );
+
+        // Presets: keep the current one if it still exists, else re-detect
+        let detected_idx = resolved.detect_preset(&self.working_dir);
+        let preset_idx = keep
+            .and_then(|name| resolved.find_preset(name))
+            .or(detected_idx)
+            .unwrap_or(0);
+        self.all_presets = resolved.presets();
+        self.categories = resolved.categories.clone();
+        self.preset_order = Self::build_preset_order(&self.all_presets, detected_idx);
+        self.current_preset_idx = self
+            .preset_order
+            .iter()
+            .position(|&idx| idx == preset_idx)
+            .unwrap_or(0);
+
+        let preset = &self.all_presets[preset_idx];
+        let commands: Vec<CommandConfig> = resolved
+            .commands_for(preset_idx)
+            .into_iter()
+            .take(self.max_icons)
+            .collect();
+        // Staying on the same preset keeps the state of unchanged commands
+        let remap = (self.preset_name.as_deref() == Some(preset.name.as_str()))
+            .then(|| CommandRemap::new(&self.commands, &commands));
+        self.commands = commands;
+        self.base_color = preset
+            .base_color
+            .as_ref()
+            .and_then(|c| preset_color(c, &self.working_dir))
+            .unwrap_or(self.palette.base_bg);
+        self.preset_name = Some(preset.name.clone());
+        self.script_config = ScriptConfig {
+            global_default: self.global_default_script,
+            preset_default: preset.default_script,
+            ai: window.ai.clone(),
+            read_file_max_bytes: window.read_file_max_bytes,
+            prelude: preset.script_prelude.clone(),
+            detach: false,
+            watcher_paused: self.script_config.watcher_paused.clone(),
+        };
+        let name = preset.name.clone();
+        self.config_path = self.sources.active_path();
+
+        match remap {
+            Some(remap) => self.remap_command_state(&remap),
+            None => self.reset_command_state(),
+        }
+        name
+    }
+
+    /// Whether the active preset is display-only (`readonly`)
+    fn is_readonly(&self) -> bool {
+        self.preset_order
+            .get(self.current_preset_idx)
+            .and_then(|&idx| self.all_presets.get(idx))
+            .is_some_and(|preset| preset.readonly)
+    }
+
+    /// Whether a command's `enabled_when` predicate currently allows it to run
+    fn is_command_enabled(&self, index: usize) -> bool {
+        match self.commands.get(index) {
+            Some(cmd) if cmd.enabled_when.is_some() => {
+                self.enabled_cache.get(index).unwrap_or(false)
+            }
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    /// Start background evaluation of stale `enabled_when` predicates
+    fn refresh_enabled(&mut self) {
+        let now = Instant::now();
+        for (index, cmd) in self.commands.iter().enumerate() {
+            let Some(ref predicate) = cmd.enabled_when else {
+                continue;
+            };
+            if !self.enabled_cache.needs_eval(index, now) {
+                continue;
+            }
+
+            let generation = self.enabled_cache.mark_requested(index);
+            let predicate = predicate.clone();
+            let cwd = cmd
+                .cwd
+                .as_ref()
+                .map(PathBuf::from)
+                .unwrap_or_else(|| self.working_dir.clone());
+            let script_config = self.script_config.clone();
+            let tx = self.enabled_tx.clone();
+
+            std::thread::spawn(move || {
+                let output = run_probe(&predicate, &cwd, &script_config);
+                let _ = tx.send(EnabledResult {
+                    index,
+                    generation,
+                    enabled: output.success,
+                });
+            });
+        }
+    }
+
+    fn check_enabled(&mut self) {
+        while let Ok(result) = self.enabled_rx.try_recv() {
+            self.enabled_cache
+                .set(result.index, result.enabled, result.generation);
+        }
+    }
+
+    /// Start background badge commands when a refresh is due
+    fn refresh_badges(&mut self) {
+        if !self.badges.is_due(self.badge_interval) {
+            return;
+        }
+        let generation = self.badges.start_refresh();
+
+        for (index, cmd) in self.commands.iter().enumerate() {
+            let Some(ref badge) = cmd.badge else {
+                continue;
+            };
+            if !self.badges.mark_in_flight(index) {
+                continue;
+            }
+
+            let badge = badge.clone();
+            let cwd = cmd
+                .cwd
+                .as_ref()
+                .map(PathBuf::from)
+                .unwrap_or_else(|| self.working_dir.clone());
+            let script_config = self.script_config.clone();
+            let tx = self.badge_tx.clone();
+
+            std::thread::spawn(move || {
+                let output = run_probe(&badge, &cwd, &script_config);
+                let _ = tx.send(BadgeResult {
+                    index,
+                    generation,
+                    count: parse_badge(&output.stdout),
+                });
+            });
+        }
+    }
+
+    fn check_badges(&mut self) {
+        while let Ok(result) = self.badge_rx.try_recv() {
+            self.badges
+                .set(result.index, result.count, result.generation);
+        }
+    }
+
+    /// Start background health-check polls that are due
+    fn refresh_polls(&mut self) {
+        let now = Instant::now();
+        for (index, cmd) in self.commands.iter().enumerate() {
+            if let Some(secs) = cmd.poll_secs.filter(|&s| s > 0) {
+                self.polls.ensure(index, Duration::from_secs(secs), now);
+            }
+        }
+
+        let (due, generation) = self.polls.start_due(now);
+        for index in due {
+            let Some(cmd) = self.commands.get(index).cloned() else {
+                continue;
             };
+            let cwd = cmd
+                .cwd
+                .as_ref()
+                .map(PathBuf::from)
+                .unwrap_or_else(|| self.working_dir.clone());
+            let script_config = self.script_config.clone();
+            let tx = self.poll_tx.clone();
+
+            std::thread::spawn(move || {
+                let success = run_command_probe(&cmd, &cwd, &script_config);
+                let _ = tx.send(PollResult {
+                    index,
+                    generation,
+                    success,
+                });
+            });
+        }
+    }
 
-            // Clear running state
-            self.running_processes.clear();
-            self.process_results.clear();
-            self.running_scripts.clear();
-            self.last_status = Some(format!("Switched to: {}", preset.name));
-            self.is_error = false;
+    fn check_polls(&mut self) {
+        while let Ok(result) = self.poll_rx.try_recv() {
+            if !self.polls.record(
+                result.index,
+                result.success,
+                result.generation,
+                Instant::now(),
+            ) {
+                continue;
+            }
+
+            // Don't override the state of a manual run in progress
+            let is_running = self.running_processes.contains_key(&result.index)
+                || self.running_scripts.contains(&result.index);
+            let Some(cmd) = self.commands.get(result.index).filter(|_| !is_running) else {
+                continue;
+            };
+            let proc_result = if result.success {
+                ProcessResult::Success
+            } else {
+                ProcessResult::Failed
+            };
+            let previous = self.process_results.insert(result.index, proc_result);
+            // A health check that starts failing (or recovers) raises the badge
+            if previous.is_some_and(|previous| previous != proc_result) {
+                let output = if result.success {
+                    "Health check passes again"
+                } else {
+                    "Health check failed"
+                };
+                self.inbox.push(InboxEntry {
+                    name: cmd.name.clone(),
+                    success: result.success,
+                    output: output.to_string(),
+                });
+            }
         }
     }
 
     fn run_command(&mut self, index: usize) {
+        if !self.is_command_enabled(index) {
+            return;
+        }
+        if self.is_readonly() {
+            self.set_status("Read-only preset", false);
+            return;
+        }
+        // Ask for the `prompt` value first; submit_input runs it again
+        if let Some(cmd) = self.commands.get(index) {
+            let variant = self.variants.get(&index).copied().unwrap_or_default();
+            if cmd.prompt.is_some()
+                && cmd.cmd_for(variant).is_some()
+                && !self.inputs.contains_key(&index)
+            {
+                self.input_pending = Some(index);
+                self.input_buffer.clear();
+                return;
+            }
+        }
+        if !self.launch_queue.admit(index, self.running_count()) {
+            if let Some(cmd) = self.commands.get(index) {
+                let message = format!("Queued: {}", cmd.name);
+                self.set_command_status(index, message, false);
+            }
+            return;
+        }
+        self.retries.start(index);
         if let Some(cmd_config) = self.commands.get(index) {
             let cwd = cmd_config
                 .cwd
                 .as_ref()
                 .map(PathBuf::from)
                 .unwrap_or_else(|| self.working_dir.clone());
+            if let Err(e) = check_cwd(&cwd) {
+                self.set_status(e, true);
+                return;
+            }
+
+            // Another bar for a subdirectory
+            if let Some(path) = cmd_config.spawn_bar.clone() {
+                match spawn_bar(&cwd, &path) {
+                    Ok(dir) => {
+                        let message = format!("Opened bar: {}", dir.display());
+                        self.set_command_status(index, message, false);
+                    }
+                    Err(e) => self.set_status(e, true),
+                }
+                return;
+            }
+
+            // Bring another application forward; without cmd/run that's all
+            if let Some(app) = cmd_config.focus_app.clone() {
+                let focused = focus_app(&app);
+                let variant = self.variants.get(&index).copied().unwrap_or_default();
+                if cmd_config.run.is_none() && cmd_config.cmd_for(variant).is_none() {
+                    self.variants.remove(&index);
+                    if focused {
+                        self.set_command_status(index, format!("Focused: {}", app), false);
+                    } else {
+                        self.set_status(format!("Cannot focus: {}", app), true);
+                    }
+                    return;
+                }
+            }
+
+            if let Some(ref lock) = cmd_config.lock {
+                if let Err(holder) = self.locks.acquire(lock, index) {
+                    let holder = self.commands.get(holder).map_or("", |c| c.name.as_str());
+                    let message = format!("Locked by: {}", holder);
+                    self.set_command_status(index, message, true);
+                    return;
+                }
+            }
 
             // Script execution (async)
             if let Some(ref script) = cmd_config.run {
@@ -250,135 +1337,1004 @@ impl LaunchBarApp {
                     return;
                 }
 
-                self.running_scripts.insert(index);
-                self.last_status = Some(format!("Running: {}", cmd_config.name));
-                self.is_error = false;
-
                 let script = script.clone();
                 let script_type =
                     resolve_script_type(cmd_config.script_type, &script, &self.script_config);
+                let detach = cmd_config.detach;
+                self.running_scripts.insert(index);
+                self.start_timer(index);
+                self.record_last_command(index);
                 let cwd = Arc::new(cwd);
+                let mut script_config = self.script_config.clone();
+                script_config.detach = detach;
                 let tx = self.script_tx.clone();
 
-                std::thread::spawn(move || {
-                    // Catch panics to ensure tx.send is always called
-                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                        run_script(&script, script_type, cwd)
-                    }));
+                std::thread::spawn(move || {
+                    // Catch panics to ensure tx.send is always called
+                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        run_script(&script, script_type, cwd, &script_config)
+                    }));
+
+                    let result = result.unwrap_or_else(|_| {
+                        ScriptResult::failed(ScriptError::Panicked, "Script panicked")
+                    });
+                    let (success, message) = (result.success, result.message);
+
+                    let _ = tx.send(AsyncScriptResult {
+                        index,
+                        success,
+                        message,
+                    });
+                });
+                return;
+            }
+
+            // Persistent shell session (async)
+            if cmd_config.session {
+                if self.running_scripts.contains(&index) {
+                    return;
+                }
+                match self.spawn_session_command(index, cwd) {
+                    Ok(()) => {
+                        self.start_timer(index);
+                        self.record_last_command(index);
+                    }
+                    Err(e) => {
+                        self.set_status(e, true);
+                        self.process_results.insert(index, ProcessResult::Failed);
+                        self.variants.remove(&index);
+                        self.dropped.remove(&index);
+                        self.inputs.remove(&index);
+                        self.locks.release(index);
+                    }
+                }
+                return;
+            }
+
+            // Shell command execution
+            let variant = self.variants.get(&index).copied().unwrap_or_default();
+            if cmd_config.cmd_for(variant).is_some() {
+                // Start with the first candidate whose program exists
+                let candidate = select_candidate(&cmd_config.candidates(variant), 0, |cmd| {
+                    is_available(cmd, &self.path_prepend)
+                })
+                .unwrap_or(0);
+
+                match self.spawn_candidate(index, candidate, &cwd) {
+                    Ok(()) => {
+                        // Clear all previous success results when a new command is run
+                        self.process_results
+                            .retain(|_, v| *v != ProcessResult::Success);
+                        self.start_timer(index);
+                        self.record_last_command(index);
+                    }
+                    Err(e) => {
+                        self.set_status(e, true);
+                        self.process_results.insert(index, ProcessResult::Failed);
+                        self.variants.remove(&index);
+                        self.dropped.remove(&index);
+                        self.inputs.remove(&index);
+                        self.locks.release(index);
+                    }
+                }
+            } else {
+                self.locks.release(index);
+                self.set_status("No command or script defined".to_string(), true);
+            }
+        }
+    }
+
+    /// Spawn one candidate of a shell command (`cmd`, then the `fallback` entries)
+    fn spawn_candidate(
+        &mut self,
+        index: usize,
+        candidate: usize,
+        cwd: &Path,
+    ) -> Result<(), String> {
+        let cmd_config = self
+            .commands
+            .get(index)
+            .ok_or("No command or script defined")?;
+        let variant = self.variants.get(&index).copied().unwrap_or_default();
+        let candidates = cmd_config.candidates(variant);
+        let cmd = candidates
+            .get(candidate)
+            .ok_or("No command or script defined")?;
+        // Expand $clipboard, $dropped and $input variables
+        let dropped = self.dropped.get(&index).map_or(&[][..], Vec::as_slice);
+        let cmd_str = expand_dropped(&expand_command(cmd)?, dropped);
+        let cmd_str = expand_input(&cmd_str, self.inputs.get(&index).map(String::as_str));
+        let options = ShellOptions {
+            login: cmd_config.login_shell.unwrap_or(self.login_shell),
+            path_prepend: self.path_prepend.clone(),
+            shell: cmd_config.shell.or(self.default_shell),
+            detach: cmd_config.detach,
+            elevate: cmd_config.elevate,
+            nice: cmd_config.nice,
+        };
+        let capture = cmd_config.success_pattern.is_some() || cmd_config.failure_pattern.is_some();
+        let mut child = match cmd_config.log_to {
+            Some(ref log_to) if !options.detach => {
+                let log = open_log(log_to, cwd, cmd_config.log_append)
+                    .map_err(|e| format!("Cannot open log '{}': {}", log_to, e))?;
+                spawn_shell_command_logged(&cmd_str, cwd, &options, log)
+            }
+            _ => spawn_shell_command(&cmd_str, cwd, &options),
+        }
+        .map_err(|e| format!("Failed: {}", e))?;
+
+        // Stream output lines to the status line
+        let mut readers = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            readers.push(forward_lines(stdout, index, self.output_tx.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            readers.push(forward_lines(stderr, index, self.output_tx.clone()));
+        }
+        // Keep the whole output when it decides the result
+        if capture {
+            self.output_tail.capture(index, self.max_output_bytes);
+            self.output_readers.insert(index, readers);
+        }
+        self.track_progress(index);
+        if candidates.len() > 1 {
+            self.fallback_used.insert(index, candidate);
+        }
+        self.running_processes.insert(index, child);
+        Ok(())
+    }
+
+    /// Follow a starting command's `progress_pattern` in its output
+    fn track_progress(&mut self, index: usize) {
+        let Some(cmd) = self.commands.get(index) else {
+            return;
+        };
+        if let Some(ref pattern) = cmd.progress_pattern {
+            if let Err(e) = self.output_tail.track_progress(index, pattern) {
+                eprintln!("[warn] Invalid progress_pattern for '{}': {}", cmd.name, e);
+            }
+        }
+    }
+
+    /// Run a `session` command in the preset's persistent shell on a background thread
+    ///
+    /// Output lines stream to the status line; the result arrives like a script's.
+    fn spawn_session_command(&mut self, index: usize, cwd: PathBuf) -> Result<(), String> {
+        let cmd_config = self
+            .commands
+            .get(index)
+            .ok_or("No command or script defined")?;
+        let variant = self.variants.get(&index).copied().unwrap_or_default();
+        let cmd = cmd_config
+            .cmd_for(variant)
+            .ok_or("No command or script defined")?;
+        let dropped = self.dropped.get(&index).map_or(&[][..], Vec::as_slice);
+        let cmd_str = expand_dropped(&expand_command(cmd)?, dropped);
+        let cmd_str = expand_input(&cmd_str, self.inputs.get(&index).map(String::as_str));
+        let options = ShellOptions {
+            login: cmd_config.login_shell.unwrap_or(self.login_shell),
+            path_prepend: self.path_prepend.clone(),
+            shell: cmd_config.shell.or(self.default_shell),
+            detach: false,
+            elevate: false,
+            nice: None,
+        };
+        // Only a command's own cwd is entered; otherwise `cd` carries over
+        let explicit_cwd = cmd_config.cwd.is_some().then(|| cwd.clone());
+        let limits = SessionLimits {
+            timeout: cmd_config.session_timeout_secs.map(Duration::from_secs),
+            cancel: Arc::clone(&self.session_cancel),
+        };
+        let session = Arc::clone(&self.shell_session);
+        let output_tx = self.output_tx.clone();
+        let tx = self.script_tx.clone();
+        self.running_scripts.insert(index);
+        self.track_progress(index);
+
+        std::thread::spawn(move || {
+            let mut slot = session.lock().unwrap_or_else(PoisonError::into_inner);
+            let mut last_line = String::new();
+            let result = run_in_session(
+                &mut slot,
+                &cmd_str,
+                &cwd,
+                explicit_cwd.as_deref(),
+                &options,
+                &limits,
+                |line| {
+                    let output = OutputLine::new(index, line);
+                    last_line = output.line.clone();
+                    let _ = output_tx.send(output);
+                },
+            );
+            let (success, message) = match result {
+                Ok(0) => (true, last_line),
+                Ok(code) => (false, format!("Failed: exit code {}", code)),
+                Err(e) => (false, format!("Failed: {}", e)),
+            };
+            let _ = tx.send(AsyncScriptResult {
+                index,
+                success,
+                message,
+            });
+        });
+        Ok(())
+    }
+
+    /// After a "command not found" exit, start the next available fallback
+    ///
+    /// Returns false if the command has no fallback left.
+    fn try_next_fallback(&mut self, index: usize) -> bool {
+        let (Some(&current), Some(cmd)) =
+            (self.fallback_used.get(&index), self.commands.get(index))
+        else {
+            return false;
+        };
+        let cwd = cmd
+            .cwd
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| self.working_dir.clone());
+        let variant = self.variants.get(&index).copied().unwrap_or_default();
+        let Some(next) = select_candidate(&cmd.candidates(variant), current + 1, |cmd| {
+            is_available(cmd, &self.path_prepend)
+        }) else {
+            return false;
+        };
+        match self.spawn_candidate(index, next, &cwd) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("[warn] {}", e);
+                false
+            }
+        }
+    }
+
+    fn check_processes(&mut self) {
+        let mut finished = Vec::new();
+        for (&idx, child) in &mut self.running_processes {
+            match child.try_wait() {
+                Ok(Some(status)) => finished.push((
+                    idx,
+                    status.success(),
+                    is_not_found_exit(status.code()),
+                    CommandExit::from_status(status),
+                )),
+                Ok(None) => {} // Still running
+                Err(_) => finished.push((idx, false, false, CommandExit::Unknown)),
+            }
+        }
+        for (idx, exit_ok, not_found, exit) in finished {
+            if !self.output_complete(idx) {
+                continue;
+            }
+            self.running_processes.remove(&idx);
+            self.output_tail.drain(&self.output_rx);
+            let output = self.output_tail.take_captured(idx);
+            let last_line = self.output_tail.latest(idx).map(str::to_string);
+            self.output_tail.remove(idx);
+            if not_found && self.try_next_fallback(idx) {
+                continue;
+            }
+            let success = self.decide_success(idx, exit_ok, &output);
+            let result = if success {
+                ProcessResult::Success
+            } else {
+                ProcessResult::Failed
+            };
+            self.finish_command(idx, result, exit, None, last_line);
+        }
+        // A pending preset switch drops the queue anyway
+        if self.pending_preset.is_none() {
+            self.start_queued();
+        }
+    }
+
+    /// Whether an exited command's output has been fully read
+    ///
+    /// Only commands with output patterns wait, and at most `OUTPUT_DRAIN_GRACE`
+    /// (a background child may keep the pipes open).
+    fn output_complete(&mut self, index: usize) -> bool {
+        let Some(readers) = self.output_readers.get(&index) else {
+            return true;
+        };
+        let exited = *self.exited_at.entry(index).or_insert_with(Instant::now);
+        if readers.iter().all(JoinHandle::is_finished) || exited.elapsed() >= OUTPUT_DRAIN_GRACE {
+            self.output_readers.remove(&index);
+            self.exited_at.remove(&index);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Decide a finished command's result from its exit code and output patterns
+    fn decide_success(&self, index: usize, exit_ok: bool, output: &[String]) -> bool {
+        let Some(cmd) = self.commands.get(index) else {
+            return exit_ok;
+        };
+        match OutcomeRule::from_command(cmd) {
+            Ok(Some(rule)) => rule.decide(exit_ok, output),
+            Ok(None) => exit_ok,
+            Err(e) => {
+                eprintln!("[warn] {}", e);
+                exit_ok
+            }
+        }
+    }
+
+    /// Number of running processes and scripts
+    fn running_count(&self) -> usize {
+        self.running_processes.len() + self.running_scripts.len()
+    }
+
+    /// Start queued commands while slots are free
+    fn start_queued(&mut self) {
+        while let Some(index) = self.launch_queue.next(self.running_count()) {
+            self.run_command(index);
+        }
+    }
+
+    /// Replace the status line message
+    fn set_status(&mut self, message: impl Into<String>, is_error: bool) {
+        self.status.set(message, is_error);
+        self.status_timer = None;
+    }
+
+    /// Replace the status line message for a command (skipped for `quiet` commands)
+    fn set_command_status(&mut self, index: usize, message: impl Into<String>, is_error: bool) {
+        if let Some(cmd) = self.commands.get(index) {
+            if self.status.set_for(cmd, message, is_error) {
+                self.status_timer = None;
+            }
+        }
+    }
+
+    /// Record a command's launch time and show its elapsed time in the status line
+    fn start_timer(&mut self, index: usize) {
+        self.started_at.insert(index, Instant::now());
+        if self.commands.get(index).is_some_and(|cmd| !cmd.quiet) {
+            self.status_timer = Some(index);
+        }
+        if self
+            .commands
+            .get(index)
+            .is_some_and(|cmd| cmd.pause_watcher)
+        {
+            self.script_config
+                .watcher_paused
+                .store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Whether a running command other than `except` has `pause_watcher` set
+    fn watcher_pausers_running(&self, except: Option<usize>) -> bool {
+        self.running_processes
+            .keys()
+            .chain(self.running_scripts.iter())
+            .filter(|&&idx| Some(idx) != except)
+            .any(|&idx| self.commands.get(idx).is_some_and(|cmd| cmd.pause_watcher))
+    }
+
+    /// Remember the most recently launched command (persisted if `remember_last_command`)
+    fn record_last_command(&mut self, index: usize) {
+        self.last_command = Some(index);
+        if !self.remember_last_command {
+            return;
+        }
+        if let Some(cmd) = self.commands.get(index) {
+            let name = cmd.name.clone();
+            if self.state.get_last_command(&self.working_dir_str) != Some(name.as_str()) {
+                self.state.set_last_command(&self.working_dir_str, &name);
+                self.save_state();
+            }
+        }
+    }
+
+    /// Re-run the most recently launched command, if any
+    fn repeat_last_command(&mut self) {
+        if let Some(index) = self.last_command {
+            self.run_command(index);
+        }
+    }
+
+    /// Bottom-line summary of a hovered command (redacted)
+    ///
+    /// With `preview_expanded` the command is shown expanded; the preview is
+    /// built once per hover so the clipboard isn't read every frame.
+    fn hover_summary(&mut self, index: usize) -> Option<String> {
+        let cmd = self.commands.get(index)?;
+        let summary = match cmd.cmd.as_deref() {
+            Some(shell_cmd) if self.preview_expanded => match self.hover_preview {
+                Some((cached, ref preview)) if cached == index => preview.clone(),
+                _ => {
+                    let preview = format!("{}: {}", cmd.name, preview_command(shell_cmd));
+                    self.hover_preview = Some((index, preview.clone()));
+                    preview
+                }
+            },
+            _ => cmd.summary(),
+        };
+        // Average run time of earlier runs
+        let average = self
+            .preset_name
+            .as_ref()
+            .and_then(|preset| self.state.get_stats(preset, &cmd.name))
+            .map(|stats| format_elapsed(stats.average()));
+        let summary = match average {
+            Some(average) => format!("{} (avg {})", summary, average),
+            None => summary,
+        };
+        Some(self.redactor.redact(&summary))
+    }
+
+    /// `3 running, 1 failed` while several commands run, if `summary_status` is set
+    fn summary_text(&self) -> Option<String> {
+        if !self.summary_status {
+            return None;
+        }
+        let failed = self
+            .process_results
+            .iter()
+            .filter(|&(index, result)| {
+                *result == ProcessResult::Failed
+                    && !self.running_processes.contains_key(index)
+                    && !self.running_scripts.contains(index)
+            })
+            .count();
+        run_summary(self.running_count(), failed)
+    }
+
+    /// Current status line text (redacted), with a live timer for the running command
+    fn status_text(&self) -> Option<String> {
+        if let Some(index) = self.status_timer {
+            if let (Some(started), Some(cmd)) =
+                (self.started_at.get(&index), self.commands.get(index))
+            {
+                return Some(format!(
+                    "Running: {} {}",
+                    cmd.name,
+                    format_elapsed(started.elapsed())
+                ));
+            }
+        }
+        self.status
+            .message()
+            .map(|message| self.redactor.redact(message))
+    }
+
+    /// Record a finished command's result and fire its success/failure hook
+    ///
+    /// The status shows `exit` (exit code or signal); `message` overrides it on
+    /// failure (e.g. a script error). `output` (last output line or script result) fills `notify_on_done`.
+    fn finish_command(
+        &mut self,
+        index: usize,
+        result: ProcessResult,
+        exit: CommandExit,
+        message: Option<String>,
+        output: Option<String>,
+    ) {
+        self.process_results.insert(index, result);
+        self.last_result = Some((result, Instant::now()));
+        self.locks.release(index);
+        if self
+            .commands
+            .get(index)
+            .is_some_and(|cmd| cmd.pause_watcher)
+            && !self.watcher_pausers_running(Some(index))
+        {
+            self.script_config
+                .watcher_paused
+                .store(false, Ordering::SeqCst);
+        }
+        let fallback = self.fallback_used.remove(&index);
+        let variant = self.variants.remove(&index).unwrap_or_default();
+        self.dropped.remove(&index);
+        let input = self.inputs.remove(&index);
+        let elapsed = self
+            .started_at
+            .remove(&index)
+            .map(|started| started.elapsed());
+        let Some(cmd) = self.commands.get(index) else {
+            return;
+        };
+
+        let success = result == ProcessResult::Success;
+        let delay = Duration::from_secs(cmd.retry_delay_secs.unwrap_or(DEFAULT_RETRY_DELAY_SECS));
+        let now = Instant::now();
+        let retry = self.retries.finish(index, success, cmd.retries, delay, now);
+        let mut status_msg = match message.filter(|_| !success) {
+            Some(message) => message,
+            None => exit_message(&cmd.name, success, exit),
+        };
+        // Name the program that ran when the command has fallbacks
+        let used = fallback.and_then(|candidate| {
+            candidate_program(cmd.candidates(variant).get(candidate)?, |var| {
+                std::env::var(var).ok()
+            })
+        });
+        if let Some(program) = used {
+            status_msg = format!("{} (via {})", status_msg, program);
+        }
+        if let Some(elapsed) = elapsed {
+            status_msg = format!("{} ({})", status_msg, format_elapsed(elapsed));
+        }
+        // A pending retry keeps the variant and defers the outcome's side effects
+        if let Some(retry) = retry {
+            status_msg = format!("{}, retrying in {}s", status_msg, retry.seconds_left(now));
+            self.variants.insert(index, variant);
+            if let Some(input) = input {
+                self.inputs.insert(index, input);
+            }
+            self.set_command_status(index, status_msg, true);
+            return;
+        }
+        // Only final outcomes count as runs and are posted, not attempts that
+        // will be retried
+        let recorded = match (elapsed, &self.preset_name) {
+            (Some(elapsed), Some(preset)) => {
+                self.state.record_run(preset, &cmd.name, elapsed);
+                true
+            }
+            _ => false,
+        };
+        if let Some(ref url) = self.webhook_url {
+            let event = WebhookEvent::new(
+                self.preset_name.as_deref(),
+                &cmd.name,
+                success,
+                &self.redactor.redact(&status_msg),
+                elapsed.unwrap_or_default(),
+            );
+            post_webhook(url, &event);
+        }
+        // Commands the user has moved on from (or `quiet` ones) report to the inbox
+        let background = cmd.quiet || self.last_command != Some(index);
+        if let Some(output) = output.as_ref().filter(|s| background && !s.is_empty()) {
+            self.inbox.push(InboxEntry {
+                name: cmd.name.clone(),
+                success,
+                output: output.clone(),
+            });
+        }
+        if let Some(ref template) = cmd.notify_on_done {
+            let output = output
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| if success { "succeeded" } else { "failed" }.to_string());
+            notify(&render_notification(
+                template,
+                &cmd.name,
+                &output,
+                elapsed.unwrap_or_default(),
+            ));
+        }
+        let hook = select_hook(cmd, success).map(str::to_string);
+        let then_preset = cmd.then_preset.clone().filter(|_| success);
+        self.set_command_status(index, status_msg, !success);
+        if recorded {
+            self.save_state();
+        }
+
+        if let Some(hook) = hook {
+            self.run_hook(index, hook, success);
+        }
+        // Switching replaces the command list `index` refers to, so it waits
+        // for the other results (see `switch_to_pending_preset`)
+        if let Some(name) = then_preset {
+            self.pending_preset.get_or_insert(name);
+        }
+    }
+
+    /// Switch to a finished command's `then_preset` (the first one if several finished)
+    fn switch_to_pending_preset(&mut self) {
+        let Some(name) = self.pending_preset.take() else {
+            return;
+        };
+        if !self.switch_to_preset_named(&name) {
+            self.set_status(format!("then_preset: no preset named '{}'", name), true);
+        }
+    }
+
+    /// Run failed commands whose retry delay has elapsed
+    fn check_retries(&mut self) {
+        for index in self.retries.due(Instant::now()) {
+            self.run_command(index);
+        }
+    }
+
+    /// Run a success/failure hook in the background
+    ///
+    /// Hooks never trigger further hooks, and their failures are only logged so
+    /// they don't mask the command's own result.
+    fn run_hook(&self, index: usize, hook: String, success: bool) {
+        let Some(cmd) = self.commands.get(index) else {
+            return;
+        };
+        let name = cmd.name.clone();
+        let cwd = cmd
+            .cwd
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| self.working_dir.clone());
+        let script_config = self.script_config.clone();
+        let kind = if success { "on_success" } else { "on_failure" };
+
+        std::thread::spawn(move || {
+            let output = run_probe(&hook, &cwd, &script_config);
+            if !output.success {
+                eprintln!("[warn] {} hook for '{}' failed", kind, name);
+            }
+        });
+    }
+
+    /// Show the latest output line of a running command in the status line
+    fn check_output(&mut self) {
+        self.output_tail.drain(&self.output_rx);
+        if let Some((idx, line)) = self
+            .output_tail
+            .take_update(Instant::now(), OUTPUT_STATUS_INTERVAL)
+        {
+            if !self.running_processes.contains_key(&idx) && !self.running_scripts.contains(&idx) {
+                return;
+            }
+            if let Some(cmd) = self.commands.get(idx) {
+                let message = format!("{}: {}", cmd.name, line);
+                self.set_command_status(idx, message, false);
+            }
+        }
+    }
+
+    fn check_scripts(&mut self) {
+        while let Ok(result) = self.script_rx.try_recv() {
+            self.running_scripts.remove(&result.index);
+            self.output_tail.remove(result.index);
+            let proc_result = if result.success {
+                ProcessResult::Success
+            } else {
+                ProcessResult::Failed
+            };
+            let exit = CommandExit::from_success(result.success);
+            let message = Some(result.message);
+            self.finish_command(result.index, proc_result, exit, message.clone(), message);
+        }
+    }
+
+    /// Enter or leave compact mode, persisting the choice for this directory
+    fn set_compact(&mut self, ctx: &egui::Context, compact: bool) {
+        self.compact = compact;
+        self.compact_expanded = false;
+        self.compact_hover_since = None;
+        self.state.set_compact(&self.working_dir_str, compact);
+        self.save_state();
+        self.resize_for_compact(ctx);
+    }
+
+    /// Resize the window to match the current compact state
+    fn resize_for_compact(&self, ctx: &egui::Context) {
+        let size = if self.compact && !self.compact_expanded {
+            COMPACT_SIZE
+        } else {
+            bar_size(
+                self.commands.len(),
+                title_bar_buttons(&self.title_bar, self.preset_order.len()),
+                self.padding,
+                self.spacing,
+            )
+        };
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+    }
+
+    /// Render the collapsed compact dot (draggable, expands on hover, restores on click)
+    fn show_compact_dot(&mut self, ctx: &egui::Context, color: egui::Color32) {
+        egui::CentralPanel::default()
+            .frame(egui::Frame::NONE)
+            .show(ctx, |ui| {
+                let rect = ui.max_rect();
+                let response = ui.interact(
+                    rect,
+                    ui.id().with("compact_dot"),
+                    egui::Sense::click_and_drag(),
+                );
+                ui.painter().circle_filled(
+                    rect.center(),
+                    rect.width().min(rect.height()) / 2.0 - 2.0,
+                    color,
+                );
+
+                if response.dragged() {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
+                }
+                if response.drag_stopped() {
+                    self.save_current_position(ctx);
+                }
+
+                if response.clicked() {
+                    self.set_compact(ctx, false);
+                } else if response.hovered() && !response.dragged() {
+                    // Expand temporarily once the pointer rests on the dot
+                    let since = *self.compact_hover_since.get_or_insert_with(Instant::now);
+                    if since.elapsed() >= COMPACT_HOVER_DELAY {
+                        self.compact_expanded = true;
+                        self.compact_hover_since = None;
+                        self.resize_for_compact(ctx);
+                    } else {
+                        ctx.request_repaint_after(COMPACT_HOVER_DELAY);
+                    }
+                } else {
+                    self.compact_hover_since = None;
+                }
+            });
+    }
+
+    /// Recently opened directories other than the current one
+    fn recent_choices(&self) -> Vec<String> {
+        self.state
+            .recent_dirs()
+            .into_iter()
+            .filter(|dir| *dir != self.working_dir_str)
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Render the recent directories list; picking one moves the bar there
+    fn show_recent_picker(&mut self, ctx: &egui::Context) {
+        let dirs = self.recent_picker.clone().unwrap_or_default();
+        let mut chosen = None;
+        egui::CentralPanel::default()
+            .frame(
+                egui::Frame::NONE
+                    .fill(self.base_color)
+                    .inner_margin(egui::Margin::same(self.padding.round() as i8)),
+            )
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new("Recent directories (Esc to close)")
+                        .size(10.0)
+                        .color(self.palette.preset_label),
+                );
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    if dirs.is_empty() {
+                        ui.label(
+                            egui::RichText::new("No other recent directories")
+                                .size(11.0)
+                                .color(self.palette.status_text),
+                        );
+                    }
+                    for dir in &dirs {
+                        let label = egui::RichText::new(dir).size(11.0);
+                        if ui.selectable_label(false, label).clicked() {
+                            chosen = Some(dir.clone());
+                        }
+                    }
+                });
+            });
+
+        if let Some(dir) = chosen {
+            self.recent_picker = None;
+            self.open_working_dir(ctx, PathBuf::from(dir));
+        } else if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.recent_picker = None;
+        }
+    }
+
+    /// List background command results in place of the bar (Esc or click to close)
+    fn show_inbox(&mut self, ctx: &egui::Context) {
+        let mut close = false;
+        egui::CentralPanel::default()
+            .frame(
+                egui::Frame::NONE
+                    .fill(self.base_color)
+                    .inner_margin(egui::Margin::same(self.padding.round() as i8)),
+            )
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new("Notifications (Esc to close)")
+                        .size(10.0)
+                        .color(self.palette.preset_label),
+                );
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for entry in self.inbox.entries() {
+                        let color = if entry.success {
+                            self.palette.status_text
+                        } else {
+                            self.palette.error_text
+                        };
+                        let text = self
+                            .redactor
+                            .redact(&format!("{}: {}", entry.name, entry.output));
+                        let label = egui::RichText::new(text).size(11.0).color(color);
+                        if ui.selectable_label(false, label).clicked() {
+                            close = true;
+                        }
+                    }
+                });
+            });
+
+        if close || ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.inbox_open = false;
+        }
+    }
+
+    /// Run the command waiting for input with the typed value as `$input`
+    fn submit_input(&mut self) {
+        let Some(index) = self.input_pending.take() else {
+            return;
+        };
+        self.inputs
+            .insert(index, std::mem::take(&mut self.input_buffer));
+        self.run_command(index);
+    }
+
+    /// Drop the command waiting for input without running it
+    fn cancel_input(&mut self) {
+        let Some(index) = self.input_pending.take() else {
+            return;
+        };
+        self.input_buffer.clear();
+        self.variants.remove(&index);
+        self.dropped.remove(&index);
+        if let Some(cmd) = self.commands.get(index) {
+            let message = format!("Cancelled: {}", cmd.name);
+            self.set_command_status(index, message, false);
+        }
+    }
+
+    /// Ask for a command's `prompt` value in place of the bar (Enter runs, Esc cancels)
+    fn show_input_prompt(&mut self, ctx: &egui::Context, index: usize) {
+        let label = self
+            .commands
+            .get(index)
+            .and_then(|cmd| cmd.prompt.clone())
+            .unwrap_or_default();
+        let mut submit = false;
+        egui::CentralPanel::default()
+            .frame(
+                egui::Frame::NONE
+                    .fill(self.base_color)
+                    .inner_margin(egui::Margin::same(self.padding.round() as i8)),
+            )
+            .show(ctx, |ui| {
+                ui.label(
+                    egui::RichText::new(format!("{} (Enter to run, Esc to cancel)", label))
+                        .size(10.0)
+                        .color(self.palette.preset_label),
+                );
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.input_buffer).desired_width(f32::INFINITY),
+                );
+                response.request_focus();
+                submit = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            });
 
-                    let (success, message) = match result {
-                        Ok(r) => (r.success, r.message),
-                        Err(_) => (false, "Script panicked".to_string()),
-                    };
+        if submit {
+            self.submit_input();
+        } else if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.cancel_input();
+        }
+    }
 
-                    let _ = tx.send(AsyncScriptResult {
-                        index,
-                        success,
-                        message,
-                    });
-                });
-                return;
+    /// Let clicks over empty regions pass through to the window below (`click_through`)
+    ///
+    /// `interactive` is None when the whole window must take input (compact dot).
+    fn update_click_through(&mut self, ctx: &egui::Context, interactive: Option<&[egui::Rect]>) {
+        let Some(click_through) = self.click_through.as_mut() else {
+            return;
+        };
+        let change = match interactive {
+            Some(regions) => {
+                let pointer = ctx.input(|i| i.pointer.hover_pos());
+                click_through.update(is_over_empty(pointer, regions), Instant::now())
+            }
+            None => click_through.reset().then_some(false),
+        };
+        if let Some(passthrough) = change {
+            ctx.send_viewport_cmd(egui::ViewportCommand::MousePassthrough(passthrough));
+            if passthrough {
+                ctx.request_repaint_after(CLICK_THROUGH_RECHECK);
             }
+        }
+    }
 
-            // Shell command execution
-            if let Some(ref cmd) = cmd_config.cmd {
-                // Expand $clipboard variable
-                let cmd_str = if cmd.contains("$clipboard") {
-                    match Clipboard::new().and_then(|mut cb| cb.get_text()) {
-                        Ok(text) => cmd.replace("$clipboard", &text),
-                        Err(_) => {
-                            self.last_status = Some("Failed to read clipboard".to_string());
-                            self.is_error = true;
-                            return;
-                        }
-                    }
-                } else {
-                    cmd.clone()
-                };
+    /// Start a window drag from `rect`, saving the position when it ends
+    fn drag_window(
+        &mut self,
+        ctx: &egui::Context,
+        ui: &egui::Ui,
+        rect: egui::Rect,
+        id: impl std::hash::Hash,
+    ) {
+        let response = ui.interact(rect, ui.id().with(id), egui::Sense::drag());
+        if response.dragged() && response.interact_pointer_pos().is_some() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
+        }
+        if response.drag_stopped() {
+            self.save_current_position(ctx);
+        }
+    }
 
-                let result = spawn_shell_command(&cmd_str, &cwd);
+    fn save_current_position(&mut self, ctx: &egui::Context) {
+        let pos = ctx.input(|i| i.viewport().outer_rect.map(|r| r.min));
+        if let Some(pos) = pos {
+            self.state.set_position(&self.working_dir_str, pos);
+            self.save_state();
+        }
+    }
 
-                match result {
-                    Ok(child) => {
-                        // Clear all previous success results when a new command is run
-                        self.process_results
-                            .retain(|_, v| *v != ProcessResult::Success);
-                        self.running_processes.insert(index, child);
-                        self.last_status = Some(format!("Running: {}", cmd_config.name));
-                        self.is_error = false;
-                    }
-                    Err(e) => {
-                        self.last_status = Some(format!("Failed: {}", e));
-                        self.is_error = true;
-                        self.process_results.insert(index, ProcessResult::Failed);
-                    }
-                }
+    /// Diagnostic summary for bug reports: preset, config, commands and their
+    /// state, the status line and recent output (secrets redacted)
+    fn build_diagnostics(&self) -> String {
+        let mut lines = vec![
+            format!(
+                "launch-bar {} ({})",
+                env!("CARGO_PKG_VERSION"),
+                std::env::consts::OS
+            ),
+            format!("Working dir: {}", self.working_dir.display()),
+            format!(
+                "Preset: {}",
+                self.preset_name.as_deref().unwrap_or("(none)")
+            ),
+            format!("Config: {}", self.config_path.display()),
+        ];
+        if let Some(message) = self.status.message() {
+            let kind = if self.status.is_error() {
+                "error"
             } else {
-                self.last_status = Some("No command or script defined".to_string());
-                self.is_error = true;
-            }
+                "info"
+            };
+            lines.push(format!("Status ({}): {}", kind, message));
         }
-    }
 
-    fn check_processes(&mut self) {
-        let mut finished = Vec::new();
-        for (&idx, child) in &mut self.running_processes {
-            match child.try_wait() {
-                Ok(Some(status)) => {
-                    let result = if status.success() {
-                        ProcessResult::Success
-                    } else {
-                        ProcessResult::Failed
-                    };
-                    finished.push((idx, result));
-                }
-                Ok(None) => {} // Still running
-                Err(_) => {
-                    finished.push((idx, ProcessResult::Failed));
+        lines.push(format!("Commands ({}):", self.commands.len()));
+        for (index, cmd) in self.commands.iter().enumerate() {
+            let state = if self.running_processes.contains_key(&index)
+                || self.running_scripts.contains(&index)
+            {
+                "running"
+            } else if self.retries.get(index).is_some() {
+                "retry pending"
+            } else if self.launch_queue.is_queued(index) {
+                "queued"
+            } else {
+                match self.process_results.get(&index) {
+                    Some(ProcessResult::Success) => "succeeded",
+                    Some(ProcessResult::Failed) => "failed",
+                    None => "idle",
                 }
+            };
+            lines.push(format!("  {}. {} [{}]", index + 1, cmd.summary(), state));
+            if let Some(output) = self.output_tail.latest(index) {
+                lines.push(format!("     last output: {}", output));
             }
         }
-        for (idx, result) in finished {
-            self.running_processes.remove(&idx);
-            self.process_results.insert(idx, result);
-            if let Some(cmd) = self.commands.get(idx) {
-                let status_msg = match result {
-                    ProcessResult::Success => format!("Done: {}", cmd.name),
-                    ProcessResult::Failed => format!("Failed: {}", cmd.name),
-                };
-                self.last_status = Some(status_msg);
-                self.is_error = result == ProcessResult::Failed;
+
+        if !self.inbox.is_empty() {
+            lines.push("Background results:".to_string());
+            for entry in self.inbox.entries() {
+                let result = if entry.success { "ok" } else { "failed" };
+                lines.push(format!("  {} ({}): {}", entry.name, result, entry.output));
             }
         }
+        self.redactor.redact(&lines.join("\n"))
     }
 
-    fn check_scripts(&mut self) {
-        while let Ok(result) = self.script_rx.try_recv() {
-            self.running_scripts.remove(&result.index);
-            let proc_result = if result.success {
-                ProcessResult::Success
-            } else {
-                ProcessResult::Failed
-            };
-            self.process_results.insert(result.index, proc_result);
-
-            if let Some(cmd) = self.commands.get(result.index) {
-                let status_msg = if result.success {
-                    format!("Done: {}", cmd.name)
-                } else {
-                    result.message
-                };
-                self.last_status = Some(status_msg);
-                self.is_error = !result.success;
-            }
+    /// Copy `build_diagnostics` to the clipboard
+    fn copy_diagnostics(&mut self) {
+        let text = self.build_diagnostics();
+        match Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text)) {
+            Ok(()) => self.set_status("Copied diagnostics", false),
+            Err(e) => self.set_status(format!("Copy failed: {}", e), true),
         }
     }
 
-    fn save_current_position(&mut self, ctx: &egui::Context) {
-        let pos = ctx.input(|i| i.viewport().outer_rect.map(|r| r.min));
-        if let Some(pos) = pos {
-            self.state.set_position(&self.working_dir_str, pos);
-            self.state.save();
+    /// Persist app state, warning once in the status line if saving fails
+    fn save_state(&mut self) {
+        if let Err(e) = self.state.save(&self.working_dir_str) {
+            if !self.state_save_warned {
+                self.state_save_warned = true;
+                self.set_status(format!("Failed to save state: {}", e), true);
+            }
         }
     }
 }
@@ -391,31 +2347,74 @@ impl eframe::App for LaunchBarApp {
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
         // Save position on exit
         // Note: ctx not available here, but state should be saved via corner button
+        self.run_close_hook();
+        // Stop accepting `launch-bar cd` and remove the socket
+        self.daemon_server = None;
+        self.session_cancel.store(true, Ordering::SeqCst);
     }
 
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         let theme = Theme::current(ctx);
 
-        // Request periodic repaint to check for file changes
-        ctx.request_repaint_after(std::time::Duration::from_millis(500));
+        // Fade toward blur_opacity while unfocused
+        let now = Instant::now();
+        let focused = ctx.input(|i| i.viewport().focused).unwrap_or(true);
+        self.focus_fade
+            .set_target(if focused { 1.0 } else { 0.0 }, now);
+        let mut opacity = match self.blur_opacity {
+            Some(blur_opacity) => lerp(blur_opacity, self.opacity, self.focus_fade.value(now)),
+            None => self.opacity,
+        };
+        // Ease toward hover_opacity while the pointer is over the bar
+        let pointer_over = ctx.input(|i| i.pointer.has_pointer());
+        self.hover_fade
+            .set_target(if pointer_over { 1.0 } else { 0.0 }, now);
+        if let Some(hover_opacity) = self.hover_opacity {
+            opacity = lerp(opacity, hover_opacity, self.hover_fade.eased_value(now));
+        }
+
+        self.system_theme = ctx.system_theme();
+        self.palette = Palette::new(&self.theme, self.high_contrast, self.system_theme)
+            .with_colors(&self.colors);
+        let palette = self.palette;
 
-        // Fixed dark background
+        // Theme background at the current opacity
         let bg_color = egui::Color32::from_rgba_unmultiplied(
-            palette::BASE_BG.r(),
-            palette::BASE_BG.g(),
-            palette::BASE_BG.b(),
-            (self.opacity * 255.0) as u8,
+            palette.base_bg.r(),
+            palette.base_bg.g(),
+            palette.base_bg.b(),
+            (opacity * 255.0) as u8,
         );
 
+        self.apply_start_position(ctx);
+
         // Check running processes and scripts
+        self.check_output();
         self.check_processes();
         self.check_scripts();
+        self.switch_to_pending_preset();
+        self.check_retries();
+        self.check_enabled();
+        self.check_badges();
+        self.check_polls();
+        self.check_daemon(ctx);
+        #[cfg(feature = "global-hotkey")]
+        self.check_global_hotkeys();
 
         // Check file changes and update highlight state
         if self.file_changed.swap(false, Ordering::SeqCst) {
+            self.enabled_cache.invalidate(Instant::now());
+            self.badges.invalidate();
             self.highlight_until = Some(Instant::now() + std::time::Duration::from_secs(5));
             ctx.request_repaint();
         }
+        if self.git_head_changed.swap(false, Ordering::SeqCst) {
+            self.probe_git_branch();
+        }
+        self.check_git_branch();
+        self.refresh_enabled();
+        self.refresh_badges();
+        self.refresh_polls();
 
         // Determine if we should highlight (file change OR window hover)
         let is_file_highlighted = self
@@ -424,26 +2423,100 @@ impl eframe::App for LaunchBarApp {
             .unwrap_or(false);
         let is_hovered = ctx.input(|i| i.pointer.has_pointer());
         let is_highlighted = is_file_highlighted || is_hovered;
+        let last_result = self
+            .last_result
+            .filter(|_| self.accent_from_result)
+            .map(|(result, at)| (result == ProcessResult::Success, at.elapsed()));
+
+        // Poll running commands and file changes, and animate fades, within max_fps
+        let activity = Activity {
+            running: self.running_count() > 0 || self.retries.has_pending(),
+            animating: self.focus_fade.is_animating(now)
+                || (self.hover_opacity.is_some() && self.hover_fade.is_animating(now))
+                || last_result.is_some_and(|(_, elapsed)| result_accent_fading(elapsed)),
+            highlight_left: self
+                .highlight_until
+                .filter(|_| is_file_highlighted)
+                .map(|until| until.saturating_duration_since(Instant::now())),
+        };
+        ctx.request_repaint_after(next_repaint(activity, self.poll_interval, self.max_fps));
+
+        // Preset color for accent line (top border)
+        let hue_offset = self.state.get_hue_offset(&self.working_dir_str);
+        let preset_color = vary_color_by_path(self.base_color, &self.working_dir_str, hue_offset);
+
+        // Compact mode: collapse back to the dot once the pointer leaves
+        if self.compact && self.compact_expanded && !is_hovered {
+            self.compact_expanded = false;
+            self.resize_for_compact(ctx);
+        }
+        if self.compact && !self.compact_expanded {
+            self.update_click_through(ctx, None);
+            self.show_compact_dot(ctx, preset_color);
+            return;
+        }
 
-        // Request repaint while highlighted (for smooth fade)
-        if is_file_highlighted {
-            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        // Ctrl/Cmd+R toggles the recent directories picker
+        if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::R)) {
+            self.recent_picker = match self.recent_picker {
+                Some(_) => None,
+                None => Some(self.recent_choices()),
+            };
+        }
+        if self.recent_picker.is_some() {
+            self.show_recent_picker(ctx);
+            return;
+        }
+        if self.inbox_open {
+            self.show_inbox(ctx);
+            return;
+        }
+        if let Some(index) = self.input_pending {
+            self.show_input_prompt(ctx, index);
+            return;
         }
 
-        // Preset color for accent line (top border)
-        let preset_color = vary_color_by_path(self.base_color, &self.working_dir_str);
+        // Enter repeats the last command
+        if ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
+            self.repeat_last_command();
+        }
+        let pressed: Vec<usize> = ctx.input_mut(|i| {
+            self.hotkeys
+                .iter()
+                .filter(|(shortcut, _)| i.consume_shortcut(shortcut))
+                .map(|&(_, index)| index)
+                .collect()
+        });
+        // Typed characters: a command's `key`, else 1-9 by position
+        let typed: Vec<usize> = ctx.input(|i| {
+            i.events
+                .iter()
+                .filter_map(|event| match event {
+                    egui::Event::Text(text) if !i.modifiers.command => Some(text),
+                    _ => None,
+                })
+                .flat_map(|text| text.chars())
+                .filter_map(|c| key_command(&self.commands, c))
+                .collect()
+        });
+        for index in pressed.into_iter().chain(typed) {
+            self.run_command(index);
+        }
+        let result_color = result_accent(preset_color, last_result, &palette);
         let accent_color = match self.accent_line.as_str() {
-            "show" => Some(preset_color),
             "hide" => None,
+            // A recent result overrides the preset color at full strength
+            _ if result_color.is_some() => result_color,
+            "show" => Some(preset_color),
             _ => {
                 // "auto": highlighted = full color, otherwise dimmed
                 Some(if is_highlighted {
                     preset_color
                 } else {
                     egui::Color32::from_rgba_unmultiplied(
-                        (preset_color.r() as u16 / 3 + palette::BASE_BG.r() as u16 * 2 / 3) as u8,
-                        (preset_color.g() as u16 / 3 + palette::BASE_BG.g() as u16 * 2 / 3) as u8,
-                        (preset_color.b() as u16 / 3 + palette::BASE_BG.b() as u16 * 2 / 3) as u8,
+                        (preset_color.r() as u16 / 3 + palette.base_bg.r() as u16 * 2 / 3) as u8,
+                        (preset_color.g() as u16 / 3 + palette.base_bg.g() as u16 * 2 / 3) as u8,
+                        (preset_color.b() as u16 / 3 + palette.base_bg.b() as u16 * 2 / 3) as u8,
                         180,
                     )
                 })
@@ -476,13 +2549,17 @@ impl eframe::App for LaunchBarApp {
         };
 
         let mut switch_preset = false;
+        let mut enter_compact = false;
+        let mut reload = false;
+        let mut restart = false;
+        let mut interactive = None;
 
         egui::CentralPanel::default()
             .frame(
                 egui::Frame::NONE
                     .fill(bg_color)
                     .stroke(border_stroke)
-                    .inner_margin(egui::Margin::same(12)),
+                    .inner_margin(egui::Margin::same(self.padding.round() as i8)),
             )
             .show(ctx, |ui| {
                 // Draw colored top accent line (at the very top edge)
@@ -493,29 +2570,19 @@ impl eframe::App for LaunchBarApp {
                             egui::pos2(rect.left(), rect.top() - 10.0),
                             egui::pos2(rect.right(), rect.top() - 10.0),
                         ],
-                        egui::Stroke::new(3.0, color),
+                        egui::Stroke::new(palette.accent_width, color),
                     );
                 }
 
-                // Window dragging
-                let response = ui.interact(
-                    ui.max_rect(),
-                    ui.id().with("drag_area"),
-                    egui::Sense::drag(),
-                );
-                if response.dragged() {
-                    if let Some(_pos) = response.interact_pointer_pos() {
-                        ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
-                    }
-                }
-                // Save position when drag ends
-                if response.drag_stopped() {
-                    self.save_current_position(ctx);
+                // Window dragging (whole panel, or only regions outside the button row)
+                let panel_rect = ui.max_rect();
+                if self.drag_anywhere {
+                    self.drag_window(ctx, ui, panel_rect, "drag_area");
                 }
 
                 // Custom title bar (always reserve space, only show icons when enabled)
                 ui.horizontal(|ui| {
-                    ui.set_min_height(20.0);
+                    ui.set_min_height(TITLE_BAR_HEIGHT);
 
                     if show_title_bar {
                         // Show preset name on the left
@@ -523,17 +2590,25 @@ impl eframe::App for LaunchBarApp {
                             ui.label(
                                 egui::RichText::new(name)
                                     .size(10.0)
-                                    .color(palette::PRESET_LABEL),
+                                    .color(palette.preset_label),
+                            );
+                        }
+                        if let Some(ref branch) = self.git_branch {
+                            ui.label(
+                                egui::RichText::new(branch)
+                                    .size(10.0)
+                                    .color(palette.preset_label),
                             );
                         }
 
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            if title_bar_button(ui, icons::X, "Close").clicked() {
+                            if title_bar_button(ui, &palette, icons::X, "Close").clicked() {
                                 self.save_current_position(ctx);
+                                self.run_close_hook();
                                 ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                             }
 
-                            if title_bar_button(ui, icons::MINUS, "Minimize").clicked() {
+                            if title_bar_button(ui, &palette, icons::MINUS, "Minimize").clicked() {
                                 ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
                             }
 
@@ -542,7 +2617,9 @@ impl eframe::App for LaunchBarApp {
                             } else {
                                 "Move to corner"
                             };
-                            if title_bar_button(ui, icons::CORNERS_IN, corner_tooltip).clicked() {
+                            if title_bar_button(ui, &palette, icons::CORNERS_IN, corner_tooltip)
+                                .clicked()
+                            {
                                 if let Some(saved_pos) = self.saved_position.take() {
                                     ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(
                                         saved_pos,
@@ -554,20 +2631,99 @@ impl eframe::App for LaunchBarApp {
                                     });
                                     if let (Some(monitor), Some(outer_rect)) = info {
                                         self.saved_position = Some(outer_rect.min);
-                                        let win_size = outer_rect.size();
-                                        let new_x = monitor.x - win_size.x - 20.0;
-                                        let new_y = monitor.y - win_size.y - 110.0;
                                         ctx.send_viewport_cmd(
-                                            egui::ViewportCommand::OuterPosition(egui::pos2(
-                                                new_x, new_y,
+                                            egui::ViewportCommand::OuterPosition(corner_position(
+                                                monitor,
+                                                outer_rect.size(),
                                             )),
                                         );
                                     }
                                 }
                             }
 
-                            if title_bar_button(ui, icons::GEAR, "Open config").clicked() {
-                                open_file(&self.config_path);
+                            if title_bar_button(ui, &palette, icons::RECORD, "Compact mode")
+                                .clicked()
+                            {
+                                enter_compact = true;
+                            }
+
+                            // Re-roll this directory's color; right-click resets it
+                            let recolor = title_bar_button(ui, &palette, icons::HASH, "Recolor");
+                            if recolor.clicked() {
+                                self.state.set_hue_offset(
+                                    &self.working_dir_str,
+                                    hue_offset.wrapping_add(1).max(1),
+                                );
+                                self.save_state();
+                            }
+                            recolor.context_menu(|ui| {
+                                if ui.button("Reset color").clicked() {
+                                    self.state.set_hue_offset(&self.working_dir_str, 0);
+                                    self.save_state();
+                                }
+                            });
+
+                            // Background command results, with an unread badge
+                            if !self.inbox.is_empty() {
+                                let unread = self.inbox.unread();
+                                let tooltip = match unread {
+                                    0 => "Notifications".to_string(),
+                                    n => format!("Notifications ({} unread)", n),
+                                };
+                                let button = title_bar_button(ui, &palette, icons::INFO, &tooltip);
+                                if unread > 0 {
+                                    let center = button.rect.right_top() + egui::vec2(-3.0, 4.0);
+                                    paint_badge(
+                                        ui.painter(),
+                                        &palette,
+                                        center,
+                                        5.0,
+                                        &format_badge(unread as u64),
+                                    );
+                                }
+                                if button.clicked() {
+                                    self.inbox_open = true;
+                                    self.inbox.mark_read();
+                                }
+                            }
+
+                            if title_bar_button(ui, &palette, icons::BUG, "Copy diagnostics")
+                                .clicked()
+                            {
+                                self.copy_diagnostics();
+                            }
+
+                            let gear = title_bar_button(ui, &palette, icons::GEAR, "Open config");
+                            if gear.clicked() {
+                                self.open_config();
+                            }
+                            gear.context_menu(|ui| {
+                                if ui.button("Copy path").clicked() {
+                                    ctx.copy_text(self.config_path.display().to_string());
+                                    self.set_status("Copied config path", false);
+                                }
+                                if ui.button("Reveal in file manager").clicked() {
+                                    if let Err(e) = reveal_in_file_manager(&self.config_path) {
+                                        self.set_status(format!("Reveal failed: {}", e), true);
+                                    }
+                                }
+                            });
+
+                            if title_bar_button(
+                                ui,
+                                &palette,
+                                icons::DOWNLOAD_SIMPLE,
+                                "Reload config",
+                            )
+                            .clicked()
+                            {
+                                reload = true;
+                            }
+
+                            if title_bar_button(ui, &palette, icons::BROOM, "Restart launcher")
+                                .clicked()
+                            {
+                                restart = true;
                             }
 
                             // Preset switch button (only show if multiple presets available)
@@ -581,7 +2737,8 @@ impl eframe::App for LaunchBarApp {
                                     .map(|p| format!("Switch to: {}", p.name))
                                     .unwrap_or_else(|| "Switch preset".to_string());
 
-                                if title_bar_button(ui, icons::ARROWS_CLOCKWISE, &tooltip).clicked()
+                                if title_bar_button(ui, &palette, icons::ARROWS_CLOCKWISE, &tooltip)
+                                    .clicked()
                                 {
                                     switch_preset = true;
                                 }
@@ -594,102 +2751,206 @@ impl eframe::App for LaunchBarApp {
                 if switch_preset {
                     self.switch_to_next_preset();
                 }
+                if reload {
+                    self.reload_config();
+                    self.resize_for_compact(ctx);
+                }
+                if restart {
+                    match self.restart() {
+                        Ok(name) => self.set_status(format!("Restarted: {}", name), false),
+                        Err(e) => self.set_status(format!("Restart failed: {}", e), true),
+                    }
+                    self.resize_for_compact(ctx);
+                }
+                if enter_compact {
+                    self.set_compact(ctx, true);
+                }
 
                 // Command buttons
                 let mut clicked_index = None;
                 let mut hovered_index: Option<usize> = None;
-                ui.horizontal(|ui| {
-                    ui.add_space(theme.spacing_sm);
-                    for (index, cmd) in self.commands.iter().enumerate() {
-                        let icon = cmd
-                            .icon
-                            .as_ref()
-                            .map(|s| get_icon(s))
-                            .unwrap_or(icons::PLAY);
-
-                        // Determine state based on process/script
-                        let is_running = self.running_processes.contains_key(&index)
-                            || self.running_scripts.contains(&index);
-                        let process_result = self.process_results.get(&index);
-
-                        let icon_color = if is_running {
-                            palette::RUNNING_ICON
-                        } else {
-                            egui::Color32::WHITE
-                        };
+                let mut buttons_right = panel_rect.left();
+                let mut button_rects = Vec::new();
+                let row_rect = ui
+                    .horizontal(|ui| {
+                        ui.add_space(theme.spacing_sm);
+                        ui.spacing_mut().item_spacing.x = self.spacing;
+                        for (index, cmd) in self.commands.iter().enumerate() {
+                            // Determine state based on process/script
+                            let is_running = self.running_processes.contains_key(&index)
+                                || self.running_scripts.contains(&index);
+                            let process_result = self.process_results.get(&index);
+                            let enabled = self.is_command_enabled(index);
+                            let retry = self.retries.get(index).filter(|_| !is_running);
+
+                            let icon_color = if !enabled {
+                                palette.disabled_icon
+                            } else if is_running {
+                                palette.running_icon
+                            } else if retry.is_some() {
+                                palette.command_icon.gamma_multiply(0.4)
+                            } else if self.launch_queue.is_queued(index) {
+                                palette.queued_icon
+                            } else {
+                                palette.command_icon
+                            };
 
-                        let icon_text = egui::RichText::new(icon)
-                            .family(egui::FontFamily::Name("icons".into()))
-                            .size(24.0)
-                            .color(icon_color);
+                            let icon_text =
+                                command_icon_text(ui.ctx(), cmd.icon.as_deref(), &cmd.name)
+                                    .size(24.0)
+                                    .color(icon_color);
 
-                        let button = egui::Button::new(icon_text)
-                            .fill(egui::Color32::TRANSPARENT)
-                            .min_size(egui::vec2(40.0, 40.0));
+                            let button = command_button(icon_text, cmd.background.as_deref());
 
-                        let response = ui.add(button);
+                            let hover_text = self.redactor.redact(&cmd.hover_text());
+                            let response = ui
+                                .add_enabled(enabled, button)
+                                .on_hover_text(&hover_text)
+                                .on_disabled_hover_text(&hover_text);
+                            buttons_right = response.rect.right();
+                            button_rects.push(response.rect);
 
-                        // Track hovered command
-                        if response.hovered() {
-                            hovered_index = Some(index);
-                        }
+                            // Track hovered command
+                            if response.hovered() {
+                                hovered_index = Some(index);
+                            }
 
-                        // Draw underline for running or finished
-                        let underline_color = if is_running {
-                            Some(palette::RUNNING_ICON)
-                        } else {
-                            process_result.map(|r| match r {
-                                ProcessResult::Success => palette::SUCCESS_UNDERLINE,
-                                ProcessResult::Failed => palette::ERROR_UNDERLINE,
-                            })
-                        };
-                        if let Some(color) = underline_color {
-                            let rect = response.rect;
-                            ui.painter().line_segment(
-                                [
-                                    egui::pos2(rect.left() + 5.0, rect.bottom() - 2.0),
-                                    egui::pos2(rect.right() - 5.0, rect.bottom() - 2.0),
-                                ],
-                                egui::Stroke::new(2.0, color),
-                            );
-                        }
+                            // Draw underline for running or finished
+                            let underline_color = if is_running {
+                                Some(palette.running_icon)
+                            } else if retry.is_some() {
+                                None
+                            } else {
+                                process_result.map(|r| match r {
+                                    ProcessResult::Success => palette.success_underline,
+                                    ProcessResult::Failed => palette.error_underline,
+                                })
+                            };
+                            if let Some(color) = underline_color {
+                                let rect = response.rect;
+                                // While running, `progress_pattern` shortens it to the progress
+                                let progress = self
+                                    .output_tail
+                                    .progress(index)
+                                    .filter(|_| is_running)
+                                    .unwrap_or(1.0);
+                                let left = rect.left() + 5.0;
+                                let right = lerp(left, rect.right() - 5.0, progress);
+                                ui.painter().line_segment(
+                                    [
+                                        egui::pos2(left, rect.bottom() - 2.0),
+                                        egui::pos2(right, rect.bottom() - 2.0),
+                                    ],
+                                    egui::Stroke::new(palette.underline_width, color),
+                                );
+                            }
 
-                        if response.clicked() {
-                            clicked_index = Some(index);
+                            // Category dot under the icon
+                            if let Some(ref category) = cmd.category {
+                                let color = category_color(
+                                    category,
+                                    &self.categories,
+                                    palette.preset_label,
+                                );
+                                let center = response.rect.center_bottom() - egui::vec2(0.0, 7.0);
+                                ui.painter().circle_filled(center, 2.0, color);
+                            }
+
+                            // Seconds until a pending retry, bottom-right
+                            if let Some(retry) = retry {
+                                ui.painter().text(
+                                    response.rect.right_bottom() + egui::vec2(-4.0, -3.0),
+                                    egui::Align2::RIGHT_BOTTOM,
+                                    retry.seconds_left(now).to_string(),
+                                    egui::FontId::proportional(10.0),
+                                    palette.status_text,
+                                );
+                            }
+
+                            // Draw count badge in the top-right corner
+                            if let Some(count) = self.badges.get(index) {
+                                let center = response.rect.right_top() + egui::vec2(-8.0, 8.0);
+                                paint_badge(
+                                    ui.painter(),
+                                    &palette,
+                                    center,
+                                    7.0,
+                                    &format_badge(count),
+                                );
+                            }
+
+                            if response.clicked() {
+                                clicked_index = Some(index);
+                            }
                         }
+                    })
+                    .response
+                    .rect;
+
+                interactive = Some(interactive_regions(panel_rect, row_rect, buttons_right));
+                if !self.drag_anywhere {
+                    for (i, rect) in drag_regions(panel_rect, row_rect, buttons_right)
+                        .into_iter()
+                        .enumerate()
+                    {
+                        self.drag_window(ctx, ui, rect, ("drag_region", i));
                     }
-                });
+                }
 
                 if let Some(index) = clicked_index {
+                    // Shift/Alt/Ctrl+click runs the matching cmd_* variant
+                    let modifiers = ctx.input(|i| i.modifiers);
+                    let variant =
+                        CmdVariant::from_modifiers(modifiers.shift, modifiers.alt, modifiers.ctrl);
+                    self.variants.insert(index, variant);
                     self.run_command(index);
                 }
 
+                // Files dropped onto a button run it with $dropped set
+                let dropped: Vec<PathBuf> = ctx.input(|i| {
+                    i.raw
+                        .dropped_files
+                        .iter()
+                        .filter_map(|file| file.path.clone())
+                        .collect()
+                });
+                if !dropped.is_empty() {
+                    let pos = ctx.input(|i| i.pointer.latest_pos());
+                    match pos.and_then(|pos| button_at(&button_rects, pos)) {
+                        Some(index) => {
+                            self.dropped.insert(index, dropped);
+                            self.run_command(index);
+                        }
+                        None => self.set_status("Drop files onto a command", true),
+                    }
+                }
+
                 // Bottom line: show hovered command info or status
                 ui.add_space(theme.spacing_xs);
-                if let Some(idx) = hovered_index {
-                    if let Some(cmd) = self.commands.get(idx) {
-                        let detail =
-                            cmd.cmd
-                                .as_deref()
-                                .or(cmd.run.as_deref().map(|s| {
-                                    if s.len() > 30 {
-                                        "[script]"
-                                    } else {
-                                        s
-                                    }
-                                }))
-                                .unwrap_or("[no command]");
-                        ui.label(
-                            egui::RichText::new(format!("{}: {}", cmd.name, detail))
-                                .color(palette::STATUS_TEXT)
-                                .size(theme.font_size_xs),
-                        );
+                if hovered_index.is_none() {
+                    self.hover_preview = None;
+                }
+                if let Some(summary) = hovered_index.and_then(|idx| self.hover_summary(idx)) {
+                    ui.label(
+                        egui::RichText::new(summary)
+                            .color(palette.status_text)
+                            .size(theme.font_size_xs),
+                    );
+                } else if let Some(summary) = self.summary_text() {
+                    let label = egui::Label::new(
+                        egui::RichText::new(summary)
+                            .color(palette.status_message)
+                            .size(theme.font_size_xs),
+                    )
+                    .sense(egui::Sense::click());
+                    if ui.add(label).on_hover_text("Show results").clicked() {
+                        self.inbox_open = true;
                     }
-                } else if let Some(status) = &self.last_status {
-                    let color = if self.is_error {
-                        palette::ERROR_TEXT
+                } else if let Some(status) = self.status_text() {
+                    let color = if self.status.is_error() {
+                        palette.error_text
                     } else {
-                        egui::Color32::WHITE
+                        palette.status_message
                     };
                     ui.label(
                         egui::RichText::new(status)
@@ -698,5 +2959,454 @@ impl eframe::App for LaunchBarApp {
                     );
                 }
             });
+
+        if let Some(regions) = interactive {
+            self.update_click_through(ctx, Some(&regions));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[[presets]]
+name = "Tools"
+commands = [{ name = "One", cmd = "true" }]
+
+[[presets]]
+name = "Web"
+cwd_pattern = "/nonexistent/*"
+commands = [{ name = "Two", cmd = "true" }]
+"#;
+
+    fn app_in(dir: &Path, preset: &str) -> LaunchBarApp {
+        let sources = ConfigSources {
+            global_path: dir.join("global.toml"),
+            local_path: dir.join("launch-bar.toml"),
+            arg_preset: None,
+            arg_profile: None,
+            stdin: None,
+            inline: Vec::new(),
+            no_detect: false,
+        };
+        let resolved = sources.resolve();
+        let idx = resolved.find_preset(preset).unwrap();
+        LaunchBarApp::with_state(
+            resolved.commands_for(idx),
+            resolved.window.clone(),
+            Palette::DARK.base_bg,
+            dir.to_path_buf(),
+            Some(preset.to_string()),
+            sources.active_path(),
+            ScriptConfig::default(),
+            resolved.presets(),
+            None,
+            resolved.categories.clone(),
+            sources,
+            AppState::default(),
+        )
+    }
+
+    fn command_names(app: &LaunchBarApp) -> Vec<&str> {
+        app.commands.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_reload_config_keeps_preset_and_rejects_invalid() {
+        let dir = std::env::temp_dir().join(format!("launch-bar-reload-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("launch-bar.toml");
+        std::fs::write(&config_path, CONFIG).unwrap();
+
+        let mut app = app_in(&dir, "Web");
+        assert_eq!(command_names(&app), vec!["Two"]);
+
+        // Edited config is picked up, current preset kept by name
+        let edited = CONFIG.replace(
+            r#"{ name = "Two", cmd = "true" }"#,
+            r#"{ name = "Two", cmd = "true" }, { name = "Three", cmd = "true" }"#,
+        );
+        std::fs::write(&config_path, edited).unwrap();
+        app.reload_config();
+        assert_eq!(app.preset_name.as_deref(), Some("Web"));
+        assert_eq!(command_names(&app), vec!["Two", "Three"]);
+        assert!(!app.status.is_error());
+
+        // Broken config keeps the previous commands and reports an error
+        std::fs::write(&config_path, "[[presets]\nname = ").unwrap();
+        app.reload_config();
+        assert_eq!(command_names(&app), vec!["Two", "Three"]);
+        assert!(app.status.is_error());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_reload_config_remaps_command_state() {
+        let dir = std::env::temp_dir().join(format!("launch-bar-remap-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("launch-bar.toml");
+        let config = CONFIG.replace(
+            r#"{ name = "Two", cmd = "true" }"#,
+            r#"{ name = "Two", cmd = "true" }, { name = "Three", cmd = "true" }"#,
+        );
+        std::fs::write(&config_path, &config).unwrap();
+
+        let mut app = app_in(&dir, "Web");
+        app.process_results.insert(0, ProcessResult::Success);
+        app.process_results.insert(1, ProcessResult::Failed);
+        app.last_command = Some(1);
+
+        // "Three" moves to the front, "Two" is edited
+        let edited = config.replace(
+            r#"{ name = "Two", cmd = "true" }, { name = "Three", cmd = "true" }"#,
+            r#"{ name = "Three", cmd = "true" }, { name = "Two", cmd = "false" }"#,
+        );
+        std::fs::write(&config_path, edited).unwrap();
+        app.reload_config();
+        assert_eq!(command_names(&app), vec!["Three", "Two"]);
+        assert_eq!(app.process_results.len(), 1);
+        assert!(app.process_results.get(&0) == Some(&ProcessResult::Failed));
+        assert_eq!(app.last_command, Some(0));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_restart_rebuilds_state() {
+        let dir = std::env::temp_dir().join(format!("launch-bar-restart-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("launch-bar.toml");
+        std::fs::write(&config_path, CONFIG).unwrap();
+
+        let mut app = app_in(&dir, "Web");
+        app.run_command(0);
+        assert_eq!(app.last_command, Some(0));
+
+        let edited = format!("[window]\nopacity = 0.5\nmax_icons = 1\n{}", CONFIG).replace(
+            r#"{ name = "One", cmd = "true" }"#,
+            r#"{ name = "One", cmd = "true" }, { name = "Extra", cmd = "true" }"#,
+        );
+        std::fs::write(&config_path, edited).unwrap();
+        assert_eq!(app.restart().unwrap(), "Tools");
+
+        // Preset re-detected, window settings and runtime state rebuilt
+        assert_eq!(app.preset_name.as_deref(), Some("Tools"));
+        assert_eq!(command_names(&app), vec!["One"]);
+        assert_eq!(app.opacity, 0.5);
+        assert_eq!(app.all_presets.len(), 2);
+        assert_eq!(app.preset_order.len(), 2);
+        assert!(app.running_processes.is_empty());
+        assert_eq!(app.last_command, None);
+        assert!(app.pending_start.is_none());
+        assert!(app.watcher.is_some());
+
+        // A broken config leaves the app untouched
+        std::fs::write(&config_path, "[[presets]\nname = ").unwrap();
+        assert!(app.restart().is_err());
+        assert_eq!(command_names(&app), vec!["One"]);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_last_command_tracks_latest_run() {
+        let dir = std::env::temp_dir().join(format!("launch-bar-last-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("launch-bar.toml"), CONFIG).unwrap();
+
+        let mut app = app_in(&dir, "Tools");
+        app.commands
+            .push(toml::from_str("name = \"Other\"\ncmd = \"true\"").unwrap());
+        assert_eq!(app.last_command, None);
+        // Nothing to repeat yet
+        app.repeat_last_command();
+        assert!(app.running_processes.is_empty());
+
+        app.run_command(0);
+        assert_eq!(app.last_command, Some(0));
+        app.run_command(1);
+        assert_eq!(app.last_command, Some(1));
+        // Unknown commands don't replace the last one
+        app.run_command(7);
+        assert_eq!(app.last_command, Some(1));
+
+        app.running_processes.clear();
+        app.repeat_last_command();
+        assert!(app.running_processes.contains_key(&1));
+        assert_eq!(app.last_command, Some(1));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_paused_watcher_ignores_events() {
+        let changed = AtomicBool::new(false);
+        let paused = AtomicBool::new(true);
+        let event = notify::Event::new(notify::EventKind::Create(notify::event::CreateKind::File));
+
+        record_event(&event, &changed, &paused);
+        assert!(!changed.load(Ordering::SeqCst));
+
+        paused.store(false, Ordering::SeqCst);
+        record_event(&event, &changed, &paused);
+        assert!(changed.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_pause_watcher_command_resumes_on_finish() {
+        let dir = std::env::temp_dir().join(format!("launch-bar-pause-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("launch-bar.toml"), CONFIG).unwrap();
+
+        let mut app = app_in(&dir, "Tools");
+        app.commands.push(
+            toml::from_str("name = \"Build\"\ncmd = \"true\"\npause_watcher = true").unwrap(),
+        );
+        let paused = app.script_config.watcher_paused.clone();
+
+        app.run_command(1);
+        assert!(paused.load(Ordering::SeqCst));
+        app.running_processes.remove(&1);
+        app.finish_command(1, ProcessResult::Success, CommandExit::Code(0), None, None);
+        assert!(!paused.load(Ordering::SeqCst));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_then_preset_switches_on_success() {
+        let dir = std::env::temp_dir().join(format!("launch-bar-then-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("launch-bar.toml"), CONFIG).unwrap();
+
+        let mut app = app_in(&dir, "Tools");
+        app.commands.push(
+            toml::from_str("name = \"Setup\"\ncmd = \"true\"\nthen_preset = \"Web\"").unwrap(),
+        );
+        // A failed run stays put
+        app.finish_command(1, ProcessResult::Failed, CommandExit::Code(1), None, None);
+        app.switch_to_pending_preset();
+        assert_eq!(app.preset_name.as_deref(), Some("Tools"));
+
+        app.finish_command(1, ProcessResult::Success, CommandExit::Code(0), None, None);
+        assert_eq!(app.preset_name.as_deref(), Some("Tools"));
+        app.switch_to_pending_preset();
+        assert_eq!(app.preset_name.as_deref(), Some("Web"));
+        assert_eq!(app.preset_order[app.current_preset_idx], 1);
+
+        // An unknown preset leaves the bar where it is, with a warning
+        app.commands[0].then_preset = Some("Missing".to_string());
+        app.finish_command(0, ProcessResult::Success, CommandExit::Code(0), None, None);
+        app.switch_to_pending_preset();
+        assert_eq!(app.preset_name.as_deref(), Some("Web"));
+        assert!(app
+            .status
+            .message()
+            .is_some_and(|m| m.contains("no preset named 'Missing'")));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_then_preset_waits_for_results_of_the_same_batch() {
+        let dir =
+            std::env::temp_dir().join(format!("launch-bar-then-batch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("launch-bar.toml"), CONFIG).unwrap();
+
+        let mut app = app_in(&dir, "Tools");
+        app.commands.push(
+            toml::from_str("name = \"Setup\"\ncmd = \"true\"\nthen_preset = \"Web\"").unwrap(),
+        );
+        // Setup finishes first, then One, in the same frame
+        for index in [1, 0] {
+            app.running_scripts.insert(index);
+            app.script_tx
+                .send(AsyncScriptResult {
+                    index,
+                    success: true,
+                    message: "done".to_string(),
+                })
+                .unwrap();
+        }
+        app.check_scripts();
+        assert_eq!(app.preset_name.as_deref(), Some("Tools"));
+        app.switch_to_pending_preset();
+        assert_eq!(app.preset_name.as_deref(), Some("Web"));
+
+        // One was finished as One, not as the new preset's first command
+        let names: Vec<_> = app.inbox.entries().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["One", "Setup"]);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_poll_state_change_reaches_inbox() {
+        let dir =
+            std::env::temp_dir().join(format!("launch-bar-poll-inbox-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("launch-bar.toml"), CONFIG).unwrap();
+
+        let mut app = app_in(&dir, "Tools");
+        app.polls.ensure(0, Duration::from_secs(5), Instant::now());
+        let (_, generation) = app.polls.start_due(Instant::now());
+        let poll = |app: &mut LaunchBarApp, success| {
+            app.poll_tx
+                .send(PollResult {
+                    index: 0,
+                    generation,
+                    success,
+                })
+                .unwrap();
+            app.check_polls();
+            app.inbox.entries().map(|e| e.success).collect::<Vec<_>>()
+        };
+
+        // The first result and repeated ones aren't news
+        assert!(poll(&mut app, true).is_empty());
+        assert!(poll(&mut app, true).is_empty());
+        assert_eq!(poll(&mut app, false), vec![false]);
+        assert_eq!(poll(&mut app, false), vec![false]);
+        assert_eq!(poll(&mut app, true), vec![true, false]);
+        assert_eq!(app.inbox.unread(), 2);
+        assert!(app.inbox.entries().all(|e| e.name == "One"));
+
+        // Results from before a preset switch are dropped
+        app.polls.clear();
+        assert_eq!(poll(&mut app, false).len(), 2);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_failed_command_waits_for_retry() {
+        let dir = std::env::temp_dir().join(format!("launch-bar-retry-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("launch-bar.toml"), CONFIG).unwrap();
+
+        let mut app = app_in(&dir, "Tools");
+        app.commands[0].retries = 1;
+        app.commands[0].retry_delay_secs = Some(60);
+        app.finish_command(0, ProcessResult::Failed, CommandExit::Code(1), None, None);
+        assert_eq!(app.retries.get(0).map(|r| r.attempts_left), Some(1));
+        assert!(app
+            .status
+            .message()
+            .is_some_and(|m| m.starts_with("Exited 1: One, retrying in")));
+
+        // The last attempt's failure stands
+        app.retries.due(Instant::now() + Duration::from_secs(60));
+        app.retries.start(0);
+        app.finish_command(0, ProcessResult::Failed, CommandExit::Code(1), None, None);
+        assert!(!app.retries.has_pending());
+        assert_eq!(app.status.message(), Some("Exited 1: One"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_readonly_preset_runs_nothing() {
+        let dir = std::env::temp_dir().join(format!("launch-bar-readonly-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = CONFIG.replace("name = \"Tools\"\n", "name = \"Tools\"\nreadonly = true\n");
+        std::fs::write(dir.join("launch-bar.toml"), config).unwrap();
+
+        let mut app = app_in(&dir, "Tools");
+        app.run_command(0);
+        assert!(app.running_processes.is_empty());
+        assert_eq!(app.last_command, None);
+        assert_eq!(app.status.message(), Some("Read-only preset"));
+
+        // Other presets run as usual
+        assert!(app.switch_to_preset_named("Web"));
+        app.run_command(0);
+        assert!(app.running_processes.contains_key(&0));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_build_diagnostics() {
+        let dir = std::env::temp_dir().join(format!("launch-bar-diag-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("launch-bar.toml"), CONFIG).unwrap();
+
+        let mut app = app_in(&dir, "Web");
+        app.process_results.insert(0, ProcessResult::Failed);
+        app.set_status("Exited 1: Two", true);
+        let diagnostics = app.build_diagnostics();
+        assert!(diagnostics.contains("Preset: Web"));
+        assert!(diagnostics.contains("Commands (1):"));
+        assert!(diagnostics.contains("  1. Two: true [failed]"));
+        assert!(diagnostics.contains("Status (error): Exited 1: Two"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_prompt_substitutes_typed_input() {
+        let dir = std::env::temp_dir().join(format!("launch-bar-prompt-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let out = dir.join("out.txt");
+        let config = format!(
+            "[[presets]]\nname = \"Tools\"\ncommands = [{{ name = \"Branch\", prompt = \"Branch name\", cmd = \"printf %s $input > {}\" }}]\n",
+            out.display()
+        );
+        std::fs::write(dir.join("launch-bar.toml"), config).unwrap();
+
+        let mut app = app_in(&dir, "Tools");
+        app.run_command(0);
+        assert_eq!(app.input_pending, Some(0));
+        assert!(app.running_processes.is_empty());
+
+        // Cancelling runs nothing
+        app.cancel_input();
+        assert_eq!(app.input_pending, None);
+        assert!(app.running_processes.is_empty());
+        assert_eq!(app.status.message(), Some("Cancelled: Branch"));
+
+        app.run_command(0);
+        app.input_buffer = "my branch's name".to_string();
+        app.submit_input();
+        assert_eq!(app.input_pending, None);
+        assert!(app.running_processes.contains_key(&0));
+        assert_eq!(
+            app.inputs.get(&0).map(String::as_str),
+            Some("my branch's name")
+        );
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while std::fs::read_to_string(&out).unwrap_or_default() != "my branch's name" {
+            assert!(Instant::now() < deadline, "command did not write $input");
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_switching_presets_keeps_merged_global_commands() {
+        let dir = std::env::temp_dir().join(format!("launch-bar-merge-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = format!(
+            "[window]\nmerge_global = true\n\n[[commands]]\nname = \"Git\"\ncmd = \"true\"\n{}",
+            CONFIG
+        );
+        std::fs::write(dir.join("launch-bar.toml"), config).unwrap();
+
+        let mut app = app_in(&dir, "Tools");
+        assert_eq!(command_names(&app), vec!["One", "Git"]);
+        assert!(app.switch_to_preset_named("Web"));
+        assert_eq!(command_names(&app), vec!["Two", "Git"]);
+        assert!(app.switch_to_preset_named("tools"));
+        assert_eq!(command_names(&app), vec!["One", "Git"]);
+        assert!(!app.switch_to_preset_named("Missing"));
+
+        std::fs::remove_dir_all(&dir).ok();
     }
 }
@@ -0,0 +1,91 @@
+//! Removing terminal escape codes from captured output
+
+/// Escape character that starts ANSI sequences
+const ESC: char = '\x1b';
+
+/// Text with ANSI escape sequences removed
+///
+/// Handles CSI sequences (`ESC [ ... final`, e.g. colors and cursor moves),
+/// OSC sequences (`ESC ] ... BEL` or `ESC ] ... ESC \`, e.g. hyperlinks and
+/// window titles) and two-character escapes (`ESC (B`-style charset switches
+/// are dropped with their one argument).
+pub fn strip_ansi(s: &str) -> String {
+    if !s.contains(ESC) {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != ESC {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters and intermediates, then one final byte in @..~
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: until BEL or ST (ESC \)
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == ESC && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Charset designation takes one more character
+            Some('(' | ')' | '*' | '+') => {
+                chars.next();
+            }
+            // Other two-character escapes (ESC 7, ESC M, ...)
+            Some(_) | None => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_colors() {
+        let colored =
+            "\x1b[1m\x1b[32m    Finished\x1b[0m `dev` profile in \x1b[38;5;208m1.2s\x1b[m";
+        assert_eq!(strip_ansi(colored), "    Finished `dev` profile in 1.2s");
+        assert_eq!(
+            strip_ansi("\x1b[31merror\x1b[39m: \x1b[1;4mboom\x1b[22;24m"),
+            "error: boom"
+        );
+    }
+
+    #[test]
+    fn test_strip_cursor_and_osc_sequences() {
+        assert_eq!(strip_ansi("\x1b[2K\x1b[1G50% done"), "50% done");
+        assert_eq!(
+            strip_ansi("see \x1b]8;;https://example.com\x1b\\docs\x1b]8;;\x1b\\ here"),
+            "see docs here"
+        );
+        assert_eq!(strip_ansi("\x1b]0;title\x07ok"), "ok");
+        assert_eq!(strip_ansi("\x1b(Bplain\x1b7"), "plain");
+    }
+
+    #[test]
+    fn test_plain_text_unchanged() {
+        assert_eq!(
+            strip_ansi("test result: ok. 3 passed"),
+            "test result: ok. 3 passed"
+        );
+        assert_eq!(strip_ansi("[1m not an escape"), "[1m not an escape");
+        // A truncated sequence at the end is dropped
+        assert_eq!(strip_ansi("done\x1b[3"), "done");
+    }
+}
@@ -0,0 +1,137 @@
+//! Numeric badges drawn on command icons
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Parse badge command stdout into a count (None hides the badge)
+///
+/// Empty output, non-numeric output and zero all hide the badge.
+pub fn parse_badge(stdout: &str) -> Option<u64> {
+    let count: u64 = stdout.trim().parse().ok()?;
+    (count > 0).then_some(count)
+}
+
+/// Format a badge count for display (large counts are capped at "99+")
+pub fn format_badge(count: u64) -> String {
+    if count > 99 {
+        "99+".to_string()
+    } else {
+        count.to_string()
+    }
+}
+
+/// Badge counts per command index with refresh bookkeeping
+#[derive(Debug, Default)]
+pub struct Badges {
+    counts: HashMap<usize, u64>,
+    in_flight: HashSet<usize>,
+    last_refresh: Option<Instant>,
+    generation: u64,
+}
+
+impl Badges {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current count for a command (None = no badge shown)
+    pub fn get(&self, index: usize) -> Option<u64> {
+        self.counts.get(&index).copied()
+    }
+
+    /// Returns true if a refresh is due for the given interval
+    pub fn is_due(&self, interval: Duration) -> bool {
+        self.last_refresh
+            .map(|t| t.elapsed() >= interval)
+            .unwrap_or(true)
+    }
+
+    /// Start a refresh round, returning the generation to tag results with
+    pub fn start_refresh(&mut self) -> u64 {
+        self.last_refresh = Some(Instant::now());
+        self.generation
+    }
+
+    /// Mark a badge command as running; false if it is already in flight
+    pub fn mark_in_flight(&mut self, index: usize) -> bool {
+        self.in_flight.insert(index)
+    }
+
+    /// Store a badge result (ignored if stale)
+    pub fn set(&mut self, index: usize, count: Option<u64>, generation: u64) {
+        if generation != self.generation {
+            return;
+        }
+        self.in_flight.remove(&index);
+        match count {
+            Some(count) => self.counts.insert(index, count),
+            None => self.counts.remove(&index),
+        };
+    }
+
+    /// Force a refresh on the next check (e.g. on watcher events)
+    pub fn invalidate(&mut self) {
+        self.last_refresh = None;
+    }
+
+    /// Forget everything (e.g. when the command list changes)
+    pub fn clear(&mut self) {
+        self.counts.clear();
+        self.in_flight.clear();
+        self.last_refresh = None;
+        self.generation += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_badge() {
+        assert_eq!(parse_badge("3\n"), Some(3));
+        assert_eq!(parse_badge("  42  "), Some(42));
+        assert_eq!(parse_badge("0"), None);
+        assert_eq!(parse_badge(""), None);
+        assert_eq!(parse_badge("error"), None);
+        assert_eq!(parse_badge("-1"), None);
+    }
+
+    #[test]
+    fn test_format_badge() {
+        assert_eq!(format_badge(1), "1");
+        assert_eq!(format_badge(99), "99");
+        assert_eq!(format_badge(100), "99+");
+    }
+
+    #[test]
+    fn test_badges_refresh_cycle() {
+        let mut badges = Badges::new();
+        assert!(badges.is_due(Duration::from_secs(30)));
+
+        let generation = badges.start_refresh();
+        assert!(!badges.is_due(Duration::from_secs(30)));
+        assert!(badges.mark_in_flight(0));
+        assert!(!badges.mark_in_flight(0));
+
+        badges.set(0, Some(5), generation);
+        assert_eq!(badges.get(0), Some(5));
+        assert!(badges.mark_in_flight(0));
+
+        badges.set(0, None, generation);
+        assert_eq!(badges.get(0), None);
+
+        badges.invalidate();
+        assert!(badges.is_due(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_badges_stale_result_ignored() {
+        let mut badges = Badges::new();
+        let generation = badges.start_refresh();
+        badges.mark_in_flight(0);
+        badges.clear();
+        badges.set(0, Some(5), generation);
+        assert_eq!(badges.get(0), None);
+    }
+}
@@ -0,0 +1,173 @@
+//! Size-capped output storage (`window.max_output_bytes`)
+
+/// Text buffer keeping at most `max_bytes` and `max_lines` of the most recent output
+///
+/// Older text is dropped from the front, up to the next line start when the
+/// kept tail has one; [`BoundedBuffer::lines`] starts with
+/// a `…[truncated N bytes]…` marker once anything was dropped. A limit of 0
+/// keeps everything.
+#[derive(Debug, Clone, Default)]
+pub struct BoundedBuffer {
+    text: String,
+    /// Byte offset of the kept tail in `text` (dropped text is compacted lazily)
+    start: usize,
+    /// Complete lines in the kept tail
+    line_count: usize,
+    truncated: usize,
+    max_bytes: usize,
+    max_lines: usize,
+}
+
+impl BoundedBuffer {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            ..Self::default()
+        }
+    }
+
+    /// Also keep at most `max_lines` complete lines (0 = no line limit)
+    pub fn with_max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = max_lines;
+        self
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        self.text.push_str(s);
+        self.line_count += s.matches('\n').count();
+        if self.max_bytes > 0 && self.len() > self.max_bytes {
+            let mut cut = self.text.len() - self.max_bytes;
+            while !self.text.is_char_boundary(cut) {
+                cut += 1;
+            }
+            // Skip the rest of a partly dropped line
+            if let Some(newline) = self.text[cut..].find('\n') {
+                if cut + newline + 1 < self.text.len() {
+                    cut += newline + 1;
+                }
+            }
+            self.drop_front(cut);
+        }
+        if self.max_lines > 0 && self.line_count > self.max_lines {
+            let excess = self.line_count - self.max_lines;
+            let cut = self
+                .as_str()
+                .match_indices('\n')
+                .nth(excess - 1)
+                .map_or(self.text.len(), |(newline, _)| self.start + newline + 1);
+            self.drop_front(cut);
+        }
+    }
+
+    /// Drop the kept text before byte `cut` of `text`
+    fn drop_front(&mut self, cut: usize) {
+        self.line_count -= self.text[self.start..cut].matches('\n').count();
+        self.truncated += cut - self.start;
+        self.start = cut;
+        if self.start > self.text.len() / 2 {
+            self.text.drain(..self.start);
+            self.start = 0;
+        }
+    }
+
+    /// Append a line of output (without its newline)
+    pub fn push_line(&mut self, line: &str) {
+        self.push_str(line);
+        self.push_str("\n");
+    }
+
+    /// Bytes currently kept
+    pub fn len(&self) -> usize {
+        self.text.len() - self.start
+    }
+
+    /// The kept tail of the output
+    pub fn as_str(&self) -> &str {
+        &self.text[self.start..]
+    }
+
+    /// Bytes dropped from the front so far
+    pub fn truncated_bytes(&self) -> usize {
+        self.truncated
+    }
+
+    /// Marker line shown in place of dropped output
+    pub fn marker(&self) -> Option<String> {
+        let truncated = self.truncated_bytes();
+        (truncated > 0).then(|| format!("…[truncated {} bytes]…", truncated))
+    }
+
+    /// Kept lines, preceded by the truncation marker if output was dropped
+    pub fn lines(&self) -> Vec<String> {
+        self.marker()
+            .into_iter()
+            .chain(self.as_str().lines().map(str::to_string))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_past_cap_keeps_tail() {
+        let mut buffer = BoundedBuffer::new(12);
+        buffer.push_line("first");
+        buffer.push_line("line");
+        assert_eq!(buffer.truncated_bytes(), 0);
+        assert_eq!(buffer.lines(), vec!["first", "line"]);
+
+        buffer.push_line("second");
+        assert_eq!(buffer.len(), 12);
+        assert_eq!(buffer.as_str(), "line\nsecond\n");
+        assert_eq!(buffer.truncated_bytes(), 6);
+
+        // A single push larger than the cap keeps only its end
+        buffer.push_str(&"x".repeat(100));
+        assert_eq!(buffer.as_str(), "x".repeat(12));
+        assert_eq!(buffer.truncated_bytes(), 106);
+    }
+
+    #[test]
+    fn test_truncation_marker() {
+        let mut buffer = BoundedBuffer::new(8);
+        assert_eq!(buffer.marker(), None);
+        buffer.push_line("aaaa");
+        buffer.push_line("bbb");
+        buffer.push_line("ccc");
+        assert_eq!(buffer.marker().as_deref(), Some("…[truncated 5 bytes]…"));
+        assert_eq!(buffer.lines(), vec!["…[truncated 5 bytes]…", "bbb", "ccc"]);
+    }
+
+    #[test]
+    fn test_cut_respects_char_boundaries_and_zero_is_unlimited() {
+        let mut buffer = BoundedBuffer::new(4);
+        buffer.push_str("aé€");
+        // Cutting at byte 2 would split "é"; the whole character goes
+        assert_eq!(buffer.as_str(), "€");
+        assert_eq!(buffer.truncated_bytes(), 3);
+
+        let mut unlimited = BoundedBuffer::new(0);
+        unlimited.push_str(&"y".repeat(1000));
+        assert_eq!(unlimited.len(), 1000);
+        assert_eq!(unlimited.marker(), None);
+    }
+
+    #[test]
+    fn test_line_limit_applies_with_unlimited_bytes() {
+        let mut buffer = BoundedBuffer::new(0).with_max_lines(2);
+        for line in ["one", "two", "three"] {
+            buffer.push_line(line);
+        }
+        assert_eq!(
+            buffer.lines(),
+            vec!["…[truncated 4 bytes]…", "two", "three"]
+        );
+
+        // Several lines in one push; an unfinished line doesn't count yet
+        buffer.push_str("four\nfive\nsix");
+        assert_eq!(buffer.as_str(), "four\nfive\nsix");
+        assert_eq!(buffer.truncated_bytes(), 14);
+    }
+}
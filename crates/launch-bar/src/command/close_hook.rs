@@ -0,0 +1,88 @@
+//! `window.on_close`: a shell command run before the bar exits
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::platform::{spawn_shell_command_silent, ShellOptions};
+
+/// Longest the bar waits for the close hook before killing it
+pub const CLOSE_HOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Close hook with everything needed to run it from `on_exit` (no ctx there)
+#[derive(Debug, Clone)]
+pub struct CloseHook {
+    pub cmd: String,
+    pub cwd: PathBuf,
+    pub options: ShellOptions,
+    pub timeout: Duration,
+}
+
+impl CloseHook {
+    /// None if `on_close` is unset or blank
+    pub fn new(cmd: Option<&str>, cwd: &Path, options: ShellOptions) -> Option<Self> {
+        let cmd = cmd.map(str::trim).filter(|c| !c.is_empty())?;
+        Some(Self {
+            cmd: cmd.to_string(),
+            cwd: cwd.to_path_buf(),
+            options,
+            timeout: CLOSE_HOOK_TIMEOUT,
+        })
+    }
+
+    /// Run to completion, killing the command once `timeout` passes
+    ///
+    /// Returns whether it exited successfully.
+    pub fn run(&self) -> Result<bool, String> {
+        let mut child = spawn_shell_command_silent(&self.cmd, &self.cwd, &self.options)
+            .map_err(|e| e.to_string())?;
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+                return Ok(status.success());
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {}ms", self.timeout.as_millis()));
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_captures_command() {
+        let hook = CloseHook::new(
+            Some(" docker compose stop "),
+            Path::new("/srv"),
+            ShellOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(hook.cmd, "docker compose stop");
+        assert_eq!(hook.cwd, PathBuf::from("/srv"));
+        assert_eq!(hook.timeout, CLOSE_HOOK_TIMEOUT);
+
+        assert!(CloseHook::new(None, Path::new("."), ShellOptions::default()).is_none());
+        assert!(CloseHook::new(Some("  "), Path::new("."), ShellOptions::default()).is_none());
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_run_applies_timeout() {
+        let mut hook =
+            CloseHook::new(Some("exit 0"), Path::new("."), ShellOptions::default()).unwrap();
+        assert_eq!(hook.run(), Ok(true));
+
+        hook.cmd = "sleep 5".to_string();
+        hook.timeout = Duration::from_millis(100);
+        let started = Instant::now();
+        assert!(hook.run().is_err());
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
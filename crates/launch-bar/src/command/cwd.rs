@@ -0,0 +1,39 @@
+//! Working directory validation
+
+use std::path::Path;
+
+/// Check that a command's working directory exists and is a directory
+pub fn check_cwd(cwd: &Path) -> Result<(), String> {
+    if cwd.is_dir() {
+        Ok(())
+    } else {
+        Err(format!("Bad cwd: {}", cwd.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_cwd_accepts_directory() {
+        assert!(check_cwd(&std::env::temp_dir()).is_ok());
+    }
+
+    #[test]
+    fn test_check_cwd_rejects_bogus_path() {
+        let bogus = std::env::temp_dir().join("launch-bar-no-such-dir/nested");
+        assert_eq!(
+            check_cwd(&bogus),
+            Err(format!("Bad cwd: {}", bogus.display()))
+        );
+    }
+
+    #[test]
+    fn test_check_cwd_rejects_file() {
+        let file = std::env::temp_dir().join(format!("launch-bar-cwd-{}", std::process::id()));
+        std::fs::write(&file, "").unwrap();
+        assert!(check_cwd(&file).is_err());
+        std::fs::remove_file(&file).ok();
+    }
+}
@@ -0,0 +1,175 @@
+//! Cached enable state for commands with an `enabled_when` predicate
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Quiet time after a watcher event before predicates are re-evaluated
+pub const ENABLED_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Longest a stale state is kept while watcher events keep coming
+pub const ENABLED_MAX_DELAY: Duration = Duration::from_secs(2);
+
+/// Enable state per command index, invalidated on file changes
+///
+/// The last known state is kept while a re-evaluation is in flight so buttons
+/// don't flicker, and the newest result that arrives is kept even if files
+/// changed meanwhile. Each predicate runs at most once at a time, and bursts
+/// of watcher events are debounced. Only `clear` discards running evaluations.
+#[derive(Debug, Default)]
+pub struct EnabledCache {
+    states: HashMap<usize, bool>,
+    /// Revision each predicate was last started at
+    requested: HashMap<usize, u64>,
+    in_flight: HashSet<usize>,
+    /// Bumped by each watcher event
+    revision: u64,
+    /// Bumped by `clear`; results started before are dropped
+    generation: u64,
+    /// First and latest watcher event since predicates were last started
+    changed: Option<(Instant, Instant)>,
+}
+
+impl EnabledCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Last known enable state (None until first evaluated)
+    pub fn get(&self, index: usize) -> Option<bool> {
+        self.states.get(&index).copied()
+    }
+
+    /// Returns true if the predicate should be (re-)evaluated at `now`
+    ///
+    /// Not while it is still running, and only once watcher events have been
+    /// quiet for `ENABLED_DEBOUNCE` (or kept coming for `ENABLED_MAX_DELAY`).
+    pub fn needs_eval(&self, index: usize, now: Instant) -> bool {
+        let settled = self.changed.is_none_or(|(first, last)| {
+            now.duration_since(last) >= ENABLED_DEBOUNCE
+                || now.duration_since(first) >= ENABLED_MAX_DELAY
+        });
+        settled
+            && !self.in_flight.contains(&index)
+            && self.requested.get(&index) != Some(&self.revision)
+    }
+
+    /// Mark an evaluation as started, returning the generation to tag its result with
+    pub fn mark_requested(&mut self, index: usize) -> u64 {
+        self.requested.insert(index, self.revision);
+        self.in_flight.insert(index);
+        self.changed = None;
+        self.generation
+    }
+
+    /// Store an evaluation result (ignored if started before `clear`)
+    pub fn set(&mut self, index: usize, enabled: bool, generation: u64) {
+        if generation == self.generation {
+            self.in_flight.remove(&index);
+            self.states.insert(index, enabled);
+        }
+    }
+
+    /// Mark all entries stale (e.g. on watcher events), keeping last known states
+    pub fn invalidate(&mut self, now: Instant) {
+        self.revision += 1;
+        let first = self.changed.map_or(now, |(first, _)| first);
+        self.changed = Some((first, now));
+    }
+
+    /// Forget everything (e.g. when the command list changes)
+    pub fn clear(&mut self) {
+        self.states.clear();
+        self.requested.clear();
+        self.in_flight.clear();
+        self.changed = None;
+        self.generation += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eval_once_per_revision() {
+        let now = Instant::now();
+        let mut cache = EnabledCache::new();
+        assert!(cache.needs_eval(0, now));
+        let generation = cache.mark_requested(0);
+        assert!(!cache.needs_eval(0, now));
+        assert!(cache.needs_eval(1, now));
+
+        cache.set(0, true, generation);
+        assert_eq!(cache.get(0), Some(true));
+        assert_eq!(cache.get(1), None);
+        assert!(!cache.needs_eval(0, now));
+    }
+
+    #[test]
+    fn test_invalidate_on_watcher_event() {
+        let now = Instant::now();
+        let mut cache = EnabledCache::new();
+        let generation = cache.mark_requested(2);
+        cache.set(2, false, generation);
+
+        cache.invalidate(now);
+        // Debounced, then due again
+        assert!(!cache.needs_eval(2, now));
+        assert!(cache.needs_eval(2, now + ENABLED_DEBOUNCE));
+        // Last known state is kept until the new result arrives
+        assert_eq!(cache.get(2), Some(false));
+
+        let generation = cache.mark_requested(2);
+        cache.set(2, true, generation);
+        assert_eq!(cache.get(2), Some(true));
+    }
+
+    #[test]
+    fn test_result_after_invalidation_is_kept() {
+        let now = Instant::now();
+        let mut cache = EnabledCache::new();
+        let generation = cache.mark_requested(0);
+        cache.invalidate(now);
+        // Still running: no second evaluation
+        assert!(!cache.needs_eval(0, now + ENABLED_DEBOUNCE));
+
+        cache.set(0, true, generation);
+        assert_eq!(cache.get(0), Some(true));
+        // Files changed while it ran, so it runs once more
+        assert!(cache.needs_eval(0, now + ENABLED_DEBOUNCE));
+    }
+
+    #[test]
+    fn test_steady_events_still_reevaluate() {
+        let start = Instant::now();
+        let mut cache = EnabledCache::new();
+        let generation = cache.mark_requested(0);
+        cache.set(0, true, generation);
+
+        let step = ENABLED_DEBOUNCE / 2;
+        let mut now = start;
+        while now < start + ENABLED_MAX_DELAY {
+            cache.invalidate(now);
+            assert!(!cache.needs_eval(0, now));
+            now += step;
+        }
+        cache.invalidate(now);
+        assert!(cache.needs_eval(0, now));
+    }
+
+    #[test]
+    fn test_clear_forgets_states() {
+        let now = Instant::now();
+        let mut cache = EnabledCache::new();
+        let generation = cache.mark_requested(0);
+        cache.set(0, true, generation);
+        let running = cache.mark_requested(1);
+        cache.clear();
+        assert_eq!(cache.get(0), None);
+        assert!(cache.needs_eval(0, now));
+        // Results started before the clear are dropped
+        cache.set(1, true, running);
+        assert_eq!(cache.get(1), None);
+        assert!(cache.needs_eval(1, now));
+    }
+}
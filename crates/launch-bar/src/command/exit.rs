@@ -0,0 +1,140 @@
+//! Exit codes and signals of finished commands, for the status line
+
+use std::process::ExitStatus;
+
+/// How a finished command ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandExit {
+    /// Exited with this code (scripts report 0/1)
+    Code(i32),
+    /// Killed by this signal (unix)
+    Signal(i32),
+    /// Exit status couldn't be read
+    Unknown,
+}
+
+impl CommandExit {
+    /// Read the code, or the signal on unix, from a process's exit status
+    pub fn from_status(status: ExitStatus) -> Self {
+        if let Some(code) = status.code() {
+            return CommandExit::Code(code);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if let Some(signal) = status.signal() {
+                return CommandExit::Signal(signal);
+            }
+        }
+        CommandExit::Unknown
+    }
+
+    /// Exit code for a script's result: 0 on success, 1 on failure
+    pub fn from_success(success: bool) -> Self {
+        CommandExit::Code(if success { 0 } else { 1 })
+    }
+
+    /// Exit code or signal name, e.g. `exit 2` / `SIGTERM` (None for a clean or unknown exit)
+    fn detail(self) -> Option<String> {
+        match self {
+            CommandExit::Code(0) | CommandExit::Unknown => None,
+            CommandExit::Code(code) => Some(format!("exit {}", code)),
+            CommandExit::Signal(signal) => Some(signal_name(signal)),
+        }
+    }
+}
+
+/// Status line for a finished command: `Done: name`, `Exited 1: name`, `Killed by SIGKILL: name`
+///
+/// A command that counts as a success despite a non-zero exit (`success_pattern`)
+/// shows the code after its name.
+pub fn exit_message(name: &str, success: bool, exit: CommandExit) -> String {
+    match (success, exit) {
+        (true, exit) => match exit.detail() {
+            Some(detail) => format!("Done: {} ({})", name, detail),
+            None => format!("Done: {}", name),
+        },
+        (false, CommandExit::Code(code)) => format!("Exited {}: {}", code, name),
+        (false, CommandExit::Signal(signal)) => {
+            format!("Killed by {}: {}", signal_name(signal), name)
+        }
+        (false, CommandExit::Unknown) => format!("Failed: {}", name),
+    }
+}
+
+/// Name of a common unix signal (numbers shared by Linux and macOS), else `signal N`
+fn signal_name(signal: i32) -> String {
+    let name = match signal {
+        1 => "SIGHUP",
+        2 => "SIGINT",
+        3 => "SIGQUIT",
+        4 => "SIGILL",
+        5 => "SIGTRAP",
+        6 => "SIGABRT",
+        8 => "SIGFPE",
+        9 => "SIGKILL",
+        11 => "SIGSEGV",
+        13 => "SIGPIPE",
+        14 => "SIGALRM",
+        15 => "SIGTERM",
+        _ => return format!("signal {}", signal),
+    };
+    name.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_message() {
+        assert_eq!(
+            exit_message("Build", true, CommandExit::Code(0)),
+            "Done: Build"
+        );
+        assert_eq!(
+            exit_message("Build", false, CommandExit::Code(1)),
+            "Exited 1: Build"
+        );
+        assert_eq!(
+            exit_message("Build", true, CommandExit::Code(2)),
+            "Done: Build (exit 2)"
+        );
+        assert_eq!(
+            exit_message("Build", false, CommandExit::Unknown),
+            "Failed: Build"
+        );
+    }
+
+    #[test]
+    fn test_exit_message_signal() {
+        assert_eq!(
+            exit_message("Server", false, CommandExit::Signal(9)),
+            "Killed by SIGKILL: Server"
+        );
+        assert_eq!(
+            exit_message("Server", false, CommandExit::Signal(42)),
+            "Killed by signal 42: Server"
+        );
+    }
+
+    #[test]
+    fn test_script_exit_codes() {
+        assert_eq!(CommandExit::from_success(true), CommandExit::Code(0));
+        assert_eq!(CommandExit::from_success(false), CommandExit::Code(1));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_from_status_reads_signal() {
+        use std::os::unix::process::ExitStatusExt;
+        assert_eq!(
+            CommandExit::from_status(ExitStatus::from_raw(15)),
+            CommandExit::Signal(15)
+        );
+        assert_eq!(
+            CommandExit::from_status(ExitStatus::from_raw(3 << 8)),
+            CommandExit::Code(3)
+        );
+    }
+}
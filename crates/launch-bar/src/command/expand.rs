@@ -0,0 +1,265 @@
+//! Variable expansion in shell commands
+
+use std::path::PathBuf;
+
+use arboard::Clipboard;
+
+use crate::platform::windows_quote_arg;
+
+/// Variable replaced with the clipboard text
+pub const CLIPBOARD_VAR: &str = "$clipboard";
+
+/// Variable replaced with the paths of files dropped onto the button
+pub const DROPPED_VAR: &str = "$dropped";
+
+/// Variable replaced with the text typed into the `prompt` input
+pub const INPUT_VAR: &str = "$input";
+
+/// Expand `$clipboard` in a command, reading the clipboard only when needed
+///
+/// Environment variables are left to the shell.
+pub fn expand_command(cmd: &str) -> Result<String, String> {
+    expand_with(cmd, || {
+        Clipboard::new().and_then(|mut cb| cb.get_text()).ok()
+    })
+}
+
+/// Expand `$clipboard` using the given clipboard reader
+fn expand_with(cmd: &str, clipboard: impl FnOnce() -> Option<String>) -> Result<String, String> {
+    if !cmd.contains(CLIPBOARD_VAR) {
+        return Ok(cmd.to_string());
+    }
+    match clipboard() {
+        Some(text) => Ok(cmd.replace(CLIPBOARD_VAR, &text)),
+        None => Err("Failed to read clipboard".to_string()),
+    }
+}
+
+/// Longest clipboard excerpt shown in a preview
+pub const PREVIEW_CLIPBOARD_MAX: usize = 40;
+
+/// Command as it would run, for display: environment variables and
+/// `$clipboard` (truncated) substituted
+pub fn preview_command(cmd: &str) -> String {
+    preview_with(
+        cmd,
+        || Clipboard::new().and_then(|mut cb| cb.get_text()).ok(),
+        |var| std::env::var(var).ok(),
+    )
+}
+
+/// Build a preview using the given clipboard reader and environment
+fn preview_with(
+    cmd: &str,
+    clipboard: impl FnOnce() -> Option<String>,
+    env: impl Fn(&str) -> Option<String>,
+) -> String {
+    // Unset variables (and our own `$clipboard` / `$dropped` / `$input`) stay as written
+    let expanded = shellexpand::env_with_context_no_errors(cmd, |var| match var {
+        "clipboard" | "dropped" | "input" => None,
+        var => env(var),
+    });
+    if !expanded.contains(CLIPBOARD_VAR) {
+        return expanded.into_owned();
+    }
+    let excerpt = match clipboard() {
+        Some(text) => truncate_preview(&text),
+        None => "<clipboard unavailable>".to_string(),
+    };
+    expanded.replace(CLIPBOARD_VAR, &excerpt)
+}
+
+/// Single-line excerpt of at most `PREVIEW_CLIPBOARD_MAX` characters
+fn truncate_preview(text: &str) -> String {
+    let flat: String = text
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    if flat.chars().count() <= PREVIEW_CLIPBOARD_MAX {
+        return flat;
+    }
+    let head: String = flat.chars().take(PREVIEW_CLIPBOARD_MAX).collect();
+    format!("{}…", head)
+}
+
+/// Replace `$dropped` with the dropped paths, quoted and joined by spaces
+pub fn expand_dropped(cmd: &str, paths: &[PathBuf]) -> String {
+    if !cmd.contains(DROPPED_VAR) {
+        return cmd.to_string();
+    }
+    let joined = paths
+        .iter()
+        .map(|path| quote_path(&path.to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join(" ");
+    cmd.replace(DROPPED_VAR, &joined)
+}
+
+/// Replace `$input` with the typed `prompt` value, quoted as one shell word
+///
+/// Only whole variable names are replaced (`$inputs` is left alone). Without
+/// a value (no `prompt`), the command is left as written.
+pub fn expand_input(cmd: &str, input: Option<&str>) -> String {
+    let Some(input) = input.filter(|_| cmd.contains(INPUT_VAR)) else {
+        return cmd.to_string();
+    };
+    let quoted = if cfg!(windows) {
+        quote_cmd_word(input)
+    } else {
+        quote_path(input)
+    };
+    let mut expanded = String::with_capacity(cmd.len());
+    let mut rest = cmd;
+    while let Some(start) = rest.find(INPUT_VAR) {
+        let end = start + INPUT_VAR.len();
+        let whole = !rest[end..].starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_');
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(if whole { &quoted } else { INPUT_VAR });
+        rest = &rest[end..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Quote free text as one argument for a `cmd /C` command line
+///
+/// The text is quoted for the program's own parser (`CommandLineToArgvW`
+/// rules), then every `cmd` metacharacter, including `"` and `%`, is escaped
+/// with `^`, so `cmd` neither expands `%VAR%` nor ends the quoting early.
+fn quote_cmd_word(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in windows_quote_arg(text).chars() {
+        if "()%!^\"<>&|".contains(c) {
+            escaped.push('^');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Quote a path (or other argument) for the shell unless it only has safe characters
+fn quote_path(path: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "/\\._-:+@%,".contains(c);
+    if !path.is_empty() && path.chars().all(safe) {
+        return path.to_string();
+    }
+    if cfg!(windows) {
+        format!("\"{}\"", path)
+    } else {
+        format!("'{}'", path.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_expand_dropped() {
+        let paths = [
+            PathBuf::from("/tmp/a.txt"),
+            PathBuf::from("/tmp/my file's.txt"),
+        ];
+        assert_eq!(
+            expand_dropped("wc -l $dropped", &paths),
+            r"wc -l /tmp/a.txt '/tmp/my file'\''s.txt'"
+        );
+        assert_eq!(expand_dropped("make", &paths), "make");
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_expand_input() {
+        assert_eq!(
+            expand_input("git switch -c $input", Some("feature/login")),
+            "git switch -c feature/login"
+        );
+        assert_eq!(
+            expand_input("git commit -m $input", Some("Fix the user's $HOME bug")),
+            r"git commit -m 'Fix the user'\''s $HOME bug'"
+        );
+        assert_eq!(expand_input("echo $input", Some("")), "echo ''");
+        assert_eq!(expand_input("echo $input", None), "echo $input");
+        assert_eq!(expand_input("make", Some("ignored")), "make");
+        // Only the whole variable name
+        assert_eq!(
+            expand_input("echo $input $inputs $input_x $input.txt", Some("a b")),
+            "echo 'a b' $inputs $input_x 'a b'.txt"
+        );
+    }
+
+    #[test]
+    fn test_quote_cmd_word() {
+        assert_eq!(quote_cmd_word("feature/login"), "feature/login");
+        assert_eq!(quote_cmd_word("two words"), "^\"two words^\"");
+        // A typed quote can't end the quoting, and %VAR% isn't expanded
+        assert_eq!(
+            quote_cmd_word("say \"hi\" & %PATH%"),
+            r#"^"say \^"hi\^" ^& ^%PATH^%^""#
+        );
+        assert_eq!(quote_cmd_word(""), "^\"^\"");
+    }
+
+    #[test]
+    fn test_expand_clipboard() {
+        assert_eq!(
+            expand_with("echo $clipboard", || Some("hi".to_string())),
+            Ok("echo hi".to_string())
+        );
+    }
+
+    #[test]
+    fn test_expand_without_variable_skips_clipboard() {
+        assert_eq!(
+            expand_with("echo $HOME", || panic!("clipboard should not be read")),
+            Ok("echo $HOME".to_string())
+        );
+    }
+
+    #[test]
+    fn test_preview_command() {
+        let env = |var: &str| (var == "USER").then(|| "me".to_string());
+        assert_eq!(
+            preview_with(
+                "say $clipboard to $USER in $UNSET",
+                || Some("hi".into()),
+                env
+            ),
+            "say hi to me in $UNSET"
+        );
+
+        let long = format!("line one\n{}", "x".repeat(100));
+        let preview = preview_with("echo '$clipboard'", || Some(long), env);
+        assert_eq!(
+            preview,
+            format!("echo 'line one {}…'", "x".repeat(PREVIEW_CLIPBOARD_MAX - 9))
+        );
+
+        assert_eq!(
+            preview_with(
+                "wc $dropped",
+                || panic!("clipboard should not be read"),
+                env
+            ),
+            "wc $dropped"
+        );
+        assert_eq!(
+            preview_with(
+                "echo $input",
+                || panic!("clipboard should not be read"),
+                env
+            ),
+            "echo $input"
+        );
+        assert_eq!(
+            preview_with("echo $clipboard", || None, env),
+            "echo <clipboard unavailable>"
+        );
+    }
+
+    #[test]
+    fn test_expand_clipboard_failure() {
+        assert!(expand_with("echo $clipboard", || None).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+//! Fallback chain for shell commands (`fallback = ["code .", "vi ."]`)
+
+use std::path::{Path, PathBuf};
+
+use crate::platform::{which_with, SHELL_BUILTINS};
+
+/// Exit codes shells use for "command not found" (`sh`: 127, `cmd.exe`: 9009)
+const NOT_FOUND_EXIT_CODES: &[i32] = &[127, 9009];
+
+/// Whether an exit code means the shell couldn't find the program
+pub fn is_not_found_exit(code: Option<i32>) -> bool {
+    code.is_some_and(|code| NOT_FOUND_EXIT_CODES.contains(&code))
+}
+
+/// Program a command line runs, with a leading `$VAR` looked up via `env`
+///
+/// Returns None if the variable is unset or empty.
+pub fn candidate_program(cmd: &str, env: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let word = cmd.split_whitespace().find(|word| !word.contains('='))?;
+    match word.strip_prefix('$') {
+        Some(var) => env(var.trim_matches(['{', '}']))?
+            .split_whitespace()
+            .next()
+            .map(str::to_string),
+        None => Some(word.to_string()),
+    }
+}
+
+/// Whether a command line's program can be run (builtins always can)
+pub fn is_available(cmd: &str, path_prepend: &[PathBuf]) -> bool {
+    let Some(program) = candidate_program(cmd, |var| std::env::var(var).ok()) else {
+        return false;
+    };
+    if SHELL_BUILTINS.contains(&program.as_str()) {
+        return true;
+    }
+    if program.contains(['/', '\\']) {
+        return Path::new(&program).exists();
+    }
+    which_with(&program, path_prepend).is_some()
+}
+
+/// First candidate at or after `start` whose program is available
+pub fn select_candidate(
+    candidates: &[String],
+    start: usize,
+    available: impl Fn(&str) -> bool,
+) -> Option<usize> {
+    candidates
+        .iter()
+        .enumerate()
+        .skip(start)
+        .find(|(_, cmd)| available(cmd))
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain() -> Vec<String> {
+        vec![
+            "$EDITOR .".to_string(),
+            "code .".to_string(),
+            "vi .".to_string(),
+        ]
+    }
+
+    fn with_binaries<'a>(present: &'a [&str]) -> impl Fn(&str) -> bool + 'a {
+        move |cmd| {
+            candidate_program(cmd, |_| None)
+                .is_some_and(|program| present.contains(&program.as_str()))
+        }
+    }
+
+    #[test]
+    fn test_select_first_present() {
+        assert_eq!(
+            select_candidate(&chain(), 0, with_binaries(&["code", "vi"])),
+            Some(1)
+        );
+        assert_eq!(
+            select_candidate(&chain(), 0, with_binaries(&["vi"])),
+            Some(2)
+        );
+        assert_eq!(select_candidate(&chain(), 0, with_binaries(&[])), None);
+    }
+
+    #[test]
+    fn test_select_after_failed_candidate() {
+        let present = with_binaries(&["code", "vi"]);
+        assert_eq!(select_candidate(&chain(), 2, &present), Some(2));
+        assert_eq!(select_candidate(&chain(), 3, &present), None);
+    }
+
+    #[test]
+    fn test_candidate_program_expands_variable() {
+        let env = |var: &str| (var == "EDITOR").then(|| "nvim -p".to_string());
+        assert_eq!(candidate_program("$EDITOR .", env).as_deref(), Some("nvim"));
+        assert_eq!(
+            candidate_program("${EDITOR} .", env).as_deref(),
+            Some("nvim")
+        );
+        assert_eq!(candidate_program("$VISUAL .", env), None);
+        assert_eq!(
+            candidate_program("RUST_LOG=debug cargo run", env).as_deref(),
+            Some("cargo")
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_is_available() {
+        let dir = std::env::temp_dir().join(format!("launch-bar-fallback-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("my-editor"), "").unwrap();
+        let prepend = std::slice::from_ref(&dir);
+
+        assert!(is_available("my-editor file.txt", prepend));
+        assert!(!is_available("no-such-editor-xyz file.txt", prepend));
+        assert!(is_available("cd sub && make", prepend));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_not_found_exit() {
+        assert!(is_not_found_exit(Some(127)));
+        assert!(!is_not_found_exit(Some(1)));
+        assert!(!is_not_found_exit(None));
+    }
+}
@@ -0,0 +1,44 @@
+//! Success/failure hooks fired when a command finishes
+
+use crate::config::CommandConfig;
+
+/// Select the hook to fire for a finished command, if any
+pub fn select_hook(cmd: &CommandConfig, success: bool) -> Option<&str> {
+    if success {
+        cmd.on_success.as_deref()
+    } else {
+        cmd.on_failure.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_command(on_success: Option<&str>, on_failure: Option<&str>) -> CommandConfig {
+        let mut cmd: CommandConfig = toml::from_str("name = \"Build\"").unwrap();
+        cmd.on_success = on_success.map(|s| s.to_string());
+        cmd.on_failure = on_failure.map(|s| s.to_string());
+        cmd
+    }
+
+    #[test]
+    fn test_hook_on_success() {
+        let cmd = make_command(Some("notify-send ok"), Some("open build.log"));
+        assert_eq!(select_hook(&cmd, true), Some("notify-send ok"));
+    }
+
+    #[test]
+    fn test_hook_on_failure() {
+        let cmd = make_command(Some("notify-send ok"), Some("open build.log"));
+        assert_eq!(select_hook(&cmd, false), Some("open build.log"));
+    }
+
+    #[test]
+    fn test_no_hook_configured() {
+        let cmd = make_command(None, Some("open build.log"));
+        assert_eq!(select_hook(&cmd, true), None);
+        let cmd = make_command(Some("notify-send ok"), None);
+        assert_eq!(select_hook(&cmd, false), None);
+    }
+}
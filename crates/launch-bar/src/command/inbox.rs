@@ -0,0 +1,96 @@
+//! Output of commands that finished in the background, with an unread count
+
+use std::collections::VecDeque;
+
+/// Oldest entries are dropped beyond this many
+pub const INBOX_CAPACITY: usize = 50;
+
+/// One finished command
+#[derive(Debug, Clone, PartialEq)]
+pub struct InboxEntry {
+    pub name: String,
+    pub success: bool,
+    /// Last output line (or the script's result)
+    pub output: String,
+}
+
+/// Finished background commands, newest first
+#[derive(Debug, Default)]
+pub struct Inbox {
+    entries: VecDeque<InboxEntry>,
+    unread: usize,
+}
+
+impl Inbox {
+    /// Add a finished command as unread
+    pub fn push(&mut self, entry: InboxEntry) {
+        self.entries.push_front(entry);
+        self.entries.truncate(INBOX_CAPACITY);
+        self.unread = (self.unread + 1).min(self.entries.len());
+    }
+
+    /// Number of entries added since the inbox was last viewed
+    pub fn unread(&self) -> usize {
+        self.unread
+    }
+
+    /// Clear the unread count (the inbox was viewed)
+    pub fn mark_read(&mut self) {
+        self.unread = 0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entries, newest first
+    pub fn entries(&self) -> impl Iterator<Item = &InboxEntry> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str) -> InboxEntry {
+        InboxEntry {
+            name: name.to_string(),
+            success: true,
+            output: "ok".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_unread_count() {
+        let mut inbox = Inbox::default();
+        assert_eq!(inbox.unread(), 0);
+
+        inbox.push(entry("Sync"));
+        inbox.push(entry("Lint"));
+        assert_eq!(inbox.unread(), 2);
+
+        inbox.mark_read();
+        assert_eq!(inbox.unread(), 0);
+        assert!(!inbox.is_empty());
+
+        inbox.push(entry("Sync"));
+        assert_eq!(inbox.unread(), 1);
+        let names: Vec<_> = inbox.entries().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["Sync", "Lint", "Sync"]);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut inbox = Inbox::default();
+        for i in 0..INBOX_CAPACITY + 5 {
+            inbox.push(entry(&i.to_string()));
+        }
+        assert_eq!(inbox.entries().count(), INBOX_CAPACITY);
+        assert_eq!(inbox.unread(), INBOX_CAPACITY);
+        assert_eq!(
+            inbox.entries().next().unwrap().name,
+            (INBOX_CAPACITY + 4).to_string()
+        );
+    }
+}
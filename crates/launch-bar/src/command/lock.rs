@@ -0,0 +1,74 @@
+//! Named locks keeping commands that share a `lock` from running together
+
+use std::collections::HashMap;
+
+/// Lock names currently held, with the command holding each
+#[derive(Debug, Default)]
+pub struct CommandLocks {
+    held: HashMap<String, usize>,
+}
+
+impl CommandLocks {
+    /// Take `name` for command `index`
+    ///
+    /// Returns the holder's index if another command has it. Re-acquiring a
+    /// lock the command already holds succeeds.
+    pub fn acquire(&mut self, name: &str, index: usize) -> Result<(), usize> {
+        match self.held.get(name) {
+            Some(&holder) if holder != index => Err(holder),
+            _ => {
+                self.held.insert(name.to_string(), index);
+                Ok(())
+            }
+        }
+    }
+
+    /// Release every lock held by command `index`
+    pub fn release(&mut self, index: usize) {
+        self.held.retain(|_, holder| *holder != index);
+    }
+
+    /// Keep only the locks whose holder passes `keep`
+    pub fn retain(&mut self, keep: impl Fn(usize) -> bool) {
+        self.held.retain(|_, holder| keep(*holder));
+    }
+
+    /// Drop all locks (e.g. when the command list changes)
+    pub fn clear(&mut self) {
+        self.held.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acquire_contend_release() {
+        let mut locks = CommandLocks::default();
+        assert_eq!(locks.acquire("cargo", 0), Ok(()));
+        // Same command may take it again; others are refused
+        assert_eq!(locks.acquire("cargo", 0), Ok(()));
+        assert_eq!(locks.acquire("cargo", 2), Err(0));
+        // Different names don't contend
+        assert_eq!(locks.acquire("npm", 2), Ok(()));
+
+        locks.release(0);
+        assert_eq!(locks.acquire("cargo", 2), Ok(()));
+        assert_eq!(locks.acquire("cargo", 1), Err(2));
+
+        locks.release(2);
+        assert_eq!(locks.acquire("npm", 1), Ok(()));
+        assert_eq!(locks.acquire("cargo", 1), Ok(()));
+    }
+
+    #[test]
+    fn test_clear_releases_all() {
+        let mut locks = CommandLocks::default();
+        locks.acquire("cargo", 0).unwrap();
+        locks.acquire("npm", 1).unwrap();
+        locks.clear();
+        assert_eq!(locks.acquire("cargo", 3), Ok(()));
+        assert_eq!(locks.acquire("npm", 3), Ok(()));
+    }
+}
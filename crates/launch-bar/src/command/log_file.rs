@@ -0,0 +1,107 @@
+//! Per-command output log files (`log_to`)
+
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Resolve a `log_to` template to a path
+///
+/// `{{date}}` becomes `date` (`YYYY-MM-DD`); relative paths are under `cwd`.
+pub fn render_log_path(template: &str, cwd: &Path, date: &str) -> PathBuf {
+    cwd.join(template.replace("{{date}}", date))
+}
+
+/// Open options for a log file: appended to with `log_append`, truncated otherwise
+pub fn log_open_options(append: bool) -> OpenOptions {
+    let mut options = OpenOptions::new();
+    options.create(true);
+    if append {
+        options.append(true);
+    } else {
+        options.write(true).truncate(true);
+    }
+    options
+}
+
+/// Open (creating parent directories) the log file for a `log_to` template
+pub fn open_log(template: &str, cwd: &Path, append: bool) -> std::io::Result<File> {
+    let path = render_log_path(template, cwd, &utc_date(SystemTime::now()));
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    log_open_options(append).open(&path)
+}
+
+/// UTC calendar date of `time` as `YYYY-MM-DD`
+fn utc_date(time: SystemTime) -> String {
+    let days = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() / 86_400) as i64;
+    // Civil-from-days (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_render_log_path() {
+        let cwd = Path::new("/work/api");
+        assert_eq!(
+            render_log_path("logs/build-{{date}}.log", cwd, "2024-03-01"),
+            PathBuf::from("/work/api/logs/build-2024-03-01.log")
+        );
+        assert_eq!(
+            render_log_path("/var/log/ci.log", cwd, "2024-03-01"),
+            PathBuf::from("/var/log/ci.log")
+        );
+    }
+
+    #[test]
+    fn test_utc_date() {
+        assert_eq!(utc_date(UNIX_EPOCH), "1970-01-01");
+        let leap_day = UNIX_EPOCH + Duration::from_secs(1_709_164_800);
+        assert_eq!(utc_date(leap_day), "2024-02-29");
+    }
+
+    #[test]
+    fn test_log_open_options() {
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("launch-bar-log-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("run.log");
+
+        log_open_options(false)
+            .open(&path)
+            .unwrap()
+            .write_all(b"one\n")
+            .unwrap();
+        log_open_options(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"two\n")
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "one\ntwo\n");
+
+        log_open_options(false)
+            .open(&path)
+            .unwrap()
+            .write_all(b"three\n")
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "three\n");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,82 @@
+//! Command lookup for the CLI
+
+use crate::config::CommandConfig;
+
+/// Find a command by name, then alias (case-insensitive), then 1-based position
+///
+/// Names win over aliases, so an alias that collides with another command's
+/// name never matches.
+pub fn find_command(commands: &[CommandConfig], key: &str) -> Option<usize> {
+    commands
+        .iter()
+        .position(|c| c.name.eq_ignore_ascii_case(key))
+        .or_else(|| {
+            commands.iter().position(|c| {
+                c.alias
+                    .as_deref()
+                    .is_some_and(|alias| alias.eq_ignore_ascii_case(key))
+            })
+        })
+        .or_else(|| {
+            key.parse::<usize>()
+                .ok()
+                .filter(|n| (1..=commands.len()).contains(n))
+                .map(|n| n - 1)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_command(name: &str) -> CommandConfig {
+        toml::from_str(&format!("name = \"{}\"\ncmd = \"true\"", name)).unwrap()
+    }
+
+    fn make_aliased(name: &str, alias: &str) -> CommandConfig {
+        toml::from_str(&format!(
+            "name = \"{}\"\nalias = \"{}\"\ncmd = \"true\"",
+            name, alias
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_find_command_by_alias() {
+        let commands = vec![make_aliased("Build", "b"), make_aliased("Test", "t")];
+        assert_eq!(find_command(&commands, "t"), Some(1));
+        assert_eq!(find_command(&commands, "B"), Some(0));
+        assert_eq!(find_command(&commands, "x"), None);
+    }
+
+    #[test]
+    fn test_find_command_by_index() {
+        let commands = vec![make_command("Build"), make_command("Test")];
+        assert_eq!(find_command(&commands, "1"), Some(0));
+        assert_eq!(find_command(&commands, "2"), Some(1));
+        assert_eq!(find_command(&commands, "0"), None);
+        assert_eq!(find_command(&commands, "3"), None);
+    }
+
+    #[test]
+    fn test_find_command_name_beats_alias_and_index() {
+        // "Test"'s alias collides with the first command's name
+        let commands = vec![
+            make_command("t"),
+            make_aliased("Test", "t"),
+            make_command("1"),
+        ];
+        assert_eq!(find_command(&commands, "T"), Some(0));
+        // A command named "1" wins over position 1
+        assert_eq!(find_command(&commands, "1"), Some(2));
+        assert_eq!(find_command(&commands, "2"), Some(1));
+    }
+
+    #[test]
+    fn test_find_command_by_name() {
+        let commands = vec![make_command("Build"), make_command("Test")];
+        assert_eq!(find_command(&commands, "Test"), Some(1));
+        assert_eq!(find_command(&commands, "build"), Some(0));
+        assert_eq!(find_command(&commands, "Deploy"), None);
+    }
+}
@@ -0,0 +1,54 @@
+//! Command runtime helpers shared by the app
+
+mod ansi;
+mod badge;
+mod buffer;
+mod close_hook;
+mod cwd;
+mod enabled;
+mod exit;
+mod expand;
+mod fallback;
+mod hooks;
+mod inbox;
+mod lock;
+mod log_file;
+mod lookup;
+mod notify;
+mod outcome;
+mod output;
+mod pattern;
+mod poll;
+mod probe;
+mod queue;
+mod redact;
+mod remap;
+mod retry;
+mod status;
+mod timing;
+mod webhook;
+
+pub use badge::{format_badge, parse_badge, Badges};
+pub use close_hook::CloseHook;
+pub use cwd::check_cwd;
+pub use enabled::EnabledCache;
+pub use exit::{exit_message, CommandExit};
+pub use expand::{expand_command, expand_dropped, expand_input, preview_command};
+pub use fallback::{candidate_program, is_available, is_not_found_exit, select_candidate};
+pub use hooks::select_hook;
+pub use inbox::{Inbox, InboxEntry};
+pub use lock::CommandLocks;
+pub use log_file::open_log;
+pub use lookup::find_command;
+pub use notify::render_notification;
+pub use outcome::OutcomeRule;
+pub use output::{forward_lines, OutputLine, OutputTail};
+pub use poll::Polls;
+pub use probe::{run_command_probe, run_probe};
+pub use queue::LaunchQueue;
+pub use redact::Redactor;
+pub use remap::CommandRemap;
+pub use retry::{Retries, DEFAULT_RETRY_DELAY_SECS};
+pub use status::{run_summary, StatusLine};
+pub use timing::format_elapsed;
+pub use webhook::{post_webhook, WebhookEvent};
@@ -0,0 +1,48 @@
+//! Completion notifications (`notify_on_done`)
+
+use std::time::Duration;
+
+use super::timing::format_elapsed;
+
+/// Render a `notify_on_done` template
+///
+/// `{{name}}` is the command name, `{{result}}` its last output line (or the
+/// script's result) and `{{duration}}` the run time, e.g. `2m05s`.
+pub fn render_notification(template: &str, name: &str, result: &str, duration: Duration) -> String {
+    template
+        .replace("{{name}}", name)
+        .replace("{{result}}", result)
+        .replace("{{duration}}", &format_elapsed(duration))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_notification() {
+        let message = render_notification(
+            "{{name}} finished in {{duration}}: {{result}}",
+            "Test",
+            "test result: ok. 12 passed",
+            Duration::from_secs(125),
+        );
+        assert_eq!(
+            message,
+            "Test finished in 2m05s: test result: ok. 12 passed"
+        );
+    }
+
+    #[test]
+    fn test_render_notification_plain_text() {
+        assert_eq!(
+            render_notification("Build done", "Build", "ok", Duration::ZERO),
+            "Build done"
+        );
+        // Unknown placeholders are left as-is
+        assert_eq!(
+            render_notification("{{name}} {{other}}", "Build", "ok", Duration::ZERO),
+            "Build {{other}}"
+        );
+    }
+}
@@ -0,0 +1,98 @@
+//! Output-based success decision (`success_pattern` / `failure_pattern`)
+
+use super::pattern::Pattern;
+use crate::config::CommandConfig;
+
+/// Output patterns that decide a command's result along with its exit code
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutcomeRule {
+    success: Option<Pattern>,
+    failure: Option<Pattern>,
+}
+
+impl OutcomeRule {
+    /// Compile a command's patterns; `None` if it has neither
+    pub fn from_command(cmd: &CommandConfig) -> Result<Option<Self>, String> {
+        if cmd.success_pattern.is_none() && cmd.failure_pattern.is_none() {
+            return Ok(None);
+        }
+        let compile = |name: &str, pattern: &Option<String>| {
+            pattern
+                .as_deref()
+                .map(Pattern::new)
+                .transpose()
+                .map_err(|e| format!("{} for '{}': {}", name, cmd.name, e))
+        };
+        Ok(Some(Self {
+            success: compile("success_pattern", &cmd.success_pattern)?,
+            failure: compile("failure_pattern", &cmd.failure_pattern)?,
+        }))
+    }
+
+    /// Whether the command succeeded, judged by its output and `exit_ok`
+    ///
+    /// Patterns are matched line by line. A matching `failure_pattern` always
+    /// fails; otherwise `success_pattern` must match some line, or without one
+    /// the exit code decides.
+    pub fn decide(&self, exit_ok: bool, output: &[String]) -> bool {
+        let any_line = |pattern: &Pattern| output.iter().any(|line| pattern.is_match(line));
+        let succeeded = match &self.success {
+            Some(pattern) => any_line(pattern),
+            None => exit_ok,
+        };
+        let failed = self.failure.as_ref().is_some_and(any_line);
+        succeeded && !failed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(success: Option<&str>, failure: Option<&str>) -> OutcomeRule {
+        let mut cmd: CommandConfig = toml::from_str("name = \"Test\"").unwrap();
+        cmd.success_pattern = success.map(String::from);
+        cmd.failure_pattern = failure.map(String::from);
+        OutcomeRule::from_command(&cmd).unwrap().unwrap()
+    }
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_success_pattern() {
+        let rule = rule(Some("^test result: ok"), None);
+        assert!(rule.decide(true, &lines("running 3 tests\ntest result: ok. 3 passed")));
+        assert!(!rule.decide(true, &lines("running 3 tests\ntest result: FAILED")));
+        assert!(!rule.decide(true, &[]));
+        // A matching success_pattern wins over the exit code
+        assert!(rule.decide(false, &lines("test result: ok. 3 passed")));
+    }
+
+    #[test]
+    fn test_failure_pattern() {
+        let failure_only = rule(None, Some("[Ee]rror:"));
+        assert!(failure_only.decide(true, &lines("Deployed 4 files")));
+        assert!(!failure_only.decide(true, &lines("Deploying\nError: permission denied")));
+        // Without a success_pattern a non-zero exit still fails
+        assert!(!failure_only.decide(false, &lines("Segmentation fault")));
+        assert!(!failure_only.decide(false, &[]));
+
+        let both = rule(Some("^Finished"), Some("^error"));
+        assert!(both.decide(true, &lines("Finished\nwarning: unused")));
+        assert!(!both.decide(true, &lines("Finished\nerror: linker failed")));
+        assert!(!both.decide(true, &lines("Compiling")));
+    }
+
+    #[test]
+    fn test_no_patterns_or_invalid() {
+        let cmd: CommandConfig = toml::from_str("name = \"Plain\"").unwrap();
+        assert_eq!(OutcomeRule::from_command(&cmd), Ok(None));
+
+        let cmd: CommandConfig =
+            toml::from_str("name = \"Bad\"\nsuccess_pattern = \"(ok\"").unwrap();
+        let err = OutcomeRule::from_command(&cmd).unwrap_err();
+        assert!(err.contains("success_pattern for 'Bad'"));
+    }
+}
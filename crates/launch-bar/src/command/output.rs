@@ -0,0 +1,372 @@
+//! Output streaming from running commands
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::sync::mpsc::{Receiver, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use super::ansi::strip_ansi;
+use super::buffer::BoundedBuffer;
+use super::pattern::Pattern;
+
+/// Most recent lines kept per captured command, whatever `max_output_bytes` allows
+const MAX_CAPTURED_LINES: usize = 1000;
+
+/// A line of output from a running command
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputLine {
+    pub index: usize,
+    pub line: String,
+}
+
+impl OutputLine {
+    /// Line `raw` of command `index`'s output, with ANSI escape codes stripped
+    pub fn new(index: usize, raw: &str) -> Self {
+        Self {
+            index,
+            line: strip_ansi(raw),
+        }
+    }
+}
+
+/// Forward lines from a child's pipe to the channel on a background thread
+///
+/// ANSI escape codes are stripped by [`OutputLine::new`] (`log_to` files get
+/// the raw output instead). Invalid UTF-8 is replaced rather than ending the
+/// stream, and the pipe is drained to the end even once nobody listens, so the
+/// child never hits a closed pipe. The returned handle finishes once the pipe
+/// is closed.
+pub fn forward_lines<R: Read + Send + 'static>(
+    reader: R,
+    index: usize,
+    tx: Sender<OutputLine>,
+) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        let mut buf = Vec::new();
+        let mut listening = true;
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+            if !listening {
+                continue;
+            }
+            let bytes = buf.strip_suffix(b"\n").unwrap_or(&buf);
+            let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+            let line = OutputLine::new(index, &String::from_utf8_lossy(bytes));
+            listening = tx.send(line).is_ok();
+        }
+    })
+}
+
+/// Latest output line per running command, with throttled status updates
+///
+/// Commands registered with [`OutputTail::capture`] also keep their output
+/// for `success_pattern` / `failure_pattern`, up to `max_output_bytes` and
+/// [`MAX_CAPTURED_LINES`] ([`BoundedBuffer`]); those registered with
+/// [`OutputTail::track_progress`] keep their latest `progress_pattern` value.
+#[derive(Debug, Default)]
+pub struct OutputTail {
+    latest: HashMap<usize, String>,
+    captured: HashMap<usize, BoundedBuffer>,
+    progress_patterns: HashMap<usize, Pattern>,
+    progress: HashMap<usize, f32>,
+    updated: Option<usize>,
+    last_shown: Option<Instant>,
+}
+
+impl OutputTail {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drain pending lines, keeping only the most recent non-blank line per command
+    pub fn drain(&mut self, rx: &Receiver<OutputLine>) {
+        while let Ok(output) = rx.try_recv() {
+            if let Some(buffer) = self.captured.get_mut(&output.index) {
+                buffer.push_line(output.line.trim_end_matches('\r'));
+            }
+            if let Some(pattern) = self.progress_patterns.get(&output.index) {
+                if let Some(fraction) = progress_fraction(pattern, &output.line) {
+                    self.progress.insert(output.index, fraction);
+                }
+            }
+            let line = output.line.trim_end();
+            if line.trim().is_empty() {
+                continue;
+            }
+            self.latest.insert(output.index, line.to_string());
+            self.updated = Some(output.index);
+        }
+    }
+
+    /// Start keeping the output of a command (replacing earlier output)
+    ///
+    /// Only the last `max_bytes` (0 = no byte limit) and [`MAX_CAPTURED_LINES`]
+    /// lines are kept.
+    pub fn capture(&mut self, index: usize, max_bytes: usize) {
+        let buffer = BoundedBuffer::new(max_bytes).with_max_lines(MAX_CAPTURED_LINES);
+        self.captured.insert(index, buffer);
+    }
+
+    /// Read a command's progress from lines matching `pattern` (`progress_pattern`)
+    pub fn track_progress(&mut self, index: usize, pattern: &str) -> Result<(), String> {
+        let pattern = Pattern::new(pattern)?;
+        self.progress_patterns.insert(index, pattern);
+        self.progress.remove(&index);
+        Ok(())
+    }
+
+    /// Latest progress of a command, 0.0 to 1.0
+    pub fn progress(&self, index: usize) -> Option<f32> {
+        self.progress.get(&index).copied()
+    }
+
+    /// Take the captured output lines of a command, after a truncation marker if cut
+    pub fn take_captured(&mut self, index: usize) -> Vec<String> {
+        self.captured
+            .remove(&index)
+            .map(|buffer| buffer.lines())
+            .unwrap_or_default()
+    }
+
+    /// Latest line for a command
+    pub fn latest(&self, index: usize) -> Option<&str> {
+        self.latest.get(&index).map(|s| s.as_str())
+    }
+
+    /// The most recently updated line, if the throttle interval has passed
+    pub fn take_update(&mut self, now: Instant, interval: Duration) -> Option<(usize, String)> {
+        if let Some(last) = self.last_shown {
+            if now.duration_since(last) < interval {
+                return None;
+            }
+        }
+        let index = self.updated.take()?;
+        self.last_shown = Some(now);
+        self.latest.get(&index).map(|line| (index, line.clone()))
+    }
+
+    /// Forget the output of a finished command
+    pub fn remove(&mut self, index: usize) {
+        self.latest.remove(&index);
+        self.captured.remove(&index);
+        self.progress_patterns.remove(&index);
+        self.progress.remove(&index);
+        if self.updated == Some(index) {
+            self.updated = None;
+        }
+    }
+
+    /// Forget the output of every command failing `keep`
+    pub fn retain(&mut self, keep: impl Fn(usize) -> bool) {
+        self.latest.retain(|&index, _| keep(index));
+        self.captured.retain(|&index, _| keep(index));
+        self.progress_patterns.retain(|&index, _| keep(index));
+        self.progress.retain(|&index, _| keep(index));
+        if self.updated.is_some_and(|index| !keep(index)) {
+            self.updated = None;
+        }
+    }
+
+    /// Forget all output (e.g. when the command list changes)
+    pub fn clear(&mut self) {
+        self.latest.clear();
+        self.captured.clear();
+        self.progress_patterns.clear();
+        self.progress.clear();
+        self.updated = None;
+    }
+}
+
+/// Percentage captured by `pattern` in `line`, as a fraction clamped to 0.0-1.0
+fn progress_fraction(pattern: &Pattern, line: &str) -> Option<f32> {
+    let percent: f32 = pattern.capture(line)?.trim().parse().ok()?;
+    percent
+        .is_finite()
+        .then(|| (percent / 100.0).clamp(0.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn send(tx: &Sender<OutputLine>, index: usize, line: &str) {
+        tx.send(OutputLine {
+            index,
+            line: line.to_string(),
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_drain_keeps_latest_line() {
+        let (tx, rx) = mpsc::channel();
+        send(&tx, 0, "Compiling foo");
+        send(&tx, 0, "Compiling bar");
+        send(&tx, 1, "other");
+        send(&tx, 0, "Finished\r");
+        send(&tx, 0, "   ");
+
+        let mut tail = OutputTail::new();
+        tail.drain(&rx);
+        assert_eq!(tail.latest(0), Some("Finished"));
+        assert_eq!(tail.latest(1), Some("other"));
+    }
+
+    #[test]
+    fn test_take_update_is_throttled() {
+        let (tx, rx) = mpsc::channel();
+        let mut tail = OutputTail::new();
+        let interval = Duration::from_millis(200);
+        let start = Instant::now();
+
+        send(&tx, 0, "first");
+        tail.drain(&rx);
+        assert_eq!(
+            tail.take_update(start, interval),
+            Some((0, "first".to_string()))
+        );
+        // Nothing new
+        assert_eq!(tail.take_update(start + interval, interval), None);
+
+        send(&tx, 0, "second");
+        tail.drain(&rx);
+        // Too soon
+        assert_eq!(tail.take_update(start + interval / 2, interval), None);
+        assert_eq!(
+            tail.take_update(start + interval, interval),
+            Some((0, "second".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_capture_keeps_all_lines() {
+        let (tx, rx) = mpsc::channel();
+        let mut tail = OutputTail::new();
+        tail.capture(0, 0);
+        send(&tx, 0, "one\r");
+        send(&tx, 0, "");
+        send(&tx, 1, "not captured");
+        send(&tx, 0, "two");
+        tail.drain(&rx);
+
+        assert_eq!(tail.take_captured(0), vec!["one", "", "two"]);
+        assert!(tail.take_captured(0).is_empty());
+        assert!(tail.take_captured(1).is_empty());
+    }
+
+    #[test]
+    fn test_capture_is_bounded() {
+        let (tx, rx) = mpsc::channel();
+        let mut tail = OutputTail::new();
+        tail.capture(0, 8);
+        for line in ["first", "second", "third"] {
+            send(&tx, 0, line);
+        }
+        tail.drain(&rx);
+        assert_eq!(
+            tail.take_captured(0),
+            vec!["…[truncated 13 bytes]…", "third"]
+        );
+    }
+
+    #[test]
+    fn test_progress_fraction() {
+        let pattern = Pattern::new("PROGRESS: (\\d+)").unwrap();
+        assert_eq!(progress_fraction(&pattern, "PROGRESS: 42"), Some(0.42));
+        assert_eq!(progress_fraction(&pattern, "PROGRESS: 250"), Some(1.0));
+        assert_eq!(progress_fraction(&pattern, "Compiling foo"), None);
+        let decimal = Pattern::new("([\\d.]+)%").unwrap();
+        assert_eq!(progress_fraction(&decimal, "downloaded 12.5%"), Some(0.125));
+    }
+
+    #[test]
+    fn test_track_progress_keeps_latest_value() {
+        let (tx, rx) = mpsc::channel();
+        let mut tail = OutputTail::new();
+        assert!(tail.track_progress(0, "(\\d+").is_err());
+        tail.track_progress(0, "PROGRESS: (\\d+)").unwrap();
+        send(&tx, 0, "PROGRESS: 10");
+        send(&tx, 0, "PROGRESS: 55");
+        send(&tx, 0, "building...");
+        send(&tx, 1, "PROGRESS: 99");
+        tail.drain(&rx);
+
+        assert_eq!(tail.progress(0), Some(0.55));
+        assert_eq!(tail.progress(1), None);
+        tail.remove(0);
+        assert_eq!(tail.progress(0), None);
+    }
+
+    #[test]
+    fn test_forward_lines() {
+        let (tx, rx) = mpsc::channel();
+        forward_lines(std::io::Cursor::new("a\nb\n"), 3, tx);
+        let lines: Vec<_> = rx.iter().collect();
+        assert_eq!(
+            lines,
+            vec![
+                OutputLine {
+                    index: 3,
+                    line: "a".to_string()
+                },
+                OutputLine {
+                    index: 3,
+                    line: "b".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_forward_lines_strips_ansi() {
+        let (tx, rx) = mpsc::channel();
+        forward_lines(std::io::Cursor::new("\x1b[1;32mok\x1b[0m 3 tests\n"), 0, tx);
+        let lines: Vec<_> = rx.iter().map(|output| output.line).collect();
+        assert_eq!(lines, vec!["ok 3 tests"]);
+    }
+
+    #[test]
+    fn test_forward_lines_survives_invalid_utf8() {
+        let (tx, rx) = mpsc::channel();
+        let input = b"ok\r\nbad \xff byte\nlast".to_vec();
+        forward_lines(std::io::Cursor::new(input), 0, tx)
+            .join()
+            .unwrap();
+        let lines: Vec<_> = rx.iter().map(|output| output.line).collect();
+        assert_eq!(lines, vec!["ok", "bad \u{fffd} byte", "last"]);
+    }
+
+    #[test]
+    fn test_forward_lines_drains_after_receiver_is_gone() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        /// Reader that counts the bytes taken from it
+        struct Counting(std::io::Cursor<Vec<u8>>, Arc<AtomicUsize>);
+        impl Read for Counting {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = self.0.read(buf)?;
+                self.1.fetch_add(n, Ordering::SeqCst);
+                Ok(n)
+            }
+        }
+
+        let (tx, rx) = mpsc::channel();
+        drop(rx);
+        let input = "line\n".repeat(10_000).into_bytes();
+        let len = input.len();
+        let read = Arc::new(AtomicUsize::new(0));
+        let reader = Counting(std::io::Cursor::new(input), read.clone());
+        forward_lines(reader, 0, tx).join().unwrap();
+        assert_eq!(read.load(Ordering::SeqCst), len);
+    }
+}
@@ -0,0 +1,174 @@
+//! Regular expressions used by `success_pattern` / `failure_pattern`,
+//! `progress_pattern` and `redact_patterns`
+//!
+//! Backed by the `regex` crate, so matching takes linear time in the input
+//! (these run on the UI thread over captured output). Look-around and
+//! backreferences are not supported.
+
+use regex::{Regex, RegexBuilder};
+
+/// Largest compiled program, so one pattern can't use unbounded memory
+const SIZE_LIMIT: usize = 1 << 20;
+
+/// Compiled pattern
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    regex: Regex,
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.regex.as_str() == other.regex.as_str()
+    }
+}
+
+impl Pattern {
+    /// Compile `pattern`; the error is a one-line description
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        RegexBuilder::new(pattern)
+            .size_limit(SIZE_LIMIT)
+            .build()
+            .map(|regex| Self { regex })
+            .map_err(|e| describe_error(&e))
+    }
+
+    /// Whether the pattern matches anywhere in `text`
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+
+    /// Text of the first group that took part in the leftmost match (the whole
+    /// match if none did)
+    pub fn capture(&self, text: &str) -> Option<String> {
+        let captures = self.regex.captures(text)?;
+        let matched = captures
+            .iter()
+            .skip(1)
+            .flatten()
+            .next()
+            .or_else(|| captures.get(0))?;
+        Some(matched.as_str().to_string())
+    }
+
+    /// Replace every non-empty, non-overlapping match in `text` with `with`
+    ///
+    /// `with` is inserted literally (no `$1` expansion).
+    pub fn replace_all(&self, text: &str, with: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut pos = 0;
+        for m in self.regex.find_iter(text).filter(|m| !m.is_empty()) {
+            result.push_str(&text[pos..m.start()]);
+            result.push_str(with);
+            pos = m.end();
+        }
+        result.push_str(&text[pos..]);
+        result
+    }
+}
+
+/// Last line of a `regex` error (its message without the caret diagram)
+fn describe_error(error: &regex::Error) -> String {
+    let message = error.to_string();
+    message
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .map(|line| line.trim_start_matches("error:").trim().to_string())
+        .unwrap_or(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        Pattern::new(pattern).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn test_literals_and_anchors() {
+        assert!(matches("passed", "12 tests passed"));
+        assert!(!matches("^passed", "12 tests passed"));
+        assert!(matches("passed$", "12 tests passed"));
+        assert!(!matches("^tests$", "12 tests passed"));
+        assert!(matches("", "anything"));
+        assert!(matches("a\\.b", "a.b"));
+        assert!(!matches("a\\.b", "axb"));
+    }
+
+    #[test]
+    fn test_quantifiers_and_classes() {
+        assert!(matches("^\\d+ passed", "128 passed; 0 failed"));
+        assert!(!matches("^\\d+ passed", "all passed"));
+        assert!(matches("0 failed|no failures", "no failures here"));
+        assert!(matches("colou?r", "color"));
+        assert!(matches("a.*z", "abc xyz"));
+        assert!(matches("[A-Z][a-z]+Error", "TypeError: x"));
+        assert!(!matches("[^0-9]", "123"));
+        assert!(matches("[-a]", "-"));
+        assert!(matches("\\s\\w+:", "  error:"));
+    }
+
+    #[test]
+    fn test_rejects_invalid_syntax() {
+        assert!(Pattern::new("(ab").is_err());
+        assert!(Pattern::new("ab)").is_err());
+        assert!(Pattern::new("*a").is_err());
+        assert!(Pattern::new("[abc").is_err());
+        assert!(Pattern::new("[z-a]").is_err());
+        assert!(Pattern::new("\\(ok\\)").is_ok());
+        assert_eq!(Pattern::new("(ab").unwrap_err(), "unclosed group");
+    }
+
+    #[test]
+    fn test_groups_alternation_and_counts() {
+        assert!(matches("(a|b)c", "bc"));
+        assert!(matches("^(ab)+$", "ababab"));
+        assert!(matches("x(a(b))y", "xaby"));
+        assert!(matches("^a{2}$", "aa"));
+        assert!(!matches("^a{2}$", "aaa"));
+    }
+
+    #[test]
+    fn test_match_time_is_linear() {
+        // Exponential for a backtracking matcher
+        let start = std::time::Instant::now();
+        assert!(!matches("a*a*a*a*a*a*a*a*b", &"a".repeat(5000)));
+        assert!(!matches(".*.*.*.*=.*.*x", &"=".repeat(5000)));
+        assert!(start.elapsed() < std::time::Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_capture() {
+        let capture = |pattern: &str, text: &str| Pattern::new(pattern).unwrap().capture(text);
+        assert_eq!(
+            capture("PROGRESS: (\\d+)", "PROGRESS: 42").as_deref(),
+            Some("42")
+        );
+        assert_eq!(
+            capture("(\\d+)%", "[====>  ] 67% eta 3s").as_deref(),
+            Some("67")
+        );
+        assert_eq!(capture("^(\\d*)$", "").as_deref(), Some(""));
+        // Without a group the whole match is returned
+        assert_eq!(capture("\\d+", "abc 12").as_deref(), Some("12"));
+        assert_eq!(
+            capture("step (\\d+)|(\\d+) done", "3 done").as_deref(),
+            Some("3")
+        );
+        assert_eq!(capture("(\\d+)%", "no progress"), None);
+        // Groups still match as plain sequences
+        assert!(matches("a(bc)d", "xabcdx"));
+    }
+
+    #[test]
+    fn test_replace_all() {
+        let replace =
+            |pattern: &str, text: &str| Pattern::new(pattern).unwrap().replace_all(text, "#");
+        assert_eq!(replace("\\d+", "a1b22c333"), "a#b#c#");
+        assert_eq!(replace("^\\w+", "abc def"), "# def");
+        assert_eq!(replace("x*", "abc"), "abc");
+        assert_eq!(replace("é+", "café ok"), "caf# ok");
+        assert_eq!(replace("z", "abc"), "abc");
+    }
+}
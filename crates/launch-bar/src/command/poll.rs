@@ -0,0 +1,199 @@
+//! Periodic health-check polling with exponential backoff
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant};
+
+/// Backoff is capped at this multiple of the base interval
+const MAX_BACKOFF_FACTOR: u32 = 16;
+
+/// Jitter applied to each interval, as a fraction of it (±10%)
+const JITTER_RATIO: f64 = 0.1;
+
+/// Poll schedule for a single command
+#[derive(Debug, Clone)]
+pub struct PollState {
+    base: Duration,
+    current: Duration,
+    next_due: Instant,
+    in_flight: bool,
+}
+
+impl PollState {
+    /// Create a poll state that is due immediately
+    pub fn new(base: Duration, now: Instant) -> Self {
+        Self {
+            base,
+            current: base,
+            next_due: now,
+            in_flight: false,
+        }
+    }
+
+    /// Current (un-jittered) interval
+    #[cfg(test)]
+    pub fn interval(&self) -> Duration {
+        self.current
+    }
+
+    /// Maximum interval reachable through backoff
+    pub fn max_interval(&self) -> Duration {
+        self.base * MAX_BACKOFF_FACTOR
+    }
+
+    /// Returns true if a poll should be started
+    pub fn is_due(&self, now: Instant) -> bool {
+        !self.in_flight && now >= self.next_due
+    }
+
+    /// Record a poll result: back off on failure, reset on success
+    ///
+    /// `jitter` is a random value in [-1.0, 1.0] scaling the interval by ±10%.
+    pub fn record(&mut self, success: bool, now: Instant, jitter: f64) {
+        self.in_flight = false;
+        self.current = if success {
+            self.base
+        } else {
+            (self.current * 2).min(self.max_interval())
+        };
+        self.next_due = now + jittered(self.current, jitter);
+    }
+}
+
+/// Scale an interval by a jitter value in [-1.0, 1.0]
+fn jittered(interval: Duration, jitter: f64) -> Duration {
+    let factor = 1.0 + JITTER_RATIO * jitter.clamp(-1.0, 1.0);
+    interval.mul_f64(factor)
+}
+
+/// Random value in [-1.0, 1.0] (std-only, seeded per call by `RandomState`)
+pub fn random_jitter() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits as f64 / u64::MAX as f64) * 2.0 - 1.0
+}
+
+/// Poll states per command index
+#[derive(Debug, Default)]
+pub struct Polls {
+    states: HashMap<usize, PollState>,
+    generation: u64,
+}
+
+impl Polls {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ensure a poll state exists for a command
+    pub fn ensure(&mut self, index: usize, base: Duration, now: Instant) {
+        self.states
+            .entry(index)
+            .or_insert_with(|| PollState::new(base, now));
+    }
+
+    /// Start all due polls, returning their indices and the current generation
+    pub fn start_due(&mut self, now: Instant) -> (Vec<usize>, u64) {
+        let mut due = Vec::new();
+        for (&index, state) in &mut self.states {
+            if state.is_due(now) {
+                state.in_flight = true;
+                due.push(index);
+            }
+        }
+        (due, self.generation)
+    }
+
+    /// Record a poll result; false if it is stale (ignored)
+    pub fn record(&mut self, index: usize, success: bool, generation: u64, now: Instant) -> bool {
+        if generation != self.generation {
+            return false;
+        }
+        if let Some(state) = self.states.get_mut(&index) {
+            state.record(success, now, random_jitter());
+        }
+        true
+    }
+
+    /// Forget everything (e.g. when the command list changes)
+    pub fn clear(&mut self) {
+        self.states.clear();
+        self.generation += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: Duration = Duration::from_secs(2);
+
+    #[test]
+    fn test_backoff_progression() {
+        let now = Instant::now();
+        let mut state = PollState::new(BASE, now);
+        assert!(state.is_due(now));
+
+        state.record(false, now, 0.0);
+        assert_eq!(state.interval(), Duration::from_secs(4));
+        state.record(false, now, 0.0);
+        assert_eq!(state.interval(), Duration::from_secs(8));
+        state.record(false, now, 0.0);
+        assert_eq!(state.interval(), Duration::from_secs(16));
+        assert!(!state.is_due(now));
+        assert!(state.is_due(now + Duration::from_secs(16)));
+    }
+
+    #[test]
+    fn test_backoff_cap() {
+        let now = Instant::now();
+        let mut state = PollState::new(BASE, now);
+        for _ in 0..20 {
+            state.record(false, now, 0.0);
+        }
+        assert_eq!(state.interval(), BASE * MAX_BACKOFF_FACTOR);
+    }
+
+    #[test]
+    fn test_reset_on_success() {
+        let now = Instant::now();
+        let mut state = PollState::new(BASE, now);
+        state.record(false, now, 0.0);
+        state.record(false, now, 0.0);
+        state.record(true, now, 0.0);
+        assert_eq!(state.interval(), BASE);
+        assert!(state.is_due(now + BASE));
+    }
+
+    #[test]
+    fn test_jitter_within_bounds() {
+        let interval = Duration::from_secs(10);
+        assert_eq!(jittered(interval, 1.0), Duration::from_secs(11));
+        assert_eq!(jittered(interval, -1.0), Duration::from_secs(9));
+        // Out-of-range jitter is clamped
+        assert_eq!(jittered(interval, 5.0), Duration::from_secs(11));
+
+        for _ in 0..100 {
+            let jitter = random_jitter();
+            assert!((-1.0..=1.0).contains(&jitter));
+            let d = jittered(interval, jitter);
+            assert!(d >= Duration::from_secs(9) && d <= Duration::from_secs(11));
+        }
+    }
+
+    #[test]
+    fn test_polls_skip_in_flight_and_stale() {
+        let now = Instant::now();
+        let mut polls = Polls::new();
+        polls.ensure(0, BASE, now);
+
+        let (due, generation) = polls.start_due(now);
+        assert_eq!(due, vec![0]);
+        // Already in flight
+        assert!(polls.start_due(now).0.is_empty());
+
+        polls.clear();
+        assert!(!polls.record(0, true, generation, now));
+        assert!(polls.start_due(now).0.is_empty());
+    }
+}
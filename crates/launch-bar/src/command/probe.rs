@@ -0,0 +1,67 @@
+//! Background probes (predicates, badges) run outside the UI thread
+
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::config::CommandConfig;
+use crate::platform::shell_output;
+use crate::script::{resolve_script_type, run_script, ScriptConfig};
+
+/// Result of running a probe
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbeOutput {
+    pub success: bool,
+    pub stdout: String,
+}
+
+/// Run a probe: `@path` runs a script file, anything else a shell command
+///
+/// Scripts only report success; their stdout is empty.
+pub fn run_probe(spec: &str, cwd: &Path, script_config: &ScriptConfig) -> ProbeOutput {
+    if spec.starts_with('@') {
+        let script_type = resolve_script_type(None, spec, script_config);
+        let result = run_script(
+            spec,
+            script_type,
+            Arc::new(cwd.to_path_buf()),
+            script_config,
+        );
+        return ProbeOutput {
+            success: result.success,
+            stdout: String::new(),
+        };
+    }
+
+    match shell_output(spec, cwd) {
+        Ok(out) => ProbeOutput {
+            success: out.status.success(),
+            stdout: String::from_utf8_lossy(&out.stdout).to_string(),
+        },
+        Err(_) => ProbeOutput {
+            success: false,
+            stdout: String::new(),
+        },
+    }
+}
+
+/// Run a command's `run` script or `cmd` to completion, reporting only success
+///
+/// Used for background health-check polls.
+pub fn run_command_probe(cmd: &CommandConfig, cwd: &Path, script_config: &ScriptConfig) -> bool {
+    if let Some(ref script) = cmd.run {
+        let script_type = resolve_script_type(cmd.script_type, script, script_config);
+        return run_script(
+            script,
+            script_type,
+            Arc::new(cwd.to_path_buf()),
+            script_config,
+        )
+        .success;
+    }
+    match cmd.cmd {
+        Some(ref shell_cmd) => shell_output(shell_cmd, cwd)
+            .map(|out| out.status.success())
+            .unwrap_or(false),
+        None => false,
+    }
+}
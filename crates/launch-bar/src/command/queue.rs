@@ -0,0 +1,109 @@
+//! Launch queue enforcing `max_concurrent`
+
+use std::collections::VecDeque;
+
+/// Commands waiting for a free slot, in launch order
+#[derive(Debug, Default)]
+pub struct LaunchQueue {
+    limit: Option<usize>,
+    queued: VecDeque<usize>,
+}
+
+impl LaunchQueue {
+    /// A `limit` of 0 means unlimited, like `None`
+    pub fn new(limit: Option<usize>) -> Self {
+        Self {
+            limit: limit.filter(|&limit| limit > 0),
+            queued: VecDeque::new(),
+        }
+    }
+
+    /// Change the limit (queued commands stay queued; 0 = unlimited)
+    pub fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit.filter(|&limit| limit > 0);
+    }
+
+    /// Returns true if a command may start with `running` commands active;
+    /// otherwise it is queued (once)
+    pub fn admit(&mut self, index: usize, running: usize) -> bool {
+        if self.has_slot(running) {
+            return true;
+        }
+        if !self.queued.contains(&index) {
+            self.queued.push_back(index);
+        }
+        false
+    }
+
+    /// Take the next queued command if a slot is free
+    pub fn next(&mut self, running: usize) -> Option<usize> {
+        if self.has_slot(running) {
+            self.queued.pop_front()
+        } else {
+            None
+        }
+    }
+
+    pub fn is_queued(&self, index: usize) -> bool {
+        self.queued.contains(&index)
+    }
+
+    /// Renumber queued commands, dropping those `new_index` has no place for
+    pub fn remap(&mut self, new_index: impl Fn(usize) -> Option<usize>) {
+        self.queued = self.queued.drain(..).filter_map(new_index).collect();
+    }
+
+    /// Drop all queued commands (e.g. when the command list changes)
+    pub fn clear(&mut self) {
+        self.queued.clear();
+    }
+
+    fn has_slot(&self, running: usize) -> bool {
+        self.limit.is_none_or(|limit| running < limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_one_with_three_launches() {
+        let mut queue = LaunchQueue::new(Some(1));
+        assert!(queue.admit(0, 0));
+        assert!(!queue.admit(1, 1));
+        assert!(!queue.admit(2, 1));
+        // Relaunching a queued command doesn't queue it twice
+        assert!(!queue.admit(1, 1));
+        assert!(queue.is_queued(1) && queue.is_queued(2));
+
+        // Nothing starts while the first command runs
+        assert_eq!(queue.next(1), None);
+        assert_eq!(queue.next(0), Some(1));
+        assert!(!queue.is_queued(1));
+        assert_eq!(queue.next(1), None);
+        assert_eq!(queue.next(0), Some(2));
+        assert_eq!(queue.next(0), None);
+    }
+
+    #[test]
+    fn test_no_limit_always_admits() {
+        let mut queue = LaunchQueue::new(None);
+        for index in 0..10 {
+            assert!(queue.admit(index, index));
+        }
+        assert_eq!(queue.next(100), None);
+    }
+
+    #[test]
+    fn test_zero_limit_is_unlimited() {
+        let mut queue = LaunchQueue::new(Some(0));
+        assert!(queue.admit(0, 0));
+        assert!(queue.admit(1, 5));
+
+        queue.set_limit(Some(1));
+        assert!(!queue.admit(2, 1));
+        queue.set_limit(Some(0));
+        assert_eq!(queue.next(3), Some(2));
+    }
+}
@@ -0,0 +1,156 @@
+//! Secret redaction for displayed text (`redact_patterns`)
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use super::pattern::Pattern;
+
+/// Replacement for redacted text
+pub const REDACTED: &str = "****";
+
+/// Environment variables whose values are always redacted
+const SECRET_SUFFIXES: &[&str] = &["_TOKEN", "_SECRET", "_KEY"];
+
+/// Shorter values are too likely to match unrelated text
+const MIN_SECRET_LEN: usize = 4;
+
+/// Redacted texts remembered before the cache is cleared
+const CACHE_LIMIT: usize = 256;
+
+/// Hides secrets in text before it reaches the status line, tooltips, the
+/// history or the diagnostics report
+///
+/// `log_to` files and warnings on stderr are written as-is.
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    patterns: Vec<Pattern>,
+    /// Values of secret-looking environment variables, longest first
+    secrets: Vec<String>,
+    /// Results for texts redacted on every frame (tooltips, the status line)
+    cache: RefCell<HashMap<String, String>>,
+}
+
+impl Redactor {
+    /// Redactor for `redact_patterns` and the secret variables of this process
+    ///
+    /// Invalid patterns are skipped with a warning.
+    pub fn new(patterns: &[String]) -> Self {
+        Self::with_env(patterns, std::env::vars())
+    }
+
+    fn with_env(patterns: &[String], env: impl IntoIterator<Item = (String, String)>) -> Self {
+        let patterns = patterns
+            .iter()
+            .filter_map(|p| match Pattern::new(p) {
+                Ok(pattern) => Some(pattern),
+                Err(e) => {
+                    eprintln!("[warn] Invalid redact pattern '{}': {}", p, e);
+                    None
+                }
+            })
+            .collect();
+        let mut secrets: Vec<String> = env
+            .into_iter()
+            .filter(|(name, value)| {
+                let name = name.to_ascii_uppercase();
+                SECRET_SUFFIXES.iter().any(|s| name.ends_with(s))
+                    && value.chars().count() >= MIN_SECRET_LEN
+            })
+            .map(|(_, value)| value)
+            .collect();
+        secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        secrets.dedup();
+        Self {
+            patterns,
+            secrets,
+            cache: RefCell::default(),
+        }
+    }
+
+    /// `text` with secret values and pattern matches replaced by `****`
+    pub fn redact(&self, text: &str) -> String {
+        if self.patterns.is_empty() && self.secrets.is_empty() {
+            return text.to_string();
+        }
+        if let Some(redacted) = self.cache.borrow().get(text) {
+            return redacted.clone();
+        }
+        let redacted = self.redact_uncached(text);
+        let mut cache = self.cache.borrow_mut();
+        if cache.len() >= CACHE_LIMIT {
+            cache.clear();
+        }
+        cache.insert(text.to_string(), redacted.clone());
+        redacted
+    }
+
+    fn redact_uncached(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for secret in &self.secrets {
+            if text.contains(secret.as_str()) {
+                text = text.replace(secret.as_str(), REDACTED);
+            }
+        }
+        for pattern in &self.patterns {
+            text = pattern.replace_all(&text, REDACTED);
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_redacts_patterns() {
+        let patterns = vec!["ghp_\\w+".to_string(), "password=\\S+".to_string()];
+        let redactor = Redactor::with_env(&patterns, Vec::new());
+        assert_eq!(
+            redactor.redact("Failed: git push https://ghp_abc123@github.com (password=hunter2)"),
+            "Failed: git push https://****@github.com (****"
+        );
+        assert_eq!(redactor.redact("Done: Build"), "Done: Build");
+    }
+
+    #[test]
+    fn test_redacts_secret_env_values() {
+        let redactor = Redactor::with_env(
+            &[],
+            env(&[
+                ("API_TOKEN", "tok-12345"),
+                ("aws_secret", "s3cr3t-value"),
+                ("DEPLOY_KEY", "k1"),
+                ("HOME", "/home/me"),
+            ]),
+        );
+        assert_eq!(
+            redactor.redact("Deploy: curl -H 'Bearer tok-12345' -u s3cr3t-value /home/me k1"),
+            "Deploy: curl -H 'Bearer ****' -u **** /home/me k1"
+        );
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_skipped() {
+        let redactor = Redactor::with_env(&["(unclosed".to_string()], Vec::new());
+        assert_eq!(redactor.redact("(unclosed"), "(unclosed");
+    }
+
+    #[test]
+    fn test_cache_stays_bounded() {
+        let redactor = Redactor::with_env(&["\\d+".to_string()], Vec::new());
+        assert_eq!(redactor.redact("pin 1234"), "pin ****");
+        assert_eq!(redactor.redact("pin 1234"), "pin ****");
+        for i in 0..CACHE_LIMIT * 2 {
+            redactor.redact(&format!("run {}", i));
+        }
+        assert!(redactor.cache.borrow().len() <= CACHE_LIMIT);
+    }
+}
@@ -0,0 +1,121 @@
+//! Carrying per-command state across a config reload
+
+use std::collections::HashMap;
+
+use crate::config::CommandConfig;
+
+/// Where each command of the old list went in the reloaded one
+///
+/// A command is matched by name and only carries over if its definition is
+/// unchanged; edited, renamed and removed commands map to nothing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandRemap {
+    new_index: Vec<Option<usize>>,
+}
+
+impl CommandRemap {
+    pub fn new(old: &[CommandConfig], new: &[CommandConfig]) -> Self {
+        let mut taken = vec![false; new.len()];
+        let new_index = old
+            .iter()
+            .map(|cmd| {
+                // Duplicate names pair up in order
+                let index = (0..new.len()).find(|&i| !taken[i] && new[i].name == cmd.name)?;
+                taken[index] = true;
+                (new[index] == *cmd).then_some(index)
+            })
+            .collect();
+        Self { new_index }
+    }
+
+    /// New index of old command `index`
+    pub fn get(&self, index: usize) -> Option<usize> {
+        self.new_index.get(index).copied().flatten()
+    }
+
+    /// Whether old command `index` is unchanged and at the same position
+    ///
+    /// Output and results still on their way from a running command carry its
+    /// index, so running state only survives where that index stays valid.
+    pub fn is_stable(&self, index: usize) -> bool {
+        self.get(index) == Some(index)
+    }
+
+    /// Move entries to their new indices, dropping those without one
+    pub fn apply<V>(&self, map: &mut HashMap<usize, V>) {
+        *map = map
+            .drain()
+            .filter_map(|(index, value)| Some((self.get(index)?, value)))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commands(specs: &[(&str, &str)]) -> Vec<CommandConfig> {
+        specs
+            .iter()
+            .map(|(name, cmd)| {
+                toml::from_str(&format!("name = \"{}\"\ncmd = \"{}\"", name, cmd)).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_remap_added_and_removed() {
+        let old = commands(&[
+            ("Build", "make"),
+            ("Test", "make test"),
+            ("Lint", "make lint"),
+        ]);
+        let new = commands(&[
+            ("Fmt", "make fmt"),
+            ("Build", "make"),
+            ("Lint", "make lint"),
+        ]);
+        let remap = CommandRemap::new(&old, &new);
+        assert_eq!(remap.get(0), Some(1));
+        assert_eq!(remap.get(1), None);
+        assert_eq!(remap.get(2), Some(2));
+        assert_eq!(remap.get(3), None);
+        assert!(!remap.is_stable(0));
+        assert!(remap.is_stable(2));
+    }
+
+    #[test]
+    fn test_remap_reordered_and_changed() {
+        let old = commands(&[
+            ("Build", "make"),
+            ("Test", "make test"),
+            ("Run", "make run"),
+        ]);
+        let new = commands(&[
+            ("Run", "make run"),
+            ("Build", "make"),
+            ("Test", "cargo test"),
+        ]);
+        let remap = CommandRemap::new(&old, &new);
+        assert_eq!(remap.get(0), Some(1));
+        // Same name, different definition
+        assert_eq!(remap.get(1), None);
+        assert_eq!(remap.get(2), Some(0));
+    }
+
+    #[test]
+    fn test_remap_duplicate_names_and_apply() {
+        let old = commands(&[("Open", "a"), ("Open", "b")]);
+        let new = commands(&[("Open", "a"), ("Open", "b")]);
+        let remap = CommandRemap::new(&old, &new);
+        assert!(remap.is_stable(0));
+        assert!(remap.is_stable(1));
+
+        let old = commands(&[("Build", "make"), ("Test", "make test")]);
+        let new = commands(&[("Test", "make test")]);
+        let remap = CommandRemap::new(&old, &new);
+        let mut results = HashMap::from([(0, "ok"), (1, "failed")]);
+        remap.apply(&mut results);
+        assert_eq!(results, HashMap::from([(0, "failed")]));
+    }
+}
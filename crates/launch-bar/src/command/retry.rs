@@ -0,0 +1,180 @@
+//! Automatic retries of failed commands (`retries`, `retry_delay_secs`)
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Delay before a retry when `retry_delay_secs` isn't set
+pub const DEFAULT_RETRY_DELAY_SECS: u64 = 3;
+
+/// A failed command waiting to be run again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPending {
+    /// Attempts still to come, including the scheduled one
+    pub attempts_left: u32,
+    pub next_at: Instant,
+}
+
+impl RetryPending {
+    /// Whole seconds until the next attempt, rounded up (for the countdown)
+    pub fn seconds_left(&self, now: Instant) -> u64 {
+        let left = self.next_at.saturating_duration_since(now);
+        left.as_secs() + u64::from(left.subsec_nanos() > 0)
+    }
+}
+
+/// Retry state per command index
+#[derive(Debug, Default)]
+pub struct Retries {
+    pending: HashMap<usize, RetryPending>,
+    /// Retries left for commands whose current run is itself a retry
+    left: HashMap<usize, u32>,
+    /// Retries taken from `due` whose run hasn't started yet
+    launching: HashSet<usize>,
+}
+
+impl Retries {
+    /// A run of `index` starts; unless it's a due retry, earlier attempts are forgotten
+    pub fn start(&mut self, index: usize) {
+        self.pending.remove(&index);
+        if !self.launching.remove(&index) {
+            self.left.remove(&index);
+        }
+    }
+
+    /// Record a finished run
+    ///
+    /// A failed run with attempts left (`retries` for a fresh run) is
+    /// scheduled again after `delay`.
+    pub fn finish(
+        &mut self,
+        index: usize,
+        success: bool,
+        retries: u32,
+        delay: Duration,
+        now: Instant,
+    ) -> Option<RetryPending> {
+        let left = self.left.remove(&index).unwrap_or(retries);
+        if success || left == 0 {
+            return None;
+        }
+        self.left.insert(index, left - 1);
+        let pending = RetryPending {
+            attempts_left: left,
+            next_at: now + delay,
+        };
+        self.pending.insert(index, pending);
+        Some(pending)
+    }
+
+    /// Take the retries whose delay has elapsed, to be run now
+    pub fn due(&mut self, now: Instant) -> Vec<usize> {
+        let mut due: Vec<usize> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| pending.next_at <= now)
+            .map(|(&index, _)| index)
+            .collect();
+        due.sort_unstable();
+        for index in &due {
+            self.pending.remove(index);
+            self.launching.insert(*index);
+        }
+        due
+    }
+
+    pub fn get(&self, index: usize) -> Option<&RetryPending> {
+        self.pending.get(&index)
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Keep only the commands `keep` accepts (e.g. unchanged ones after a reload)
+    pub fn retain(&mut self, keep: impl Fn(usize) -> bool) {
+        self.pending.retain(|&index, _| keep(index));
+        self.left.retain(|&index, _| keep(index));
+        self.launching.retain(|&index| keep(index));
+    }
+
+    /// Forget all retries (e.g. when the command list changes)
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.left.clear();
+        self.launching.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELAY: Duration = Duration::from_secs(3);
+
+    #[test]
+    fn test_retry_transitions_over_time() {
+        let t0 = Instant::now();
+        let mut retries = Retries::default();
+        retries.start(0);
+
+        // First failure: pending with both attempts to come
+        let pending = retries.finish(0, false, 2, DELAY, t0).unwrap();
+        assert_eq!(pending.attempts_left, 2);
+        assert_eq!(pending.seconds_left(t0), 3);
+        assert_eq!(pending.seconds_left(t0 + Duration::from_millis(1500)), 2);
+        assert!(retries.due(t0 + Duration::from_secs(2)).is_empty());
+        assert!(retries.get(0).is_some());
+
+        // Delay elapsed: the retry runs and is no longer pending
+        assert_eq!(retries.due(t0 + DELAY), vec![0]);
+        assert!(retries.get(0).is_none() && !retries.has_pending());
+        retries.start(0);
+
+        // Second failure uses the remaining attempt
+        let t1 = t0 + Duration::from_secs(5);
+        let pending = retries.finish(0, false, 2, DELAY, t1).unwrap();
+        assert_eq!(pending.attempts_left, 1);
+        assert_eq!(retries.due(t1 + DELAY), vec![0]);
+        retries.start(0);
+
+        // Out of attempts: the failure stands
+        assert_eq!(retries.finish(0, false, 2, DELAY, t1 + DELAY), None);
+        assert!(!retries.has_pending());
+
+        // A new run starts over with all attempts
+        retries.start(0);
+        let pending = retries.finish(0, false, 2, DELAY, t1).unwrap();
+        assert_eq!(pending.attempts_left, 2);
+    }
+
+    #[test]
+    fn test_success_or_manual_run_ends_retries() {
+        let now = Instant::now();
+        let mut retries = Retries::default();
+        assert_eq!(retries.finish(0, false, 0, DELAY, now), None);
+
+        retries.finish(0, false, 3, DELAY, now);
+        assert_eq!(retries.due(now + DELAY), vec![0]);
+        retries.start(0);
+        assert_eq!(retries.finish(0, true, 3, DELAY, now), None);
+        // Next failure is a fresh run again
+        assert_eq!(
+            retries
+                .finish(0, false, 3, DELAY, now)
+                .unwrap()
+                .attempts_left,
+            3
+        );
+
+        // Running by hand during the countdown cancels the pending retry
+        retries.start(0);
+        assert!(!retries.has_pending());
+        assert_eq!(
+            retries
+                .finish(0, false, 3, DELAY, now)
+                .unwrap()
+                .attempts_left,
+            3
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Status line shown under the command buttons
+
+use crate::config::CommandConfig;
+
+/// Latest status message and whether it reports an error
+#[derive(Debug, Default)]
+pub struct StatusLine {
+    message: Option<String>,
+    is_error: bool,
+}
+
+impl StatusLine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the status message
+    pub fn set(&mut self, message: impl Into<String>, is_error: bool) {
+        self.message = Some(message.into());
+        self.is_error = is_error;
+    }
+
+    /// Replace the status message on behalf of a command, unless it is `quiet`
+    ///
+    /// Returns true if the status was updated.
+    pub fn set_for(
+        &mut self,
+        cmd: &CommandConfig,
+        message: impl Into<String>,
+        is_error: bool,
+    ) -> bool {
+        if cmd.quiet {
+            return false;
+        }
+        self.set(message, is_error);
+        true
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.is_error
+    }
+}
+
+/// `3 running, 1 failed` summary shown instead of the status (`summary_status`)
+///
+/// Only while more than one command runs.
+pub fn run_summary(running: usize, failed: usize) -> Option<String> {
+    if running < 2 {
+        return None;
+    }
+    Some(match failed {
+        0 => format!("{} running", running),
+        failed => format!("{} running, {} failed", running, failed),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_command(quiet: bool) -> CommandConfig {
+        toml::from_str(&format!(
+            "name = \"Open\"\ncmd = \"open notes.md\"\nquiet = {}",
+            quiet
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_quiet_command_keeps_existing_status() {
+        let mut status = StatusLine::new();
+        status.set("Failed: deploy", true);
+
+        assert!(!status.set_for(&make_command(true), "Done: Open", false));
+        assert_eq!(status.message(), Some("Failed: deploy"));
+        assert!(status.is_error());
+    }
+
+    #[test]
+    fn test_command_overwrites_status() {
+        let mut status = StatusLine::new();
+        status.set("Failed: deploy", true);
+
+        assert!(status.set_for(&make_command(false), "Done: Open", false));
+        assert_eq!(status.message(), Some("Done: Open"));
+        assert!(!status.is_error());
+    }
+
+    #[test]
+    fn test_run_summary() {
+        assert_eq!(run_summary(0, 0), None);
+        // A single running command keeps its own status
+        assert_eq!(run_summary(1, 2), None);
+        assert_eq!(run_summary(2, 0).as_deref(), Some("2 running"));
+        assert_eq!(run_summary(3, 1).as_deref(), Some("3 running, 1 failed"));
+    }
+}
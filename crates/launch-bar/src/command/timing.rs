@@ -0,0 +1,45 @@
+//! Elapsed-time formatting for running commands
+
+use std::time::Duration;
+
+/// Format a duration compactly: `850ms`, `12s`, `2m05s`, `1h03m`
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs == 0 {
+        format!("{}ms", elapsed.as_millis())
+    } else if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_elapsed_millis() {
+        assert_eq!(format_elapsed(Duration::from_millis(0)), "0ms");
+        assert_eq!(format_elapsed(Duration::from_millis(850)), "850ms");
+    }
+
+    #[test]
+    fn test_format_elapsed_seconds() {
+        assert_eq!(format_elapsed(Duration::from_millis(1000)), "1s");
+        assert_eq!(format_elapsed(Duration::from_millis(14_900)), "14s");
+        assert_eq!(format_elapsed(Duration::from_secs(59)), "59s");
+    }
+
+    #[test]
+    fn test_format_elapsed_minutes_and_hours() {
+        assert_eq!(format_elapsed(Duration::from_secs(60)), "1m00s");
+        assert_eq!(format_elapsed(Duration::from_secs(125)), "2m05s");
+        assert_eq!(
+            format_elapsed(Duration::from_secs(3600 + 3 * 60 + 9)),
+            "1h03m"
+        );
+    }
+}
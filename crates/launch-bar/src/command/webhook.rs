@@ -0,0 +1,125 @@
+//! Command results POSTed to `window.webhook_url`
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// JSON body sent for each finished command
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebhookEvent {
+    pub preset: Option<String>,
+    pub name: String,
+    pub success: bool,
+    /// Status line text, e.g. `Exited 1: Test (3s)`
+    pub message: String,
+    pub duration_ms: u64,
+    /// Filled in by `post_webhook` on its own thread (`hostname` may be run)
+    pub host: String,
+}
+
+impl WebhookEvent {
+    pub fn new(
+        preset: Option<&str>,
+        name: &str,
+        success: bool,
+        message: &str,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            preset: preset.map(str::to_string),
+            name: name.to_string(),
+            success,
+            message: message.to_string(),
+            duration_ms: duration.as_millis() as u64,
+            host: String::new(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// This machine's name (`$HOSTNAME` / `%COMPUTERNAME%`, else `hostname`), looked up once
+fn host_name() -> &'static str {
+    static HOST: OnceLock<String> = OnceLock::new();
+    HOST.get_or_init(|| {
+        ["HOSTNAME", "COMPUTERNAME"]
+            .iter()
+            .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+            .or_else(|| {
+                let output = std::process::Command::new("hostname").output().ok()?;
+                let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                (!name.is_empty()).then_some(name)
+            })
+            .unwrap_or_default()
+    })
+}
+
+/// POST `event` to `url` on a background thread; failures are only logged
+pub fn post_webhook(url: &str, event: &WebhookEvent) {
+    let url = url.to_string();
+    let mut event = event.clone();
+    std::thread::spawn(move || {
+        if event.host.is_empty() {
+            event.host = host_name().to_string();
+        }
+        if let Err(e) = post_json(&url, &event.to_json()) {
+            eprintln!("[warn] webhook: {}", e);
+        }
+    });
+}
+
+/// POST a JSON body, failing on errors and non-2xx responses
+#[cfg(feature = "http")]
+fn post_json(url: &str, body: &str) -> Result<(), String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(std::time::Duration::from_secs(10)))
+        .build()
+        .into();
+    agent
+        .post(url)
+        .header("Content-Type", "application/json")
+        .send(body)
+        .map(|_| ())
+        .map_err(|e| format!("cannot post to {}: {}", url, e))
+}
+
+/// Posting needs the `http` feature
+#[cfg(not(feature = "http"))]
+fn post_json(_url: &str, _body: &str) -> Result<(), String> {
+    Err("webhook_url needs the 'http' feature".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_body() {
+        let mut event = WebhookEvent::new(
+            Some("Rust"),
+            "Test",
+            false,
+            "Exited 101: Test (2s)",
+            Duration::from_millis(2345),
+        );
+        event.host = "buildbox".to_string();
+        let body: serde_json::Value = serde_json::from_str(&event.to_json()).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "preset": "Rust",
+                "name": "Test",
+                "success": false,
+                "message": "Exited 101: Test (2s)",
+                "duration_ms": 2345,
+                "host": "buildbox",
+            })
+        );
+
+        let event = WebhookEvent::new(None, "Build", true, "Done: Build", Duration::ZERO);
+        assert!(event.to_json().contains(r#""preset":null"#));
+    }
+}
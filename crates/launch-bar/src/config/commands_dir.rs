@@ -0,0 +1,115 @@
+//! Commands auto-registered from script files in `.launch-bar/commands/`
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::types::CommandConfig;
+use crate::script::ScriptType;
+
+/// Directory, relative to the working directory, scanned for command scripts
+pub const COMMANDS_DIR: &str = ".launch-bar/commands";
+
+/// One command per `*.rhai` / `*.lua` file, sorted by file name
+///
+/// The file stem becomes the name; an `icon: <name>` line in the leading
+/// comment block sets the icon. A missing directory yields no commands.
+pub fn scan_commands_dir(dir: &Path) -> Vec<CommandConfig> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let script_type = ScriptType::from_extension(path.to_str()?)?;
+            let name = path.file_stem()?.to_str()?.to_string();
+            Some(CommandConfig {
+                name,
+                run: Some(format!("@{}", path.display())),
+                icon: leading_icon(&path, script_type),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// `icon: <name>` from the comment lines at the top of a script
+fn leading_icon(path: &Path, script_type: ScriptType) -> Option<String> {
+    let marker = match script_type {
+        ScriptType::Rhai => "//",
+        ScriptType::Lua => "--",
+    };
+    let file = File::open(path).ok()?;
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .map(|line| line.trim().to_string())
+        .skip_while(|line| line.is_empty())
+        .take_while(|line| line.starts_with(marker))
+        .find_map(|line| {
+            let comment = line.trim_start_matches(marker).trim();
+            let icon = comment.strip_prefix("icon:")?.trim();
+            (!icon.is_empty()).then(|| icon.to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "launch-bar-commands-dir-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_scan_builds_commands() {
+        let dir = temp_dir("scan");
+        std::fs::write(
+            dir.join("deploy.rhai"),
+            "// Deploy to staging\n// icon: upload\nshell(\"./deploy.sh\");\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("build.lua"), "\n-- icon: wrench\nshell('make')\n").unwrap();
+        std::fs::write(dir.join("plain.rhai"), "notify(\"hi\");\n// icon: late\n").unwrap();
+        std::fs::write(dir.join("notes.txt"), "// icon: info\n").unwrap();
+
+        let commands = scan_commands_dir(&dir);
+        let summary: Vec<_> = commands
+            .iter()
+            .map(|c| (c.name.as_str(), c.icon.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("build", Some("wrench")),
+                ("deploy", Some("upload")),
+                ("plain", None),
+            ]
+        );
+        assert_eq!(
+            commands[1].run,
+            Some(format!("@{}", dir.join("deploy.rhai").display()))
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_scan_missing_dir() {
+        assert!(scan_commands_dir(Path::new("/nonexistent/launch-bar/commands")).is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+//! `launch-bar config dump`: the merged configuration, with each preset's source
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::resolver::ResolvedConfig;
+use super::types::{Preset, WindowSettings};
+
+/// Serializable view of a resolved configuration
+#[derive(Debug, Serialize)]
+pub struct ConfigDump<'a> {
+    /// Preset selected by `--preset`, `LAUNCH_BAR_PRESET` or stdin
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explicit_preset: Option<&'a str>,
+    pub window: &'a WindowSettings,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub categories: &'a HashMap<String, String>,
+    pub presets: Vec<PresetDump<'a>>,
+}
+
+/// A resolved preset and the source that won it
+#[derive(Debug, Serialize)]
+pub struct PresetDump<'a> {
+    pub source: &'static str,
+    #[serde(flatten)]
+    pub preset: &'a Preset,
+}
+
+impl<'a> ConfigDump<'a> {
+    pub fn new(resolved: &'a ResolvedConfig) -> Self {
+        Self {
+            explicit_preset: resolved
+                .explicit_preset
+                .as_ref()
+                .map(|(name, _)| name.as_str()),
+            window: &resolved.window,
+            categories: &resolved.categories,
+            presets: resolved
+                .presets
+                .iter()
+                .map(|r| PresetDump {
+                    source: r.source.as_str(),
+                    preset: &r.preset,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_toml(&self) -> Result<String, String> {
+        toml::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PresetResolver;
+
+    fn config(src: &str) -> crate::config::types::Config {
+        toml::from_str(src).unwrap()
+    }
+
+    #[test]
+    fn test_project_preset_overrides_global() {
+        let mut resolver = PresetResolver::new();
+        resolver.add_global(config(
+            r#"
+[[presets]]
+name = "Rust"
+commands = [{ name = "Build", cmd = "cargo build" }]
+
+[[presets]]
+name = "Notes"
+commands = [{ name = "Open", cmd = "open notes.md" }]
+"#,
+        ));
+        resolver.add_project(config(
+            r#"
+[[presets]]
+name = "Rust"
+commands = [{ name = "Test", cmd = "cargo test" }]
+"#,
+        ));
+        let resolved = resolver.resolve();
+        let dump = ConfigDump::new(&resolved);
+
+        let json: serde_json::Value = serde_json::from_str(&dump.to_json().unwrap()).unwrap();
+        let presets = json["presets"].as_array().unwrap();
+        let rust: Vec<_> = presets.iter().filter(|p| p["name"] == "Rust").collect();
+        assert_eq!(rust.len(), 1);
+        assert_eq!(rust[0]["source"], "project");
+        assert_eq!(rust[0]["commands"][0]["name"], "Test");
+        let notes = presets.iter().find(|p| p["name"] == "Notes").unwrap();
+        assert_eq!(notes["source"], "global");
+
+        // The TOML form parses back
+        let toml_dump = dump.to_toml().unwrap();
+        let value: toml::Value = toml::from_str(&toml_dump).unwrap();
+        assert_eq!(value["presets"].as_array().unwrap().len(), 2);
+    }
+}
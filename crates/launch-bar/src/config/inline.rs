@@ -0,0 +1,91 @@
+//! Ad-hoc commands given on the command line (`--cmd "name=command:icon"`)
+
+use super::types::{CommandConfig, Preset};
+
+/// Name of the ephemeral preset holding `--cmd` commands
+pub const INLINE_PRESET_NAME: &str = "Inline";
+
+/// Parse `name=command[:icon]`
+///
+/// The icon is the text after the last `:` when it looks like an icon name
+/// (letters, digits, `-`, `_`). End with a bare `:` to keep a colon in the
+/// command without setting an icon.
+pub fn parse_inline_cmd(s: &str) -> Result<CommandConfig, String> {
+    let (name, rest) = s
+        .split_once('=')
+        .ok_or_else(|| format!("'{}' is not in name=command:icon form", s))?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(format!("'{}' has an empty name", s));
+    }
+
+    let (cmd, icon) = match rest.rsplit_once(':') {
+        Some((cmd, "")) => (cmd, None),
+        Some((cmd, icon)) if is_icon_name(icon) => (cmd, Some(icon.to_string())),
+        _ => (rest, None),
+    };
+    let cmd = cmd.trim();
+    if cmd.is_empty() {
+        return Err(format!("'{}' has an empty command", s));
+    }
+
+    Ok(CommandConfig {
+        name: name.to_string(),
+        cmd: Some(cmd.to_string()),
+        icon,
+        ..Default::default()
+    })
+}
+
+fn is_icon_name(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Ephemeral preset holding the `--cmd` commands
+pub fn inline_preset(commands: Vec<CommandConfig>) -> Preset {
+    Preset {
+        name: INLINE_PRESET_NAME.to_string(),
+        commands,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_with_icon() {
+        let cmd = parse_inline_cmd("Build=cargo build:wrench").unwrap();
+        assert_eq!(cmd.name, "Build");
+        assert_eq!(cmd.cmd.as_deref(), Some("cargo build"));
+        assert_eq!(cmd.icon.as_deref(), Some("wrench"));
+    }
+
+    #[test]
+    fn test_parse_missing_icon() {
+        let cmd = parse_inline_cmd("Test=cargo test").unwrap();
+        assert_eq!(cmd.cmd.as_deref(), Some("cargo test"));
+        assert_eq!(cmd.icon, None);
+
+        // Colons that don't introduce an icon stay in the command
+        let cmd = parse_inline_cmd("Docs=open https://docs.rs").unwrap();
+        assert_eq!(cmd.cmd.as_deref(), Some("open https://docs.rs"));
+        assert_eq!(cmd.icon, None);
+
+        // A trailing ':' means "no icon"
+        let cmd = parse_inline_cmd("Echo=echo a:b:").unwrap();
+        assert_eq!(cmd.cmd.as_deref(), Some("echo a:b"));
+        assert_eq!(cmd.icon, None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_inline_cmd("cargo build").is_err());
+        assert!(parse_inline_cmd("=cargo build").is_err());
+        assert!(parse_inline_cmd("Build=").is_err());
+        assert!(parse_inline_cmd("Build=:wrench").is_err());
+    }
+}
@@ -0,0 +1,98 @@
+//! Locate a preset's section in raw config text (parsed TOML has no positions)
+
+use super::types::GLOBAL_PRESET_NAME;
+
+/// 1-based line of the `[[presets]]` header whose `name` is `name`
+///
+/// Matching is case-insensitive, like preset lookup. For `[Global]` this is
+/// the top-level `commands` key or first `[[commands]]` table.
+pub fn find_preset_line(toml_src: &str, name: &str) -> Option<usize> {
+    let global = name == GLOBAL_PRESET_NAME;
+    let mut header: Option<usize> = None;
+    let mut top_level = true;
+
+    for (i, raw) in toml_src.lines().enumerate() {
+        let line = raw.trim();
+        if line.starts_with('[') {
+            top_level = false;
+            if global
+                && line
+                    .trim_start_matches('[')
+                    .trim_start()
+                    .starts_with("commands")
+            {
+                return Some(i + 1);
+            }
+            header = (line == "[[presets]]").then_some(i + 1);
+            continue;
+        }
+        if global && top_level && key_value(line).is_some_and(|(key, _)| key == "commands") {
+            return Some(i + 1);
+        }
+        if let (Some(header), Some(("name", value))) = (header, key_value(line)) {
+            if unquote(value).is_some_and(|v| v.eq_ignore_ascii_case(name)) {
+                return Some(header);
+            }
+        }
+    }
+    None
+}
+
+/// Split `key = value` (value trimmed, trailing comment kept)
+fn key_value(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once('=')?;
+    Some((key.trim(), value.trim()))
+}
+
+/// Contents of a leading `"..."` or `'...'` string
+fn unquote(value: &str) -> Option<&str> {
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let rest = &value[1..];
+    rest.find(quote).map(|end| &rest[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"[window]
+opacity = 0.8
+
+commands = []
+
+[[presets]]
+name = "RustDev"
+detect_file = "Cargo.toml"
+commands = [
+    { name = "Run", cmd = "cargo run" },
+]
+
+[[presets]]
+# Node projects
+name = 'NodeDev'  # single quotes
+commands = []
+
+[profiles.work]
+name = "NotAPreset"
+"#;
+
+    #[test]
+    fn test_find_preset_line() {
+        assert_eq!(find_preset_line(CONFIG, "RustDev"), Some(6));
+        assert_eq!(find_preset_line(CONFIG, "nodedev"), Some(13));
+        // Command names and non-preset tables don't count
+        assert_eq!(find_preset_line(CONFIG, "Run"), None);
+        assert_eq!(find_preset_line(CONFIG, "NotAPreset"), None);
+        assert_eq!(find_preset_line(CONFIG, "Missing"), None);
+    }
+
+    #[test]
+    fn test_find_global_commands_line() {
+        // `commands` inside [window] is not top-level
+        assert_eq!(find_preset_line(CONFIG, GLOBAL_PRESET_NAME), None);
+        let src = "commands = [\n  { name = \"Hi\", cmd = \"echo hi\" },\n]\n";
+        assert_eq!(find_preset_line(src, GLOBAL_PRESET_NAME), Some(1));
+        let src = "[window]\nopacity = 1.0\n\n[[commands]]\nname = \"Hi\"\n";
+        assert_eq!(find_preset_line(src, GLOBAL_PRESET_NAME), Some(4));
+    }
+}
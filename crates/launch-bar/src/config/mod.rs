@@ -1,10 +1,27 @@
 //! Configuration module for Launch Bar
 
+mod commands_dir;
 mod detect;
+mod dump;
+mod inline;
+mod locate;
 mod resolver;
+mod schema;
+mod sources;
 mod state;
+mod stats;
+mod stdin;
 mod types;
 
-pub use resolver::{PresetResolver, ResolvedConfig};
+pub use dump::ConfigDump;
+pub use inline::parse_inline_cmd;
+pub use locate::find_preset_line;
+pub use resolver::{merged_commands, PickCandidate, PresetResolver, ResolvedConfig};
+pub use schema::config_schema;
+pub use sources::{read_config_file, ConfigSources, ENV_PRESET};
 pub use state::AppState;
-pub use types::{CommandConfig, Config, Preset, WindowSettings};
+pub use stats::CommandStats;
+pub use stdin::{parse_stdin_input, StdinFormat, StdinInput};
+pub use types::{
+    CmdVariant, ColorConfig, CommandConfig, Preset, WindowSettings, GLOBAL_PRESET_NAME,
+};
@@ -5,14 +5,16 @@
 //! 2. Project config (./launch-bar.toml)
 //! 3. CLI argument (--preset <name>)
 //! 4. Environment variable (LAUNCH_BAR_PRESET)
+//! 5. Stdin (--stdin <format>), ephemeral and always selected
 
 use super::detect::detect_preset_idx;
-use super::types::{Config, Preset, WindowSettings};
+use super::types::{
+    retain_applicable, CommandConfig, Config, LibraryCommand, Preset, ProfileOverride,
+    WindowSettings, GLOBAL_PRESET_NAME,
+};
+use std::collections::HashMap;
 use std::path::Path;
 
-#[cfg(test)]
-use super::types::GLOBAL_PRESET_NAME;
-
 /// Configuration source with priority ordering
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ConfigSource {
@@ -20,20 +22,38 @@ pub enum ConfigSource {
     Project = 1,
     Arg = 2,
     Env = 3,
+    Stdin = 4,
 }
 
 impl ConfigSource {
-    #[allow(dead_code)]
     pub fn as_str(&self) -> &'static str {
         match self {
             ConfigSource::Global => "global",
             ConfigSource::Project => "project",
             ConfigSource::Arg => "arg",
             ConfigSource::Env => "env",
+            ConfigSource::Stdin => "stdin",
         }
     }
 }
 
+/// A preset offered by `--pick`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PickCandidate {
+    pub name: String,
+    /// Config source label (`global`, `project`, ...)
+    pub source: &'static str,
+    /// Whether its `detect_file` / `cwd_pattern` matches the working directory
+    pub detected: bool,
+}
+
+/// Select `name` as the `--preset` argument does: a stdin selection always wins
+fn select_arg_preset(explicit: &mut Option<(String, ConfigSource)>, name: String) {
+    if !matches!(explicit, Some((_, ConfigSource::Stdin))) {
+        *explicit = Some((name, ConfigSource::Arg));
+    }
+}
+
 /// Preset with source tracking
 #[derive(Debug, Clone)]
 pub struct ResolvedPreset {
@@ -51,6 +71,16 @@ pub struct PresetResolver {
     window: WindowSettings,
     /// Explicitly selected preset name (from arg or env)
     explicit_preset: Option<(String, ConfigSource)>,
+    /// Profiles from all sources (later sources replace same-named profiles)
+    profiles: HashMap<String, ProfileOverride>,
+    /// Profile selected by CLI argument
+    arg_profile: Option<String>,
+    /// Profile selected by environment variable
+    env_profile: Option<String>,
+    /// `[[library]]` commands from all sources (later sources replace same ids)
+    library: Vec<LibraryCommand>,
+    /// `[categories]` colors from all sources (later sources override names)
+    categories: HashMap<String, String>,
 }
 
 impl PresetResolver {
@@ -59,6 +89,11 @@ impl PresetResolver {
             presets: Vec::new(),
             window: WindowSettings::default(),
             explicit_preset: None,
+            profiles: HashMap::new(),
+            arg_profile: None,
+            env_profile: None,
+            library: Vec::new(),
+            categories: HashMap::new(),
         }
     }
 
@@ -72,19 +107,63 @@ impl PresetResolver {
         self.add_config(config, ConfigSource::Project);
     }
 
+    /// Add a preset read from stdin (highest priority) and select it
+    pub fn add_stdin_preset(&mut self, preset: Preset) {
+        self.explicit_preset = Some((preset.name.clone(), ConfigSource::Stdin));
+        self.presets.push(ResolvedPreset {
+            preset,
+            source: ConfigSource::Stdin,
+        });
+    }
+
+    /// Add a config read from stdin (highest priority), selecting its first preset
+    pub fn add_stdin_config(&mut self, config: Config) {
+        let first = config
+            .commands_as_preset()
+            .or_else(|| config.presets.first().cloned())
+            .map(|p| p.name);
+        self.add_config(config, ConfigSource::Stdin);
+        if let Some(name) = first {
+            self.explicit_preset = Some((name, ConfigSource::Stdin));
+        }
+    }
+
     /// Set explicit preset from CLI argument
     pub fn set_arg_preset(&mut self, name: String) {
-        self.explicit_preset = Some((name, ConfigSource::Arg));
+        select_arg_preset(&mut self.explicit_preset, name);
     }
 
     /// Set explicit preset from environment variable
     pub fn set_env_preset(&mut self, name: String) {
-        // Only set if not already set by Arg (Arg has higher priority)
-        if self.explicit_preset.as_ref().map(|(_, s)| *s) != Some(ConfigSource::Arg) {
+        // Only set if not already set by Arg or Stdin (both have higher priority)
+        if !matches!(
+            self.explicit_source(),
+            Some(ConfigSource::Arg | ConfigSource::Stdin)
+        ) {
             self.explicit_preset = Some((name, ConfigSource::Env));
         }
     }
 
+    /// Select a profile from the CLI argument (overrides the environment)
+    pub fn set_arg_profile(&mut self, name: String) {
+        self.arg_profile = Some(name);
+    }
+
+    /// Select a profile from the environment variable
+    pub fn set_env_profile(&mut self, name: String) {
+        self.env_profile = Some(name);
+    }
+
+    /// Selected profile name: CLI argument first, then environment
+    fn selected_profile(&self) -> Option<&str> {
+        self.arg_profile.as_deref().or(self.env_profile.as_deref())
+    }
+
+    /// Source of the current explicit preset selection
+    fn explicit_source(&self) -> Option<ConfigSource> {
+        self.explicit_preset.as_ref().map(|(_, s)| *s)
+    }
+
     /// Add config from a specific source
     fn add_config(&mut self, config: Config, source: ConfigSource) {
         // Merge window settings (later overrides)
@@ -102,6 +181,16 @@ impl PresetResolver {
         for preset in config.presets {
             self.presets.push(ResolvedPreset { preset, source });
         }
+
+        self.profiles.extend(config.profiles);
+
+        for entry in config.library {
+            self.library
+                .retain(|existing| !existing.id.eq_ignore_ascii_case(&entry.id));
+            self.library.push(entry);
+        }
+
+        self.categories.extend(config.categories);
     }
 
     /// Merge window settings (only override non-default values)
@@ -109,15 +198,67 @@ impl PresetResolver {
         // Later source values override earlier ones
         self.window.max_icons = new_window.max_icons;
         self.window.opacity = new_window.opacity;
+        if new_window.blur_opacity.is_some() {
+            self.window.blur_opacity = new_window.blur_opacity;
+        }
+        if new_window.hover_opacity.is_some() {
+            self.window.hover_opacity = new_window.hover_opacity;
+        }
         if new_window.background_color.is_some() {
             self.window.background_color = new_window.background_color.clone();
         }
         self.window.border = new_window.border.clone();
         self.window.title_bar = new_window.title_bar.clone();
         self.window.accent_line = new_window.accent_line.clone();
+        self.window.accent_from_result = new_window.accent_from_result;
+        self.window.summary_status = new_window.summary_status;
+        self.window.preview_expanded = new_window.preview_expanded;
+        if new_window.on_close.is_some() {
+            self.window.on_close = new_window.on_close.clone();
+        }
+        if new_window.editor.is_some() {
+            self.window.editor = new_window.editor.clone();
+        }
+        if new_window.webhook_url.is_some() {
+            self.window.webhook_url = new_window.webhook_url.clone();
+        }
         if new_window.default_script.is_some() {
             self.window.default_script = new_window.default_script;
         }
+        self.window.badge_interval_secs = new_window.badge_interval_secs;
+        self.window.max_output_bytes = new_window.max_output_bytes;
+        self.window.login_shell = new_window.login_shell;
+        if new_window.default_shell.is_some() {
+            self.window.default_shell = new_window.default_shell;
+        }
+        self.window.merge_global = new_window.merge_global;
+        self.window.auto_detect = new_window.auto_detect;
+        self.window.drag_anywhere = new_window.drag_anywhere;
+        self.window.start_position = new_window.start_position.clone();
+        self.window.show_git_branch = new_window.show_git_branch;
+        if new_window.max_concurrent.is_some() {
+            self.window.max_concurrent = new_window.max_concurrent;
+        }
+        self.window.all_spaces = new_window.all_spaces;
+        self.window.no_activate = new_window.no_activate;
+        self.window.x11_dock = new_window.x11_dock;
+        self.window.click_through = new_window.click_through;
+        self.window.remember_last_command = new_window.remember_last_command;
+        self.window.poll_interval_ms = new_window.poll_interval_ms;
+        self.window.max_fps = new_window.max_fps;
+        self.window.theme = new_window.theme.clone();
+        self.window.high_contrast = new_window.high_contrast;
+        self.window.colors = new_window.colors.clone();
+        self.window.padding = new_window.padding;
+        self.window.spacing = new_window.spacing;
+        self.window.read_file_max_bytes = new_window.read_file_max_bytes;
+        self.window.ai = new_window.ai.clone();
+        if !new_window.path_prepend.is_empty() {
+            self.window.path_prepend = new_window.path_prepend.clone();
+        }
+        if !new_window.redact_patterns.is_empty() {
+            self.window.redact_patterns = new_window.redact_patterns.clone();
+        }
     }
 
     /// Resolve presets (deduplicate by name, later source wins)
@@ -159,13 +300,49 @@ impl PresetResolver {
         let mut presets = global_presets;
         presets.extend(other_presets);
 
+        // Pull in `use`d library commands, then resolve `extends` after
+        // dedup so children see the winning parent
+        for warning in apply_library(&mut presets, &self.library) {
+            eprintln!("[warn] {}", warning);
+        }
+        for warning in apply_inheritance(&mut presets) {
+            eprintln!("[warn] {}", warning);
+        }
+
+        // Apply the selected profile on top of the merged settings
+        let mut window = self.window.clone();
+        if let Some(name) = self.selected_profile() {
+            match self.find_profile(name) {
+                Some(profile) => {
+                    profile.apply(&mut window);
+                    // Stdin presets are always available
+                    presets.retain(|r| {
+                        r.source == ConfigSource::Stdin || profile.allows(&r.preset.name)
+                    });
+                }
+                None => eprintln!("[warn] Profile '{}' not found", name),
+            }
+        }
+        for warning in window.validate() {
+            eprintln!("[warn] {}", warning);
+        }
+
         ResolvedConfig {
             presets,
-            window: self.window.clone(),
+            window,
             explicit_preset: self.explicit_preset.clone(),
+            categories: self.categories.clone(),
         }
     }
 
+    /// Find a profile by name (case-insensitive)
+    fn find_profile(&self, name: &str) -> Option<&ProfileOverride> {
+        self.profiles
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, profile)| profile)
+    }
+
     /// Get merged window settings
     #[allow(dead_code)]
     pub fn window(&self) -> &WindowSettings {
@@ -173,6 +350,105 @@ impl PresetResolver {
     }
 }
 
+/// Prepend each preset's `use`d library commands, returning warnings
+///
+/// Library commands come first, in `use` order; the preset's own commands
+/// with the same name replace them. Unknown ids are skipped.
+fn apply_library(presets: &mut [ResolvedPreset], library: &[LibraryCommand]) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    for resolved in presets.iter_mut() {
+        let preset = &mut resolved.preset;
+        if preset.uses.is_empty() {
+            continue;
+        }
+        let mut used = Vec::new();
+        for id in &preset.uses {
+            match library
+                .iter()
+                .find(|entry| entry.id.eq_ignore_ascii_case(id))
+            {
+                Some(entry) => used.push(entry.command.clone()),
+                None => warnings.push(format!(
+                    "Preset '{}' uses unknown library command '{}'",
+                    preset.name, id
+                )),
+            }
+        }
+        preset.commands = merge_commands(used, &preset.commands);
+    }
+
+    warnings
+}
+
+/// Append `overrides` to `base`, replacing same-named (case-insensitive) commands in place
+fn merge_commands(mut base: Vec<CommandConfig>, overrides: &[CommandConfig]) -> Vec<CommandConfig> {
+    for cmd in overrides {
+        match base
+            .iter_mut()
+            .find(|c| c.name.eq_ignore_ascii_case(&cmd.name))
+        {
+            Some(existing) => *existing = cmd.clone(),
+            None => base.push(cmd.clone()),
+        }
+    }
+    base
+}
+
+/// Resolve preset inheritance (`extends`), returning warnings
+///
+/// Parent commands come first; child commands with the same name replace them.
+/// Presets with a missing parent or an inheritance cycle keep their own commands.
+fn apply_inheritance(presets: &mut [ResolvedPreset]) -> Vec<String> {
+    let originals: Vec<Preset> = presets.iter().map(|r| r.preset.clone()).collect();
+    let mut warnings = Vec::new();
+
+    for resolved in presets.iter_mut() {
+        if resolved.preset.extends.is_none() {
+            continue;
+        }
+        let mut chain = vec![resolved.preset.name.to_lowercase()];
+        match inherited_commands(&originals, &resolved.preset, &mut chain) {
+            Ok(commands) => resolved.preset.commands = commands,
+            Err(warning) => warnings.push(warning),
+        }
+    }
+
+    warnings
+}
+
+/// Collect a preset's commands including those of its ancestors
+fn inherited_commands(
+    all: &[Preset],
+    preset: &Preset,
+    chain: &mut Vec<String>,
+) -> Result<Vec<CommandConfig>, String> {
+    let Some(ref parent_name) = preset.extends else {
+        return Ok(preset.commands.clone());
+    };
+
+    let key = parent_name.to_lowercase();
+    if chain.contains(&key) {
+        return Err(format!(
+            "Preset '{}' has an inheritance cycle via '{}'",
+            preset.name, parent_name
+        ));
+    }
+    let parent = all
+        .iter()
+        .find(|p| p.name.eq_ignore_ascii_case(parent_name))
+        .ok_or_else(|| {
+            format!(
+                "Preset '{}' extends unknown preset '{}'",
+                preset.name, parent_name
+            )
+        })?;
+
+    chain.push(key);
+    let commands = inherited_commands(all, parent, chain)?;
+    Ok(merge_commands(commands, &preset.commands))
+}
+
 impl Default for PresetResolver {
     fn default() -> Self {
         Self::new()
@@ -185,6 +461,8 @@ pub struct ResolvedConfig {
     pub presets: Vec<ResolvedPreset>,
     pub window: WindowSettings,
     pub explicit_preset: Option<(String, ConfigSource)>,
+    /// Category name -> hex color
+    pub categories: HashMap<String, String>,
 }
 
 impl ResolvedConfig {
@@ -200,6 +478,34 @@ impl ResolvedConfig {
             .position(|r| r.preset.name.eq_ignore_ascii_case(name))
     }
 
+    /// Append commands to every preset, skipping names a preset already defines
+    pub fn add_dir_commands(&mut self, commands: &[CommandConfig]) {
+        for resolved in &mut self.presets {
+            let preset = &mut resolved.preset;
+            for cmd in commands {
+                if !preset
+                    .commands
+                    .iter()
+                    .any(|c| c.name.eq_ignore_ascii_case(&cmd.name))
+                {
+                    preset.commands.push(cmd.clone());
+                }
+            }
+        }
+    }
+
+    /// Commands shown for a preset
+    ///
+    /// With `window.merge_global`, `[Global]` commands follow the preset's own
+    /// (skipping names the preset already defines). Commands whose `only_if_os`
+    /// or `only_if_binary` don't hold here are left out.
+    pub fn commands_for(&self, idx: usize) -> Vec<CommandConfig> {
+        let global = self
+            .find_preset(GLOBAL_PRESET_NAME)
+            .map(|global_idx| &self.presets[global_idx].preset);
+        merged_commands(&self.presets[idx].preset, global, self.window.merge_global)
+    }
+
     /// Detect or select initial preset
     pub fn detect_preset(&self, working_dir: &Path) -> Option<usize> {
         // 1. Explicit preset (Env or Arg) has highest priority
@@ -210,11 +516,45 @@ impl ResolvedConfig {
             eprintln!("[warn] Specified preset '{}' not found", name);
         }
 
-        // 2. Auto-detect by file/path pattern
+        // 2. Without auto-detection, the global preset
+        if !self.window.auto_detect {
+            return self.find_preset(GLOBAL_PRESET_NAME);
+        }
+
+        // 3. Auto-detect by file/path pattern
         let presets: Vec<_> = self.presets.iter().map(|r| r.preset.clone()).collect();
         detect_preset_idx(working_dir, &presets)
     }
 
+    /// Select a preset by name as `--preset` would (e.g. the `--pick` choice)
+    pub fn select_preset(&mut self, name: String) {
+        select_arg_preset(&mut self.explicit_preset, name);
+    }
+
+    /// Every preset with its source, for the `--pick` list
+    ///
+    /// Global presets come first, then by source and name, so the numbering
+    /// is the same on every start.
+    pub fn pick_candidates(&self, working_dir: &Path) -> Vec<PickCandidate> {
+        let mut presets: Vec<&ResolvedPreset> = self.presets.iter().collect();
+        presets.sort_by_key(|r| {
+            (
+                !r.preset.is_global(),
+                r.source,
+                r.preset.name.to_lowercase(),
+            )
+        });
+        presets
+            .into_iter()
+            .map(|resolved| PickCandidate {
+                name: resolved.preset.name.clone(),
+                source: resolved.source.as_str(),
+                detected: detect_preset_idx(working_dir, std::slice::from_ref(&resolved.preset))
+                    .is_some(),
+            })
+            .collect()
+    }
+
     /// Build switch order: Global presets -> Project presets -> cycle
     ///
     /// Order: detected -> global group -> other group -> back to detected
@@ -245,18 +585,45 @@ impl ResolvedConfig {
     }
 }
 
+/// Commands shown for `preset`, followed by `global`'s with `merge_global`
+///
+/// Shared by [`ResolvedConfig::commands_for`] and preset switching in the app,
+/// which only keeps the resolved presets.
+pub fn merged_commands(
+    preset: &Preset,
+    global: Option<&Preset>,
+    merge_global: bool,
+) -> Vec<CommandConfig> {
+    let mut commands = preset.commands.clone();
+    if merge_global && preset.name != GLOBAL_PRESET_NAME {
+        for cmd in global.map_or(&[][..], |global| global.commands.as_slice()) {
+            if !commands
+                .iter()
+                .any(|c| c.name.eq_ignore_ascii_case(&cmd.name))
+            {
+                commands.push(cmd.clone());
+            }
+        }
+    }
+    retain_applicable(&mut commands);
+    commands
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::CommandConfig;
 
     fn make_preset(name: &str, detect_file: Option<&str>) -> Preset {
         Preset {
             name: name.to_string(),
             detect_file: detect_file.map(|s| s.to_string()),
             cwd_pattern: None,
+            extends: None,
             base_color: None,
             default_script: None,
+            script_prelude: None,
+            uses: vec![],
+            readonly: false,
             commands: vec![],
         }
     }
@@ -266,6 +633,9 @@ mod tests {
             window: WindowSettings::default(),
             presets,
             commands,
+            profiles: HashMap::new(),
+            library: vec![],
+            categories: HashMap::new(),
         }
     }
 
@@ -298,10 +668,50 @@ mod tests {
         let commands = vec![CommandConfig {
             name: "Terminal".to_string(),
             cmd: Some("open -a Terminal .".to_string()),
+            cmd_shift: None,
+            cmd_alt: None,
+            cmd_ctrl: None,
+            fallback: vec![],
             run: None,
             script_type: None,
             icon: Some("terminal".to_string()),
             cwd: None,
+            enabled_when: None,
+            badge: None,
+            poll_secs: None,
+            on_success: None,
+            on_failure: None,
+            login_shell: None,
+            shell: None,
+            description: None,
+            alias: None,
+            quiet: false,
+            notify_on_done: None,
+            lock: None,
+            detach: false,
+            session: false,
+            log_to: None,
+            log_append: false,
+            hotkey: None,
+            key: None,
+            background: None,
+            category: None,
+            success_pattern: None,
+            failure_pattern: None,
+            progress_pattern: None,
+            pause_watcher: false,
+            elevate: false,
+            then_preset: None,
+            retries: 0,
+            retry_delay_secs: None,
+            spawn_bar: None,
+            only_if_os: None,
+            only_if_binary: None,
+            nice: None,
+            focus_app: None,
+            prompt: None,
+            session_timeout_secs: None,
+            global_hotkey: None,
         }];
         let config = make_config(vec![], commands);
         resolver.add_global(config);
@@ -330,6 +740,69 @@ mod tests {
         assert_eq!(resolved.presets[detected.unwrap()].preset.name, "Prod");
     }
 
+    #[test]
+    fn test_no_detect_selects_global_preset() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let config = make_config(
+            vec![make_preset("Rust", Some("Cargo.toml"))],
+            vec![make_command("Open", "open .")],
+        );
+        let mut resolver = PresetResolver::new();
+        resolver.add_global(config.clone());
+        let mut resolved = resolver.resolve();
+        let detected = resolved.detect_preset(dir).unwrap();
+        assert_eq!(resolved.presets[detected].preset.name, "Rust");
+
+        // `--no-detect` turns auto-detection off
+        resolved.window.auto_detect = false;
+        let selected = resolved.detect_preset(dir).unwrap();
+        assert_eq!(resolved.presets[selected].preset.name, GLOBAL_PRESET_NAME);
+
+        // An explicit preset still wins
+        let mut resolver = PresetResolver::new();
+        resolver.add_global(config);
+        resolver.set_arg_preset("Rust".to_string());
+        let mut resolved = resolver.resolve();
+        resolved.window.auto_detect = false;
+        let selected = resolved.detect_preset(dir).unwrap();
+        assert_eq!(resolved.presets[selected].preset.name, "Rust");
+    }
+
+    #[test]
+    fn test_pick_candidates() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let mut resolver = PresetResolver::new();
+        resolver.add_global(make_config(
+            vec![
+                make_preset("Node", Some("package.json")),
+                make_preset("Rust", Some("Cargo.toml")),
+            ],
+            vec![make_command("Open", "open .")],
+        ));
+        resolver.add_project(make_config(vec![make_preset("Local", None)], vec![]));
+        let mut resolved = resolver.resolve();
+
+        let candidates = resolved.pick_candidates(dir);
+        let summary: Vec<_> = candidates
+            .iter()
+            .map(|c| (c.name.as_str(), c.source, c.detected))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (GLOBAL_PRESET_NAME, "global", false),
+                ("Local", "project", false),
+                ("Node", "global", false),
+                ("Rust", "global", true),
+            ]
+        );
+
+        // The picked preset is selected like --preset
+        resolved.select_preset("Local".to_string());
+        let selected = resolved.detect_preset(dir).unwrap();
+        assert_eq!(resolved.presets[selected].preset.name, "Local");
+    }
+
     #[test]
     fn test_arg_overrides_env() {
         let mut resolver = PresetResolver::new();
@@ -349,4 +822,322 @@ mod tests {
             Some(("Dev".to_string(), ConfigSource::Arg))
         );
     }
+
+    fn make_command(name: &str, cmd: &str) -> CommandConfig {
+        toml::from_str(&format!("name = \"{}\"\ncmd = \"{}\"", name, cmd)).unwrap()
+    }
+
+    fn make_child(name: &str, extends: &str, commands: Vec<CommandConfig>) -> Preset {
+        let mut preset = make_preset(name, Some("Cargo.toml"));
+        preset.extends = Some(extends.to_string());
+        preset.commands = commands;
+        preset
+    }
+
+    fn command_names(preset: &Preset) -> Vec<&str> {
+        preset.commands.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    fn find<'a>(resolved: &'a ResolvedConfig, name: &str) -> &'a Preset {
+        &resolved.presets[resolved.find_preset(name).unwrap()].preset
+    }
+
+    #[test]
+    fn test_extends_single_inheritance() {
+        let mut resolver = PresetResolver::new();
+        let mut base = make_preset("Base", None);
+        base.commands = vec![make_command("Git", "git status")];
+        let child = make_child("Rust", "Base", vec![make_command("Build", "cargo build")]);
+        resolver.add_global(make_config(vec![base, child], vec![]));
+
+        let resolved = resolver.resolve();
+        assert_eq!(command_names(find(&resolved, "Rust")), vec!["Git", "Build"]);
+        assert_eq!(command_names(find(&resolved, "Base")), vec!["Git"]);
+    }
+
+    #[test]
+    fn test_extends_override_by_name() {
+        let mut presets = vec![];
+        let mut base = make_preset("Base", None);
+        base.commands = vec![
+            make_command("Build", "make"),
+            make_command("Test", "make test"),
+        ];
+        presets.push(base);
+        presets.push(make_child(
+            "Rust",
+            "base",
+            vec![make_command("build", "cargo build")],
+        ));
+        let mut resolved: Vec<ResolvedPreset> = presets
+            .into_iter()
+            .map(|preset| ResolvedPreset {
+                preset,
+                source: ConfigSource::Global,
+            })
+            .collect();
+
+        assert!(apply_inheritance(&mut resolved).is_empty());
+        let child = &resolved[1].preset;
+        assert_eq!(command_names(child), vec!["build", "Test"]);
+        assert_eq!(child.commands[0].cmd.as_deref(), Some("cargo build"));
+    }
+
+    #[test]
+    fn test_extends_missing_parent() {
+        let mut resolved = vec![ResolvedPreset {
+            preset: make_child("Rust", "Nope", vec![make_command("Build", "cargo build")]),
+            source: ConfigSource::Global,
+        }];
+
+        let warnings = apply_inheritance(&mut resolved);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("unknown preset 'Nope'"));
+        assert_eq!(command_names(&resolved[0].preset), vec!["Build"]);
+    }
+
+    #[test]
+    fn test_extends_cycle() {
+        let mut resolved: Vec<ResolvedPreset> = vec![
+            make_child("A", "B", vec![make_command("One", "true")]),
+            make_child("B", "A", vec![make_command("Two", "true")]),
+        ]
+        .into_iter()
+        .map(|preset| ResolvedPreset {
+            preset,
+            source: ConfigSource::Global,
+        })
+        .collect();
+
+        let warnings = apply_inheritance(&mut resolved);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.iter().all(|w| w.contains("cycle")));
+        assert_eq!(command_names(&resolved[0].preset), vec!["One"]);
+        assert_eq!(command_names(&resolved[1].preset), vec!["Two"]);
+    }
+
+    #[test]
+    fn test_stdin_preset_overrides_project() {
+        let mut resolver = PresetResolver::new();
+
+        let project_config = make_config(vec![make_preset("Scratch", Some("Cargo.toml"))], vec![]);
+        resolver.add_project(project_config);
+        resolver.add_stdin_preset(make_preset("Scratch", None));
+        resolver.set_arg_preset("Other".to_string());
+        resolver.set_env_preset("Other".to_string());
+
+        let resolved = resolver.resolve();
+        assert_eq!(resolved.presets.len(), 1);
+        assert_eq!(resolved.presets[0].source, ConfigSource::Stdin);
+        assert_eq!(resolved.presets[0].preset.detect_file, None);
+
+        // Stdin preset is selected regardless of arg/env
+        let detected = resolved.detect_preset(Path::new("."));
+        assert_eq!(detected, Some(0));
+        assert_eq!(
+            resolved.explicit_preset,
+            Some(("Scratch".to_string(), ConfigSource::Stdin))
+        );
+    }
+
+    #[test]
+    fn test_merge_global_appends_global_commands() {
+        let mut resolver = PresetResolver::new();
+        let mut config = make_config(
+            vec![],
+            vec![
+                make_command("Git", "git status"),
+                make_command("Build", "make"),
+            ],
+        );
+        config.window.merge_global = true;
+        let mut rust = make_preset("Rust", Some("Cargo.toml"));
+        rust.commands = vec![
+            make_command("Build", "cargo build"),
+            make_command("Test", "cargo test"),
+        ];
+        config.presets.push(rust);
+        resolver.add_config(config, ConfigSource::Global);
+        let resolved = resolver.resolve();
+
+        let idx = resolved.find_preset("Rust").unwrap();
+        let commands = resolved.commands_for(idx);
+        let names: Vec<_> = commands.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Build", "Test", "Git"]);
+        assert_eq!(commands[0].cmd.as_deref(), Some("cargo build"));
+
+        // The [Global] preset itself is not duplicated
+        let global = resolved.find_preset(GLOBAL_PRESET_NAME).unwrap();
+        assert_eq!(resolved.commands_for(global).len(), 2);
+    }
+
+    #[test]
+    fn test_merge_global_disabled_by_default() {
+        let mut resolver = PresetResolver::new();
+        let mut rust = make_preset("Rust", Some("Cargo.toml"));
+        rust.commands = vec![make_command("Test", "cargo test")];
+        let config = make_config(vec![rust], vec![make_command("Git", "git status")]);
+        resolver.add_config(config, ConfigSource::Global);
+        let resolved = resolver.resolve();
+
+        let idx = resolved.find_preset("Rust").unwrap();
+        assert_eq!(resolved.commands_for(idx).len(), 1);
+    }
+
+    fn config_with_profiles() -> Config {
+        let mut config = make_config(
+            vec![
+                make_preset("Rust", Some("Cargo.toml")),
+                make_preset("Node", Some("package.json")),
+            ],
+            vec![],
+        );
+        config.profiles.insert(
+            "work".to_string(),
+            ProfileOverride {
+                opacity: Some(1.0),
+                accent_line: Some("show".to_string()),
+                presets: Some(vec!["rust".to_string()]),
+                ..Default::default()
+            },
+        );
+        config.profiles.insert(
+            "home".to_string(),
+            ProfileOverride {
+                opacity: Some(0.5),
+                ..Default::default()
+            },
+        );
+        config
+    }
+
+    #[test]
+    fn test_profile_overrides_window_and_filters_presets() {
+        let mut resolver = PresetResolver::new();
+        resolver.add_global(config_with_profiles());
+        resolver.set_arg_profile("Work".to_string());
+        let resolved = resolver.resolve();
+
+        assert_eq!(resolved.window.opacity, 1.0);
+        assert_eq!(resolved.window.accent_line, "show");
+        // Untouched settings keep their merged value
+        assert_eq!(resolved.window.border, "auto");
+        let names: Vec<_> = resolved.presets.iter().map(|r| &r.preset.name).collect();
+        assert_eq!(names, vec!["Rust"]);
+    }
+
+    #[test]
+    fn test_profile_arg_overrides_env() {
+        let mut resolver = PresetResolver::new();
+        resolver.add_global(config_with_profiles());
+        resolver.set_arg_profile("home".to_string());
+        resolver.set_env_profile("work".to_string());
+        let resolved = resolver.resolve();
+        assert_eq!(resolved.window.opacity, 0.5);
+        assert_eq!(resolved.presets.len(), 2);
+
+        let mut resolver = PresetResolver::new();
+        resolver.add_global(config_with_profiles());
+        resolver.set_env_profile("work".to_string());
+        assert_eq!(resolver.resolve().window.opacity, 1.0);
+    }
+
+    #[test]
+    fn test_unknown_profile_is_ignored() {
+        let mut resolver = PresetResolver::new();
+        resolver.add_global(config_with_profiles());
+        resolver.set_arg_profile("travel".to_string());
+        let resolved = resolver.resolve();
+        assert_eq!(resolved.window.opacity, WindowSettings::default().opacity);
+        assert_eq!(resolved.presets.len(), 2);
+    }
+
+    fn library_config() -> Config {
+        toml::from_str(
+            r#"
+[[library]]
+id = "build"
+name = "Build"
+cmd = "cargo build"
+
+[[library]]
+id = "test"
+name = "Test"
+cmd = "cargo test"
+
+[[presets]]
+name = "Rust"
+detect_file = "Cargo.toml"
+use = ["test", "build"]
+commands = [
+    { name = "Run", cmd = "cargo run" },
+    { name = "Build", cmd = "cargo build --release" },
+]
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_library_commands_come_first() {
+        let mut resolver = PresetResolver::new();
+        resolver.add_project(library_config());
+        let resolved = resolver.resolve();
+
+        let rust = find(&resolved, "Rust");
+        assert_eq!(command_names(rust), vec!["Test", "Build", "Run"]);
+        // The preset's own "Build" replaces the library one in place
+        assert_eq!(
+            rust.commands[1].cmd.as_deref(),
+            Some("cargo build --release")
+        );
+    }
+
+    #[test]
+    fn test_library_later_source_replaces_id() {
+        let mut resolver = PresetResolver::new();
+        resolver.add_global(library_config());
+        let project: Config = toml::from_str(
+            "[[library]]\nid = \"TEST\"\nname = \"Test\"\ncmd = \"cargo nextest run\"\n",
+        )
+        .unwrap();
+        resolver.add_project(project);
+        let resolved = resolver.resolve();
+
+        let rust = find(&resolved, "Rust");
+        assert_eq!(rust.commands[0].cmd.as_deref(), Some("cargo nextest run"));
+    }
+
+    #[test]
+    fn test_library_unknown_id_warns() {
+        let mut config = library_config();
+        config.presets[0].uses = vec!["lint".to_string(), "build".to_string()];
+        let mut presets = vec![ResolvedPreset {
+            preset: config.presets[0].clone(),
+            source: ConfigSource::Project,
+        }];
+
+        let warnings = apply_library(&mut presets, &config.library);
+        assert_eq!(
+            warnings,
+            vec!["Preset 'Rust' uses unknown library command 'lint'"]
+        );
+        assert_eq!(command_names(&presets[0].preset), vec!["Build", "Run"]);
+    }
+
+    #[test]
+    fn test_library_with_extends() {
+        let mut config = library_config();
+        config.presets.push(make_child(
+            "RustPlus",
+            "Rust",
+            vec![make_command("Lint", "cargo clippy")],
+        ));
+        let mut resolver = PresetResolver::new();
+        resolver.add_project(config);
+        let resolved = resolver.resolve();
+
+        let child = find(&resolved, "RustPlus");
+        assert_eq!(command_names(child), vec!["Test", "Build", "Run", "Lint"]);
+    }
 }
@@ -0,0 +1,38 @@
+//! JSON Schema generation for editor autocomplete (`launch-bar schema`)
+
+use super::types::Config;
+
+/// Generate the JSON Schema for the config file format
+pub fn config_schema() -> serde_json::Value {
+    serde_json::to_value(schemars::schema_for!(Config)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_top_level_keys() {
+        let schema = config_schema();
+        let properties = schema["properties"].as_object().unwrap();
+        for key in ["window", "presets", "commands"] {
+            assert!(properties.contains_key(key), "missing key: {}", key);
+        }
+    }
+
+    #[test]
+    fn test_schema_script_type_variants() {
+        let schema = config_schema().to_string();
+        assert!(schema.contains("\"rhai\""));
+        assert!(schema.contains("\"lua\""));
+    }
+
+    #[test]
+    fn test_schema_command_fields() {
+        let schema = config_schema();
+        let command = &schema["$defs"]["CommandConfig"]["properties"];
+        assert!(command.get("cmd").is_some());
+        assert!(command.get("run").is_some());
+        assert_eq!(schema["$defs"]["CommandConfig"]["required"][0], "name");
+    }
+}
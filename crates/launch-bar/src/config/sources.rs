@@ -0,0 +1,228 @@
+//! Config sources and (re)loading
+
+use std::path::{Path, PathBuf};
+
+use super::commands_dir::{scan_commands_dir, COMMANDS_DIR};
+use super::inline::inline_preset;
+use super::resolver::{PresetResolver, ResolvedConfig};
+use super::stdin::StdinInput;
+use super::types::{CommandConfig, Config};
+
+/// Environment variable selecting a preset by name
+pub const ENV_PRESET: &str = "LAUNCH_BAR_PRESET";
+
+/// Environment variable selecting a profile by name
+const ENV_PROFILE: &str = "LAUNCH_BAR_PROFILE";
+
+/// Everything a configuration is resolved from, kept for reloading
+#[derive(Debug, Clone)]
+pub struct ConfigSources {
+    pub global_path: PathBuf,
+    pub local_path: PathBuf,
+    pub arg_preset: Option<String>,
+    pub arg_profile: Option<String>,
+    pub stdin: Option<StdinInput>,
+    /// Commands from `--cmd`; when present, every other source is ignored
+    pub inline: Vec<CommandConfig>,
+    /// Skip preset auto-detection (`--no-detect`)
+    pub no_detect: bool,
+}
+
+impl ConfigSources {
+    /// Resolve all sources, skipping config files that can't be read or parsed
+    pub fn resolve(&self) -> ResolvedConfig {
+        self.build(false)
+            .expect("lenient config resolution never fails")
+    }
+
+    /// Re-read and resolve all sources, failing on the first invalid config file
+    pub fn reload(&self) -> Result<ResolvedConfig, String> {
+        self.build(true)
+    }
+
+    /// Config file opened by the gear button: project config if present, else global
+    pub fn active_path(&self) -> PathBuf {
+        if self.local_path.exists() {
+            self.local_path.clone()
+        } else {
+            self.global_path.clone()
+        }
+    }
+
+    fn build(&self, strict: bool) -> Result<ResolvedConfig, String> {
+        let mut resolver = PresetResolver::new();
+
+        // Inline commands form a one-off bar without any config file
+        if !self.inline.is_empty() {
+            resolver.add_stdin_preset(inline_preset(self.inline.clone()));
+            return Ok(resolver.resolve());
+        }
+
+        // 1. Global config (lowest priority)
+        if let Some(config) = load_source(&self.global_path, strict)? {
+            resolver.add_global(config);
+        }
+
+        // 2. Project config (overrides global)
+        if let Some(config) = load_source(&self.local_path, strict)? {
+            resolver.add_project(config);
+        }
+
+        // 3. CLI argument preset (overrides project)
+        if let Some(ref name) = self.arg_preset {
+            resolver.set_arg_preset(name.clone());
+        }
+
+        // 4. Environment variable
+        if let Ok(env_preset) = std::env::var(ENV_PRESET) {
+            if !env_preset.is_empty() {
+                resolver.set_env_preset(env_preset);
+            }
+        }
+
+        // Profile: CLI argument, then environment variable
+        if let Some(ref name) = self.arg_profile {
+            resolver.set_arg_profile(name.clone());
+        }
+        if let Ok(env_profile) = std::env::var(ENV_PROFILE) {
+            if !env_profile.is_empty() {
+                resolver.set_env_profile(env_profile);
+            }
+        }
+
+        // 5. Stdin preset/config (highest priority, always selected)
+        match self.stdin.clone() {
+            Some(StdinInput::Preset(preset)) => resolver.add_stdin_preset(*preset),
+            Some(StdinInput::Config(config)) => resolver.add_stdin_config(*config),
+            None => {}
+        }
+
+        let mut resolved = resolver.resolve();
+        if self.no_detect {
+            resolved.window.auto_detect = false;
+        }
+
+        // 6. Scripts in .launch-bar/commands/ (after the configured commands)
+        if let Some(dir) = self.local_path.parent() {
+            resolved.add_dir_commands(&scan_commands_dir(&dir.join(COMMANDS_DIR)));
+        }
+
+        Ok(resolved)
+    }
+}
+
+/// Read and parse a config file
+pub fn read_config_file(path: &Path) -> Result<Config, String> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    toml::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Load an optional config file; errors are fatal only when `strict`
+fn load_source(path: &Path, strict: bool) -> Result<Option<Config>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    match read_config_file(path) {
+        Ok(config) => Ok(Some(config)),
+        Err(e) if strict => Err(e),
+        Err(_) => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_inline_cmd;
+
+    fn sources_in(dir: &Path) -> ConfigSources {
+        ConfigSources {
+            global_path: dir.join("global.toml"),
+            local_path: dir.join("launch-bar.toml"),
+            arg_preset: None,
+            arg_profile: None,
+            stdin: None,
+            inline: Vec::new(),
+            no_detect: false,
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "launch-bar-sources-{}-{}",
+            name,
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_reload_fails_on_invalid_file() {
+        let dir = temp_dir("invalid");
+        let sources = sources_in(&dir);
+        std::fs::write(&sources.local_path, "[[presets]\nname = ").unwrap();
+
+        assert!(sources.reload().is_err());
+        // Lenient resolution skips the broken file
+        assert!(sources.resolve().presets.is_empty());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_active_path_prefers_project_config() {
+        let dir = temp_dir("active");
+        let sources = sources_in(&dir);
+        assert_eq!(sources.active_path(), sources.global_path);
+
+        std::fs::write(&sources.local_path, "").unwrap();
+        assert_eq!(sources.active_path(), sources.local_path);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_inline_commands_skip_config_files() {
+        let dir = temp_dir("inline");
+        let mut sources = sources_in(&dir);
+        std::fs::write(
+            &sources.local_path,
+            "[[presets]]\nname = \"Rust\"\ncommands = [{ name = \"Run\", cmd = \"cargo run\" }]\n",
+        )
+        .unwrap();
+        sources.inline = vec![parse_inline_cmd("Build=cargo build:wrench").unwrap()];
+
+        let resolved = sources.resolve();
+        assert_eq!(resolved.presets.len(), 1);
+        let idx = resolved.detect_preset(&dir).unwrap();
+        assert_eq!(resolved.presets[idx].preset.name, "Inline");
+        assert_eq!(resolved.commands_for(idx)[0].name, "Build");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_commands_dir_appends_to_presets() {
+        let dir = temp_dir("commands-dir");
+        let sources = sources_in(&dir);
+        std::fs::write(
+            &sources.local_path,
+            "[[presets]]\nname = \"Rust\"\ncommands = [{ name = \"Lint\", cmd = \"cargo clippy\" }]\n",
+        )
+        .unwrap();
+        let commands_dir = dir.join(COMMANDS_DIR);
+        std::fs::create_dir_all(&commands_dir).unwrap();
+        std::fs::write(commands_dir.join("lint.rhai"), "shell(\"cargo clippy\");\n").unwrap();
+        std::fs::write(
+            commands_dir.join("deploy.lua"),
+            "-- icon: upload\nshell('./deploy.sh')\n",
+        )
+        .unwrap();
+
+        let resolved = sources.resolve();
+        let commands = resolved.commands_for(0);
+        let names: Vec<_> = commands.iter().map(|c| c.name.as_str()).collect();
+        // The configured "Lint" wins over lint.rhai
+        assert_eq!(names, vec!["Lint", "deploy"]);
+        assert_eq!(commands[1].icon.as_deref(), Some("upload"));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
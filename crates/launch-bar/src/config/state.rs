@@ -1,23 +1,50 @@
 //! Application state persistence
 
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use eframe::egui;
 use serde::{Deserialize, Serialize};
 
-/// Persistent application state (window positions per directory)
+use super::stats::CommandStats;
+
+/// Persistent application state (window positions and compact mode per directory)
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct AppState {
     positions: HashMap<String, [f32; 2]>,
+    #[serde(default)]
+    compact: HashSet<String>,
+    /// Name of the last command run per directory (`remember_last_command`)
+    #[serde(default)]
+    last_command: HashMap<String, String>,
+    /// When each directory was last opened (unix seconds)
+    #[serde(default)]
+    recent: HashMap<String, u64>,
+    /// Preset chosen with `--pick` per directory
+    #[serde(default)]
+    picked_preset: HashMap<String, String>,
+    /// Seed mixed into a directory's color variation (title-bar recolor button)
+    #[serde(default)]
+    hue_offsets: HashMap<String, u32>,
+    /// Run times per preset, then command name (shared by all directories)
+    #[serde(default)]
+    stats: BTreeMap<String, BTreeMap<String, CommandStats>>,
+    /// Runs recorded by this instance since its last save, per (preset, command)
+    #[serde(skip)]
+    pending_runs: BTreeMap<(String, String), Vec<Duration>>,
 }
 
 impl AppState {
     /// Load state from disk
     pub fn load() -> Self {
-        let state_path = Self::state_path();
+        Self::load_from(&Self::state_path())
+    }
+
+    /// Load state from a specific path (defaults on missing or invalid file)
+    pub fn load_from(state_path: &Path) -> Self {
         if state_path.exists() {
-            std::fs::read_to_string(&state_path)
+            std::fs::read_to_string(state_path)
                 .ok()
                 .and_then(|s| toml::from_str(&s).ok())
                 .unwrap_or_default()
@@ -26,14 +53,84 @@ impl AppState {
         }
     }
 
-    /// Save state to disk
-    pub fn save(&self) {
-        let state_path = Self::state_path();
+    /// Save this directory's entries to disk
+    pub fn save(&mut self, cwd: &str) -> std::io::Result<()> {
+        self.save_to(&Self::state_path(), cwd)
+    }
+
+    /// Save this directory's entries to a specific path
+    ///
+    /// Other bars share the file, so the on-disk state is re-read and only the
+    /// `cwd` entries are replaced, under an advisory lock on `<path>.lock`.
+    /// Runs recorded since the last save are added to the stats on disk, which
+    /// then replace this instance's stats.
+    pub fn save_to(&mut self, state_path: &Path, cwd: &str) -> std::io::Result<()> {
         if let Some(parent) = state_path.parent() {
-            std::fs::create_dir_all(parent).ok();
+            std::fs::create_dir_all(parent)?;
+        }
+        let lock = std::fs::File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(state_path.with_extension("toml.lock"))?;
+        lock.lock()?;
+
+        let mut merged = Self::load_from(state_path);
+        merged.merge_entry(self, cwd);
+        let content = toml::to_string_pretty(&merged)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        // Write-then-rename so concurrent readers never see a partial file
+        let tmp_path = state_path.with_extension("toml.tmp");
+        std::fs::write(&tmp_path, content)?;
+        std::fs::rename(&tmp_path, state_path)?;
+        self.stats = merged.stats;
+        self.pending_runs.clear();
+        Ok(())
+    }
+
+    /// Copy `other`'s entries for a working directory into this state
+    fn merge_entry(&mut self, other: &AppState, cwd: &str) {
+        match other.positions.get(cwd) {
+            Some(pos) => {
+                self.positions.insert(cwd.to_string(), *pos);
+            }
+            None => {
+                self.positions.remove(cwd);
+            }
         }
-        if let Ok(content) = toml::to_string_pretty(self) {
-            std::fs::write(&state_path, content).ok();
+        self.set_compact(cwd, other.is_compact(cwd));
+        if let Some(&opened) = other.recent.get(cwd) {
+            self.recent.insert(cwd.to_string(), opened);
+        }
+        match other.last_command.get(cwd) {
+            Some(name) => {
+                self.last_command.insert(cwd.to_string(), name.clone());
+            }
+            None => {
+                self.last_command.remove(cwd);
+            }
+        }
+        match other.picked_preset.get(cwd) {
+            Some(name) => {
+                self.picked_preset.insert(cwd.to_string(), name.clone());
+            }
+            None => {
+                self.picked_preset.remove(cwd);
+            }
+        }
+        self.set_hue_offset(cwd, other.get_hue_offset(cwd));
+        // Stats aren't per directory; add the runs `other` recorded since it saved
+        for ((preset, name), durations) in &other.pending_runs {
+            let stats = self
+                .stats
+                .entry(preset.clone())
+                .or_default()
+                .entry(name.clone())
+                .or_default();
+            for &duration in durations {
+                stats.record(duration);
+            }
         }
     }
 
@@ -54,4 +151,251 @@ impl AppState {
     pub fn set_position(&mut self, cwd: &str, pos: egui::Pos2) {
         self.positions.insert(cwd.to_string(), [pos.x, pos.y]);
     }
+
+    /// Check whether compact mode is enabled for a working directory
+    pub fn is_compact(&self, cwd: &str) -> bool {
+        self.compact.contains(cwd)
+    }
+
+    /// Record that a working directory was opened at `timestamp` (unix seconds)
+    pub fn touch_recent(&mut self, cwd: &str, timestamp: u64) {
+        self.recent.insert(cwd.to_string(), timestamp);
+    }
+
+    /// Recently opened directories, most recent first
+    pub fn recent_dirs(&self) -> Vec<&str> {
+        let mut dirs: Vec<(&str, u64)> = self
+            .recent
+            .iter()
+            .map(|(dir, &opened)| (dir.as_str(), opened))
+            .collect();
+        dirs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        dirs.into_iter().map(|(dir, _)| dir).collect()
+    }
+
+    /// Get the last command run in a working directory
+    pub fn get_last_command(&self, cwd: &str) -> Option<&str> {
+        self.last_command.get(cwd).map(String::as_str)
+    }
+
+    /// Record the last command run in a working directory
+    pub fn set_last_command(&mut self, cwd: &str, name: &str) {
+        self.last_command.insert(cwd.to_string(), name.to_string());
+    }
+
+    /// Preset picked for a working directory with `--pick`
+    pub fn get_picked_preset(&self, cwd: &str) -> Option<&str> {
+        self.picked_preset.get(cwd).map(String::as_str)
+    }
+
+    /// Remember the preset picked for a working directory
+    pub fn set_picked_preset(&mut self, cwd: &str, name: &str) {
+        self.picked_preset.insert(cwd.to_string(), name.to_string());
+    }
+
+    /// Forget the preset picked for a working directory; false if there was none
+    pub fn forget_picked_preset(&mut self, cwd: &str) -> bool {
+        self.picked_preset.remove(cwd).is_some()
+    }
+
+    /// Hue offset (color seed) for a working directory; 0 keeps the plain path color
+    pub fn get_hue_offset(&self, cwd: &str) -> u32 {
+        self.hue_offsets.get(cwd).copied().unwrap_or(0)
+    }
+
+    /// Store the hue offset for a working directory (0 resets it)
+    pub fn set_hue_offset(&mut self, cwd: &str, offset: u32) {
+        if offset == 0 {
+            self.hue_offsets.remove(cwd);
+        } else {
+            self.hue_offsets.insert(cwd.to_string(), offset);
+        }
+    }
+
+    /// Record a finished run of `name` in `preset`
+    pub fn record_run(&mut self, preset: &str, name: &str, duration: Duration) {
+        self.stats
+            .entry(preset.to_string())
+            .or_default()
+            .entry(name.to_string())
+            .or_default()
+            .record(duration);
+        self.pending_runs
+            .entry((preset.to_string(), name.to_string()))
+            .or_default()
+            .push(duration);
+    }
+
+    pub fn get_stats(&self, preset: &str, name: &str) -> Option<&CommandStats> {
+        self.stats.get(preset)?.get(name)
+    }
+
+    /// All stats by preset, then command name (sorted)
+    pub fn all_stats(&self) -> &BTreeMap<String, BTreeMap<String, CommandStats>> {
+        &self.stats
+    }
+
+    /// Enable or disable compact mode for a working directory
+    pub fn set_compact(&mut self, cwd: &str, compact: bool) {
+        if compact {
+            self.compact.insert(cwd.to_string());
+        } else {
+            self.compact.remove(cwd);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("launch-bar-state-{}-{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir = temp_dir("roundtrip");
+        let path = dir.join("state.toml");
+
+        let mut state = AppState::default();
+        state.set_position("/project", egui::pos2(10.0, 20.0));
+        state.set_compact("/project", true);
+        state.set_last_command("/project", "Test");
+        state.set_hue_offset("/project", 3);
+        state.set_picked_preset("/project", "Rust");
+        state.save_to(&path, "/project").unwrap();
+
+        let loaded = AppState::load_from(&path);
+        assert_eq!(
+            loaded.get_position("/project"),
+            Some(egui::pos2(10.0, 20.0))
+        );
+        assert!(loaded.is_compact("/project"));
+        assert_eq!(loaded.get_last_command("/project"), Some("Test"));
+        assert_eq!(loaded.get_hue_offset("/project"), 3);
+        assert_eq!(loaded.get_hue_offset("/other"), 0);
+        assert_eq!(loaded.get_picked_preset("/project"), Some("Rust"));
+        assert_eq!(loaded.get_picked_preset("/other"), None);
+
+        // A forgotten pick stays forgotten after saving
+        let mut loaded = loaded;
+        assert!(loaded.forget_picked_preset("/project"));
+        assert!(!loaded.forget_picked_preset("/project"));
+        loaded.save_to(&path, "/project").unwrap();
+        assert_eq!(
+            AppState::load_from(&path).get_picked_preset("/project"),
+            None
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_save_to_unwritable_path_fails() {
+        let dir = temp_dir("unwritable");
+        // A regular file where the parent directory should be
+        let blocker = dir.join("blocker");
+        std::fs::write(&blocker, "").unwrap();
+        let path = blocker.join("state.toml");
+
+        let mut state = AppState::default();
+        assert!(state.save_to(&path, "/project").is_err());
+
+        // Loading from the same path still yields a clean default
+        let loaded = AppState::load_from(&path);
+        assert_eq!(loaded.get_position("/project"), None);
+        assert!(!loaded.is_compact("/project"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_concurrent_instances_keep_each_others_entries() {
+        let dir = temp_dir("merge");
+        let path = dir.join("state.toml");
+
+        // Both bars start from the same (empty) state
+        let mut first = AppState::load_from(&path);
+        let mut second = AppState::load_from(&path);
+
+        first.set_position("/first", egui::pos2(1.0, 2.0));
+        first.save_to(&path, "/first").unwrap();
+
+        second.set_position("/second", egui::pos2(3.0, 4.0));
+        second.set_compact("/second", true);
+        second.save_to(&path, "/second").unwrap();
+
+        let loaded = AppState::load_from(&path);
+        assert_eq!(loaded.get_position("/first"), Some(egui::pos2(1.0, 2.0)));
+        assert_eq!(loaded.get_position("/second"), Some(egui::pos2(3.0, 4.0)));
+        assert!(loaded.is_compact("/second"));
+        assert!(!loaded.is_compact("/first"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_stats_merge_across_instances() {
+        let dir = temp_dir("stats");
+        let path = dir.join("state.toml");
+
+        let mut first = AppState::load_from(&path);
+        let mut second = AppState::load_from(&path);
+        first.record_run("Rust", "Test", Duration::from_secs(2));
+        first.save_to(&path, "/first").unwrap();
+        second.record_run("Node", "Build", Duration::from_secs(5));
+        second.save_to(&path, "/second").unwrap();
+
+        let loaded = AppState::load_from(&path);
+        assert_eq!(loaded.get_stats("Rust", "Test").map(|s| s.runs), Some(1));
+        assert_eq!(
+            loaded.get_stats("Node", "Build").map(|s| s.max_ms),
+            Some(5000)
+        );
+        assert_eq!(loaded.get_stats("Rust", "Build"), None);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_stats_of_one_command_add_up_across_instances() {
+        let dir = temp_dir("stats-same");
+        let path = dir.join("state.toml");
+
+        let mut first = AppState::load_from(&path);
+        let mut second = AppState::load_from(&path);
+        first.record_run("Rust", "Test", Duration::from_secs(2));
+        first.save_to(&path, "/first").unwrap();
+        second.record_run("Rust", "Test", Duration::from_secs(4));
+        second.save_to(&path, "/second").unwrap();
+        // Saving again doesn't count the same runs twice
+        first.save_to(&path, "/first").unwrap();
+
+        let loaded = AppState::load_from(&path);
+        let stats = loaded.get_stats("Rust", "Test").unwrap();
+        assert_eq!((stats.runs, stats.min_ms, stats.max_ms), (2, 2000, 4000));
+        assert_eq!(stats.average(), Duration::from_secs(3));
+        // Each instance sees the merged stats after saving
+        assert_eq!(second.get_stats("Rust", "Test").map(|s| s.runs), Some(2));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_recent_dirs_ordered_by_timestamp() {
+        let mut state = AppState::default();
+        state.touch_recent("/old", 100);
+        state.touch_recent("/newest", 300);
+        state.touch_recent("/middle", 200);
+        assert_eq!(state.recent_dirs(), vec!["/newest", "/middle", "/old"]);
+
+        // Re-opening moves a directory to the front
+        state.touch_recent("/old", 400);
+        assert_eq!(state.recent_dirs(), vec!["/old", "/newest", "/middle"]);
+    }
 }
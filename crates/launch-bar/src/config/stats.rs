@@ -0,0 +1,74 @@
+//! Run time statistics per command (`launch-bar stats`)
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Durations of a command's finished runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CommandStats {
+    pub runs: u64,
+    pub last_ms: u64,
+    pub min_ms: u64,
+    pub max_ms: u64,
+    /// Running average over all runs
+    pub avg_ms: f64,
+}
+
+impl CommandStats {
+    /// Add one finished run
+    pub fn record(&mut self, duration: Duration) {
+        let ms = duration.as_millis() as u64;
+        self.min_ms = if self.runs == 0 {
+            ms
+        } else {
+            self.min_ms.min(ms)
+        };
+        self.max_ms = self.max_ms.max(ms);
+        self.last_ms = ms;
+        self.runs += 1;
+        self.avg_ms += (ms as f64 - self.avg_ms) / self.runs as f64;
+    }
+
+    pub fn average(&self) -> Duration {
+        Duration::from_millis(self.avg_ms.round() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_updates_average_min_max() {
+        let mut stats = CommandStats::default();
+        for ms in [400, 100, 700, 200] {
+            stats.record(Duration::from_millis(ms));
+        }
+        assert_eq!(stats.runs, 4);
+        assert_eq!(stats.last_ms, 200);
+        assert_eq!(stats.min_ms, 100);
+        assert_eq!(stats.max_ms, 700);
+        assert_eq!(stats.average(), Duration::from_millis(350));
+
+        stats.record(Duration::from_millis(1600));
+        assert_eq!(stats.max_ms, 1600);
+        assert_eq!(stats.average(), Duration::from_millis(600));
+    }
+
+    #[test]
+    fn test_first_run_sets_min() {
+        let mut stats = CommandStats::default();
+        stats.record(Duration::from_secs(3));
+        assert_eq!(
+            stats,
+            CommandStats {
+                runs: 1,
+                last_ms: 3000,
+                min_ms: 3000,
+                max_ms: 3000,
+                avg_ms: 3000.0,
+            }
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! Ephemeral configuration read from stdin (`--stdin <format>`)
+
+use super::types::{Config, Preset};
+
+/// Input format for `--stdin`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StdinFormat {
+    Toml,
+    Json,
+}
+
+impl StdinFormat {
+    /// Parse a format name ("toml" or "json")
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "toml" => Some(Self::Toml),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Configuration read from stdin: either a single preset or a full config
+#[derive(Debug, Clone)]
+pub enum StdinInput {
+    Preset(Box<Preset>),
+    Config(Box<Config>),
+}
+
+/// Parse stdin content as a single `Preset`, falling back to a full `Config`
+pub fn parse_stdin_input(content: &str, format: StdinFormat) -> Result<StdinInput, String> {
+    match format {
+        StdinFormat::Toml => {
+            if let Ok(preset) = toml::from_str::<Preset>(content) {
+                return Ok(StdinInput::Preset(Box::new(preset)));
+            }
+            toml::from_str::<Config>(content)
+                .map(|config| StdinInput::Config(Box::new(config)))
+                .map_err(|e| e.to_string())
+        }
+        StdinFormat::Json => {
+            if let Ok(preset) = serde_json::from_str::<Preset>(content) {
+                return Ok(StdinInput::Preset(Box::new(preset)));
+            }
+            serde_json::from_str::<Config>(content)
+                .map(|config| StdinInput::Config(Box::new(config)))
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_toml_preset() {
+        let input = r#"
+name = "Scratch"
+commands = [{ name = "Hi", cmd = "echo hi" }]
+"#;
+        match parse_stdin_input(input, StdinFormat::Toml).unwrap() {
+            StdinInput::Preset(preset) => {
+                assert_eq!(preset.name, "Scratch");
+                assert_eq!(preset.commands.len(), 1);
+            }
+            StdinInput::Config(_) => panic!("expected preset"),
+        }
+    }
+
+    #[test]
+    fn test_parse_json_config() {
+        let input = r#"{"presets": [{"name": "A"}, {"name": "B"}]}"#;
+        match parse_stdin_input(input, StdinFormat::Json).unwrap() {
+            StdinInput::Config(config) => assert_eq!(config.presets.len(), 2),
+            StdinInput::Preset(_) => panic!("expected config"),
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_input() {
+        assert!(parse_stdin_input("{not json", StdinFormat::Json).is_err());
+    }
+}
@@ -1,14 +1,18 @@
 //! Configuration types for Launch Bar
 
-use serde::Deserialize;
+use std::collections::HashMap;
 
-use crate::script::ScriptType;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::platform::{which_with, Shell};
+use crate::script::{AiConfig, ScriptType};
 
 /// Reserved name for top-level commands converted to preset
 pub const GLOBAL_PRESET_NAME: &str = "[Global]";
 
 /// Main configuration structure
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct Config {
     #[serde(default)]
     pub window: WindowSettings,
@@ -16,6 +20,15 @@ pub struct Config {
     pub presets: Vec<Preset>,
     #[serde(default)]
     pub commands: Vec<CommandConfig>,
+    /// Named overrides selected with `--profile` or `LAUNCH_BAR_PROFILE`
+    #[serde(default)]
+    pub profiles: HashMap<String, ProfileOverride>,
+    /// Reusable commands that presets pull in with `use = ["id", ...]`
+    #[serde(default)]
+    pub library: Vec<LibraryCommand>,
+    /// Dot colors (hex) for command `category` names
+    #[serde(default)]
+    pub categories: HashMap<String, String>,
 }
 
 impl Config {
@@ -30,25 +43,104 @@ impl Config {
             name: GLOBAL_PRESET_NAME.to_string(),
             detect_file: None,
             cwd_pattern: None,
+            extends: None,
             base_color: self.window.background_color.clone(),
             default_script: self.window.default_script,
+            script_prelude: None,
+            uses: Vec::new(),
+            readonly: false,
             commands: self.commands.clone(),
         })
     }
 }
 
+/// Window and preset overrides applied when a profile is selected
+#[derive(Debug, Deserialize, Clone, Default, JsonSchema)]
+pub struct ProfileOverride {
+    #[serde(default)]
+    pub opacity: Option<f32>,
+    #[serde(default)]
+    pub blur_opacity: Option<f32>,
+    #[serde(default)]
+    pub hover_opacity: Option<f32>,
+    #[serde(default)]
+    pub background_color: Option<String>,
+    #[serde(default)]
+    pub border: Option<String>,
+    #[serde(default)]
+    pub title_bar: Option<String>,
+    #[serde(default)]
+    pub accent_line: Option<String>,
+    #[serde(default)]
+    pub max_icons: Option<usize>,
+    /// Only these presets (by name) are available while the profile is active
+    #[serde(default)]
+    pub presets: Option<Vec<String>>,
+}
+
+impl ProfileOverride {
+    /// Apply the window overrides
+    pub fn apply(&self, window: &mut WindowSettings) {
+        if let Some(opacity) = self.opacity {
+            window.opacity = opacity;
+        }
+        if self.blur_opacity.is_some() {
+            window.blur_opacity = self.blur_opacity;
+        }
+        if self.hover_opacity.is_some() {
+            window.hover_opacity = self.hover_opacity;
+        }
+        if self.background_color.is_some() {
+            window.background_color = self.background_color.clone();
+        }
+        if let Some(ref border) = self.border {
+            window.border = border.clone();
+        }
+        if let Some(ref title_bar) = self.title_bar {
+            window.title_bar = title_bar.clone();
+        }
+        if let Some(ref accent_line) = self.accent_line {
+            window.accent_line = accent_line.clone();
+        }
+        if let Some(max_icons) = self.max_icons {
+            window.max_icons = max_icons;
+        }
+    }
+
+    /// Whether a preset is allowed by the allowlist (all are if unset)
+    pub fn allows(&self, preset_name: &str) -> bool {
+        self.presets.as_ref().is_none_or(|names| {
+            names
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(preset_name))
+        })
+    }
+}
+
 /// Preset configuration for project-specific commands
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Default, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct Preset {
     pub name: String,
     #[serde(default)]
     pub detect_file: Option<String>,
     #[serde(default)]
     pub cwd_pattern: Option<String>,
+    /// Name of a preset whose commands this preset inherits
+    #[serde(default)]
+    pub extends: Option<String>,
     #[serde(default)]
     pub base_color: Option<String>,
     #[serde(default)]
     pub default_script: Option<ScriptType>,
+    /// Script code (or `@file`) run before every script while this preset is active
+    #[serde(default)]
+    pub script_prelude: Option<String>,
+    /// Ids of `[[library]]` commands placed before this preset's own commands
+    #[serde(default, rename = "use")]
+    pub uses: Vec<String>,
+    /// Display-only: buttons show badges and results but clicks and shortcuts run nothing
+    #[serde(default)]
+    pub readonly: bool,
     #[serde(default)]
     pub commands: Vec<CommandConfig>,
 }
@@ -60,12 +152,38 @@ impl Preset {
     }
 }
 
+/// Command in the `[[library]]`, referenced from presets by `id`
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
+pub struct LibraryCommand {
+    pub id: String,
+    #[serde(flatten)]
+    pub command: CommandConfig,
+}
+
 /// Command configuration
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
 pub struct CommandConfig {
     pub name: String,
+    /// Short name accepted by `launch-bar run`
+    #[serde(default)]
+    pub alias: Option<String>,
+    /// Longer text shown as the button tooltip (defaults to `name: cmd`)
+    #[serde(default)]
+    pub description: Option<String>,
     #[serde(default)]
     pub cmd: Option<String>,
+    /// Run instead of `cmd` on Shift+click
+    #[serde(default)]
+    pub cmd_shift: Option<String>,
+    /// Run instead of `cmd` on Alt+click
+    #[serde(default)]
+    pub cmd_alt: Option<String>,
+    /// Run instead of `cmd` on Ctrl+click
+    #[serde(default)]
+    pub cmd_ctrl: Option<String>,
+    /// Commands tried in order when `cmd`'s program isn't found
+    #[serde(default)]
+    pub fallback: Vec<String>,
     #[serde(default)]
     pub run: Option<String>,
     #[serde(default)]
@@ -74,15 +192,218 @@ pub struct CommandConfig {
     pub icon: Option<String>,
     #[serde(default)]
     pub cwd: Option<String>,
+    /// Shell command (or `@script`) that must succeed for the button to be clickable
+    #[serde(default)]
+    pub enabled_when: Option<String>,
+    /// Shell command (or `@script`) whose stdout count is drawn as a badge
+    #[serde(default)]
+    pub badge: Option<String>,
+    /// Re-run this command in the background every N seconds as a health check
+    #[serde(default)]
+    pub poll_secs: Option<u64>,
+    /// Shell command (or `@script`) run after the command succeeds
+    #[serde(default)]
+    pub on_success: Option<String>,
+    /// Shell command (or `@script`) run after the command fails
+    #[serde(default)]
+    pub on_failure: Option<String>,
+    /// Override `window.login_shell` for this command
+    #[serde(default)]
+    pub login_shell: Option<bool>,
+    /// Interpreter for this command (overrides `window.default_shell`)
+    #[serde(default)]
+    pub shell: Option<Shell>,
+    /// Don't report this command's progress or result in the status line
+    #[serde(default)]
+    pub quiet: bool,
+    /// Notification shown when the command finishes (`{{name}}`, `{{result}}`, `{{duration}}`)
+    #[serde(default)]
+    pub notify_on_done: Option<String>,
+    /// Commands sharing a lock name never run at the same time
+    #[serde(default)]
+    pub lock: Option<String>,
+    /// Run detached from the launcher so the process survives it exiting
+    #[serde(default)]
+    pub detach: bool,
+    /// Run in the preset's persistent shell session, keeping shell state
+    /// (variables, `cd`, activated venvs) between commands
+    #[serde(default)]
+    pub session: bool,
+    /// File that receives the command's stdout/stderr instead of the status
+    /// line (relative to cwd; `{{date}}` expands to the UTC date)
+    #[serde(default)]
+    pub log_to: Option<String>,
+    /// Append to `log_to` instead of truncating it on each run
+    #[serde(default)]
+    pub log_append: bool,
+    /// Keyboard shortcut that runs this command, e.g. `cmd+shift+d`
+    #[serde(default)]
+    pub hotkey: Option<String>,
+    /// Character typed to run this command (e.g. "3"); replaces its positional 1-9 key
+    #[serde(default)]
+    pub key: Option<char>,
+    /// Button fill color (hex, e.g. "#7F1D1D"); transparent by default
+    #[serde(default)]
+    pub background: Option<String>,
+    /// Category name; a dot in its `[categories]` color is drawn under the icon
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Pattern an output line must match for the command to count as a success
+    #[serde(default)]
+    pub success_pattern: Option<String>,
+    /// Pattern marking the command as failed if any output line matches (a
+    /// non-zero exit fails too unless `success_pattern` is set)
+    #[serde(default)]
+    pub failure_pattern: Option<String>,
+    /// Pattern whose group captures a percentage (e.g. "PROGRESS: (\\d+)"), drawn as a partial underline
+    #[serde(default)]
+    pub progress_pattern: Option<String>,
+    /// Pause the file watcher while this command runs (e.g. builds writing into the dir)
+    #[serde(default)]
+    pub pause_watcher: bool,
+    /// Run with admin rights: `sudo -A` with a GUI askpass on unix, the UAC prompt on Windows
+    #[serde(default)]
+    pub elevate: bool,
+    /// Preset to switch to after the command succeeds ("setup then work")
+    #[serde(default)]
+    pub then_preset: Option<String>,
+    /// Run a failed command again up to this many times
+    #[serde(default)]
+    pub retries: u32,
+    /// Seconds to wait before each retry (default 3)
+    #[serde(default)]
+    pub retry_delay_secs: Option<u64>,
+    /// Open another bar in this directory (relative to cwd), e.g. per-crate bars from a workspace bar
+    #[serde(default)]
+    pub spawn_bar: Option<String>,
+    /// Only show the command on these OSes (comma-separated: "macos", "linux", "windows")
+    #[serde(default)]
+    pub only_if_os: Option<String>,
+    /// Only show the command when this program is on `PATH`
+    #[serde(default)]
+    pub only_if_binary: Option<String>,
+    /// CPU priority as a niceness, -20 (highest) to 19 (lowest); `nice -n` on
+    /// unix, a priority class on Windows
+    #[serde(default)]
+    pub nice: Option<i32>,
+    /// Bring this application's window forward (macOS app name, window title
+    /// elsewhere); with no `cmd` or `run`, that's all the command does
+    #[serde(default)]
+    pub focus_app: Option<String>,
+    /// Ask for a value before each run (this is the input's label); it
+    /// replaces `$input` in `cmd`, quoted as one word
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// Stop a `session` command after this many seconds (restarts the session)
+    #[serde(default)]
+    pub session_timeout_secs: Option<u64>,
+    /// System-wide shortcut that runs this command while another app is
+    /// focused, e.g. `ctrl+alt+d` (needs the `global-hotkey` feature)
+    #[serde(default)]
+    pub global_hotkey: Option<String>,
+}
+
+impl CommandConfig {
+    /// `name: cmd` summary (long inline scripts are shown as `[script]`)
+    pub fn summary(&self) -> String {
+        let detail = self
+            .cmd
+            .as_deref()
+            .or(self
+                .run
+                .as_deref()
+                .map(|s| if s.len() > 30 { "[script]" } else { s }))
+            .unwrap_or("[no command]");
+        format!("{}: {}", self.name, detail)
+    }
+
+    /// Tooltip text: `description` if set, else the summary
+    pub fn hover_text(&self) -> String {
+        self.description.clone().unwrap_or_else(|| self.summary())
+    }
+
+    /// Shell command for a modifier variant, falling back to `cmd`
+    pub fn cmd_for(&self, variant: CmdVariant) -> Option<&String> {
+        let cmd = match variant {
+            CmdVariant::Plain => None,
+            CmdVariant::Shift => self.cmd_shift.as_ref(),
+            CmdVariant::Alt => self.cmd_alt.as_ref(),
+            CmdVariant::Ctrl => self.cmd_ctrl.as_ref(),
+        };
+        cmd.or(self.cmd.as_ref())
+    }
+
+    /// Shell commands to try in order: the variant's command, then each `fallback`
+    pub fn candidates(&self, variant: CmdVariant) -> Vec<String> {
+        self.cmd_for(variant)
+            .into_iter()
+            .chain(&self.fallback)
+            .cloned()
+            .collect()
+    }
+
+    /// Whether `only_if_os` and `only_if_binary` allow the command on `os`
+    /// (a `std::env::consts::OS` value)
+    pub fn is_applicable(&self, os: &str, has_binary: impl Fn(&str) -> bool) -> bool {
+        let os_matches = self.only_if_os.as_deref().is_none_or(|list| {
+            list.split(',')
+                .any(|name| name.trim().eq_ignore_ascii_case(os))
+        });
+        let binary_found = self
+            .only_if_binary
+            .as_deref()
+            .is_none_or(|program| has_binary(program.trim()));
+        os_matches && binary_found
+    }
+}
+
+/// Drop commands whose `only_if_os` / `only_if_binary` don't hold on this machine
+pub fn retain_applicable(commands: &mut Vec<CommandConfig>) {
+    commands.retain(|cmd| {
+        cmd.is_applicable(std::env::consts::OS, |program| {
+            which_with(program, &[]).is_some()
+        })
+    });
+}
+
+/// Command variant selected by the modifier held while clicking
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CmdVariant {
+    #[default]
+    Plain,
+    Shift,
+    Alt,
+    Ctrl,
+}
+
+impl CmdVariant {
+    /// Ctrl wins over Alt, which wins over Shift
+    pub fn from_modifiers(shift: bool, alt: bool, ctrl: bool) -> Self {
+        if ctrl {
+            Self::Ctrl
+        } else if alt {
+            Self::Alt
+        } else if shift {
+            Self::Shift
+        } else {
+            Self::Plain
+        }
+    }
 }
 
 /// Window settings
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct WindowSettings {
     #[serde(default = "default_max_icons")]
     pub max_icons: usize,
     #[serde(default = "default_opacity")]
     pub opacity: f32,
+    /// Opacity while the window is unfocused (None = same as `opacity`)
+    #[serde(default)]
+    pub blur_opacity: Option<f32>,
+    /// Opacity while the pointer is over the bar, eased in and out (None = no change)
+    #[serde(default)]
+    pub hover_opacity: Option<f32>,
     #[serde(default)]
     pub background_color: Option<String>,
     #[serde(default = "default_border")]
@@ -91,18 +412,163 @@ pub struct WindowSettings {
     pub title_bar: String,
     #[serde(default = "default_auto")]
     pub accent_line: String,
+    /// Shell command run (up to 5s) before the bar closes, e.g. to stop a dev server
+    #[serde(default)]
+    pub on_close: Option<String>,
+    /// Editor command for the config (e.g. "code --wait", "nvim"); else `$EDITOR`, then the OS default
+    #[serde(default)]
+    pub editor: Option<String>,
+    /// URL each command result is POSTed to as JSON (needs the `http` feature)
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Turn the accent line green/red after a command succeeds/fails, fading back
+    #[serde(default)]
+    pub accent_from_result: bool,
+    /// Collapse the status line into "3 running, 1 failed" while several commands run
+    #[serde(default)]
+    pub summary_status: bool,
+    /// Show hovered commands with `$clipboard` and environment variables expanded
+    #[serde(default)]
+    pub preview_expanded: bool,
     #[serde(default)]
     pub default_script: Option<ScriptType>,
+    #[serde(default = "default_badge_interval_secs")]
+    pub badge_interval_secs: u64,
+    /// Most output kept per command for output patterns; older output is
+    /// dropped behind a truncation marker (0 = no byte limit; at most 1000
+    /// lines are kept either way)
+    #[serde(default = "default_max_output_bytes")]
+    pub max_output_bytes: usize,
+    /// Run shell commands through the user's login shell (`$SHELL -lc`)
+    #[serde(default)]
+    pub login_shell: bool,
+    /// Interpreter for shell commands: sh, bash, zsh, fish, nu or cmd (None = `sh` / `cmd`)
+    #[serde(default)]
+    pub default_shell: Option<Shell>,
+    /// Directories (relative to the working directory) prepended to `PATH`
+    #[serde(default)]
+    pub path_prepend: Vec<String>,
+    /// Patterns replaced by `****` in the status line, tooltips, history and
+    /// diagnostics (values of `*_TOKEN` / `*_SECRET` / `*_KEY` variables are
+    /// always hidden); `log_to` files are not redacted
+    #[serde(default)]
+    pub redact_patterns: Vec<String>,
+    /// Show `[Global]` commands after the detected preset's commands
+    #[serde(default)]
+    pub merge_global: bool,
+    /// Pick the preset from project files and paths; when false (or `--no-detect`),
+    /// start in `[Global]` unless a preset is named explicitly
+    #[serde(default = "default_true")]
+    pub auto_detect: bool,
+    /// Drag the window from anywhere, including over the buttons
+    #[serde(default = "default_true")]
+    pub drag_anywhere: bool,
+    /// Startup placement: "saved", "corner", "center" or "cursor"
+    #[serde(default = "default_start_position")]
+    pub start_position: String,
+    /// Show the working directory's git branch in the title bar
+    #[serde(default)]
+    pub show_git_branch: bool,
+    /// Queue commands launched while this many are already running (None or 0 = unlimited)
+    #[serde(default)]
+    pub max_concurrent: Option<usize>,
+    /// Show the window on every macOS Space (ignored on other platforms)
+    #[serde(default)]
+    pub all_spaces: bool,
+    /// Don't take keyboard focus from other apps on launch (and on click on
+    /// Windows, or on X11 with `x11_dock`)
+    #[serde(default)]
+    pub no_activate: bool,
+    /// Create the window as an X11 dock, which window managers don't focus
+    /// or decorate (Linux/X11 only)
+    #[serde(default)]
+    pub x11_dock: bool,
+    /// Let clicks on empty areas pass through to the window below
+    #[serde(default)]
+    pub click_through: bool,
+    /// Remember the last command per directory so Enter repeats it after a restart
+    #[serde(default)]
+    pub remember_last_command: bool,
+    /// How often running commands are checked, in milliseconds
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    /// Frame rate cap for animations and polling
+    #[serde(default = "default_max_fps")]
+    pub max_fps: u32,
+    /// Color theme: "dark", "light" or "system" (follow the OS appearance)
+    #[serde(default = "default_theme")]
+    pub theme: String,
+    /// Higher-contrast colors and thicker accent lines
+    #[serde(default)]
+    pub high_contrast: bool,
+    /// Running/success/error colors replacing the theme's
+    #[serde(default)]
+    pub colors: ColorConfig,
+    /// Margin around the bar content, in points (0-127)
+    #[serde(default = "default_padding")]
+    pub padding: f32,
+    /// Gap between command buttons, in points
+    #[serde(default = "default_spacing")]
+    pub spacing: f32,
+    /// Largest file `read_file()` returns to scripts, in bytes (0 = no limit)
+    #[serde(default = "default_read_file_max_bytes")]
+    pub read_file_max_bytes: u64,
+    /// CLI invoked by `claude()` / `ai()` in scripts
+    #[serde(default)]
+    pub ai: AiConfig,
 }
 
 fn default_max_icons() -> usize {
     5
 }
 
+fn default_badge_interval_secs() -> u64 {
+    30
+}
+
+fn default_max_output_bytes() -> usize {
+    256 * 1024
+}
+
 fn default_opacity() -> f32 {
     0.8
 }
 
+/// Largest `window.padding` (egui margins are `i8`)
+const MAX_PADDING: f32 = i8::MAX as f32;
+
+fn default_padding() -> f32 {
+    12.0
+}
+
+fn default_spacing() -> f32 {
+    16.0
+}
+
+fn default_read_file_max_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_poll_interval_ms() -> u64 {
+    100
+}
+
+fn default_theme() -> String {
+    "dark".to_string()
+}
+
+fn default_max_fps() -> u32 {
+    30
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_start_position() -> String {
+    "saved".to_string()
+}
+
 fn default_border() -> String {
     "auto".to_string()
 }
@@ -115,16 +581,350 @@ fn default_auto() -> String {
     "auto".to_string()
 }
 
+impl WindowSettings {
+    /// Clamp out-of-range numbers and reset unknown enum strings, returning warnings
+    ///
+    /// Enum values are also trimmed and lowercased.
+    pub fn validate(&mut self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        clamp_unit("opacity", &mut self.opacity, 1.0, &mut warnings);
+        if let Some(ref mut blur_opacity) = self.blur_opacity {
+            clamp_unit("blur_opacity", blur_opacity, 1.0, &mut warnings);
+        }
+        if let Some(ref mut hover_opacity) = self.hover_opacity {
+            clamp_unit("hover_opacity", hover_opacity, 1.0, &mut warnings);
+        }
+        // egui margins are whole points stored in an i8
+        if !(0.0..=MAX_PADDING).contains(&self.padding) {
+            let clamped = if self.padding.is_nan() {
+                default_padding()
+            } else {
+                self.padding.clamp(0.0, MAX_PADDING)
+            };
+            warnings.push(format!(
+                "window.padding = {} is outside 0-{}; using {}",
+                self.padding, MAX_PADDING, clamped
+            ));
+            self.padding = clamped;
+        }
+
+        const SHOW_HIDE: &[&str] = &["auto", "show", "hide"];
+        normalize_choice("border", &mut self.border, SHOW_HIDE, &mut warnings);
+        normalize_choice("title_bar", &mut self.title_bar, SHOW_HIDE, &mut warnings);
+        normalize_choice(
+            "accent_line",
+            &mut self.accent_line,
+            SHOW_HIDE,
+            &mut warnings,
+        );
+        normalize_choice(
+            "theme",
+            &mut self.theme,
+            &["dark", "light", "system"],
+            &mut warnings,
+        );
+        warnings.extend(self.colors.validate());
+
+        warnings
+    }
+}
+
+/// Semantic color overrides (`[window.colors]`), as hex strings
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub struct ColorConfig {
+    /// Icon of a running command
+    #[serde(default)]
+    pub running: Option<String>,
+    /// Line under a command that succeeded
+    #[serde(default)]
+    pub success: Option<String>,
+    /// Line under a command that failed
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl ColorConfig {
+    /// Drop entries that aren't valid hex colors, returning warnings
+    pub fn validate(&mut self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for (name, value) in [
+            ("running", &mut self.running),
+            ("success", &mut self.success),
+            ("error", &mut self.error),
+        ] {
+            if let Some(hex) = value.as_deref() {
+                if !is_hex_color(hex) {
+                    warnings.push(format!(
+                        "window.colors.{} = \"{}\" is not a hex color like \"#RRGGBB\"; using the default",
+                        name, hex
+                    ));
+                    *value = None;
+                }
+            }
+        }
+        warnings
+    }
+}
+
+/// Whether `hex` is a color like "#RRGGBB" (the `#` is optional)
+fn is_hex_color(hex: &str) -> bool {
+    let digits = hex.trim_start_matches('#');
+    digits.len() == 6 && digits.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Clamp `value` to 0.0-1.0 (`fallback` for NaN)
+fn clamp_unit(name: &str, value: &mut f32, fallback: f32, warnings: &mut Vec<String>) {
+    let clamped = if value.is_nan() {
+        fallback
+    } else {
+        value.clamp(0.0, 1.0)
+    };
+    if clamped != *value || value.is_nan() {
+        warnings.push(format!(
+            "window.{} = {} is outside 0.0-1.0; using {}",
+            name, value, clamped
+        ));
+        *value = clamped;
+    }
+}
+
+/// Lowercase `value`, replacing it with the first choice if unknown
+fn normalize_choice(name: &str, value: &mut String, choices: &[&str], warnings: &mut Vec<String>) {
+    let normalized = value.trim().to_lowercase();
+    if choices.contains(&normalized.as_str()) {
+        *value = normalized;
+    } else {
+        warnings.push(format!(
+            "window.{} = \"{}\" is not one of {}; using \"{}\"",
+            name,
+            value,
+            choices.join(", "),
+            choices[0]
+        ));
+        *value = choices[0].to_string();
+    }
+}
+
 impl Default for WindowSettings {
     fn default() -> Self {
         Self {
             max_icons: default_max_icons(),
             opacity: default_opacity(),
+            blur_opacity: None,
+            hover_opacity: None,
             background_color: None,
             border: default_border(),
             title_bar: default_title_bar(),
             accent_line: default_auto(),
+            accent_from_result: false,
+            summary_status: false,
+            preview_expanded: false,
+            on_close: None,
+            editor: None,
+            webhook_url: None,
             default_script: None,
+            badge_interval_secs: default_badge_interval_secs(),
+            max_output_bytes: default_max_output_bytes(),
+            login_shell: false,
+            default_shell: None,
+            path_prepend: Vec::new(),
+            redact_patterns: Vec::new(),
+            merge_global: false,
+            auto_detect: true,
+            drag_anywhere: true,
+            start_position: default_start_position(),
+            show_git_branch: false,
+            max_concurrent: None,
+            all_spaces: false,
+            no_activate: false,
+            x11_dock: false,
+            click_through: false,
+            remember_last_command: false,
+            poll_interval_ms: default_poll_interval_ms(),
+            max_fps: default_max_fps(),
+            theme: default_theme(),
+            high_contrast: false,
+            colors: ColorConfig::default(),
+            padding: default_padding(),
+            spacing: default_spacing(),
+            read_file_max_bytes: default_read_file_max_bytes(),
+            ai: AiConfig::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(toml_str: &str) -> CommandConfig {
+        toml::from_str(toml_str).unwrap()
+    }
+
+    #[test]
+    fn test_only_if_os_and_binary() {
+        let cmd = command(
+            r#"
+name = "Brew"
+cmd = "brew update"
+only_if_os = "macos, linux"
+only_if_binary = "brew"
+"#,
+        );
+        let installed = |program: &str| program == "brew";
+        assert!(cmd.is_applicable("macos", installed));
+        assert!(cmd.is_applicable("linux", installed));
+        // OS mismatch
+        assert!(!cmd.is_applicable("windows", installed));
+        // Missing binary
+        assert!(!cmd.is_applicable("macos", |_| false));
+
+        // No conditions: always shown
+        let plain = command("name = \"Build\"\ncmd = \"make\"");
+        assert!(plain.is_applicable("windows", |_| false));
+
+        let mut commands = vec![plain, cmd.clone()];
+        commands[1].only_if_binary = Some("launch-bar-no-such-binary".to_string());
+        retain_applicable(&mut commands);
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].name, "Build");
+    }
+
+    #[test]
+    fn test_hover_text_prefers_description() {
+        let cmd = command(
+            r#"
+name = "Test"
+cmd = "cargo test"
+description = "Run the full test suite"
+"#,
+        );
+        assert_eq!(cmd.hover_text(), "Run the full test suite");
+        assert_eq!(cmd.summary(), "Test: cargo test");
+    }
+
+    #[test]
+    fn test_modifier_selects_variant() {
+        let cmd = command(
+            r#"
+name = "Test"
+cmd = "cargo test"
+cmd_shift = "cargo test --release"
+cmd_alt = "cargo test $clipboard"
+"#,
+        );
+        let pick = |shift, alt, ctrl| {
+            cmd.cmd_for(CmdVariant::from_modifiers(shift, alt, ctrl))
+                .map(String::as_str)
+        };
+        assert_eq!(pick(false, false, false), Some("cargo test"));
+        assert_eq!(pick(true, false, false), Some("cargo test --release"));
+        assert_eq!(pick(false, true, false), Some("cargo test $clipboard"));
+        assert_eq!(pick(true, true, false), Some("cargo test $clipboard"));
+        // Unset variant falls back to `cmd`
+        assert_eq!(pick(false, false, true), Some("cargo test"));
+        assert_eq!(
+            cmd.candidates(CmdVariant::Shift),
+            vec!["cargo test --release"]
+        );
+    }
+
+    #[test]
+    fn test_hover_text_falls_back_to_summary() {
+        let cmd = command("name = \"Test\"\ncmd = \"cargo test\"");
+        assert_eq!(cmd.hover_text(), "Test: cargo test");
+
+        let script = command(&format!("name = \"S\"\nrun = \"{}\"", "x".repeat(31)));
+        assert_eq!(script.hover_text(), "S: [script]");
+
+        assert_eq!(
+            command("name = \"Empty\"").hover_text(),
+            "Empty: [no command]"
+        );
+    }
+
+    #[test]
+    fn test_validate_clamps_opacity() {
+        let mut window = WindowSettings {
+            opacity: 5.0,
+            blur_opacity: Some(-0.5),
+            ..Default::default()
+        };
+        let warnings = window.validate();
+        assert_eq!(window.opacity, 1.0);
+        assert_eq!(window.blur_opacity, Some(0.0));
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("window.opacity = 5"));
+
+        let mut window = WindowSettings {
+            opacity: f32::NAN,
+            ..Default::default()
+        };
+        assert_eq!(window.validate().len(), 1);
+        assert_eq!(window.opacity, 1.0);
+    }
+
+    #[test]
+    fn test_validate_unknown_enums() {
+        let mut window = WindowSettings {
+            border: "maybe".to_string(),
+            title_bar: " Show ".to_string(),
+            theme: "solarized".to_string(),
+            ..Default::default()
+        };
+        let warnings = window.validate();
+        assert_eq!(window.border, "auto");
+        assert_eq!(window.title_bar, "show");
+        assert_eq!(window.theme, "dark");
+        assert_eq!(
+            warnings,
+            vec![
+                "window.border = \"maybe\" is not one of auto, show, hide; using \"auto\"",
+                "window.theme = \"solarized\" is not one of dark, light, system; using \"dark\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_clamps_padding() {
+        let mut window = WindowSettings {
+            padding: 300.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            window.validate(),
+            vec!["window.padding = 300 is outside 0-127; using 127"]
+        );
+        assert_eq!(window.padding, 127.0);
+
+        window.padding = -4.0;
+        assert_eq!(window.validate().len(), 1);
+        assert_eq!(window.padding, 0.0);
+    }
+
+    #[test]
+    fn test_validate_defaults_are_clean() {
+        assert!(WindowSettings::default().validate().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_colors_are_dropped() {
+        let mut colors = ColorConfig {
+            running: Some("#0072B2".to_string()),
+            success: Some("009E73".to_string()),
+            error: Some("orange".to_string()),
+        };
+        assert_eq!(
+            colors.validate(),
+            vec![
+                "window.colors.error = \"orange\" is not a hex color like \"#RRGGBB\"; using the default"
+            ]
+        );
+        assert_eq!(colors.running.as_deref(), Some("#0072B2"));
+        assert_eq!(colors.success.as_deref(), Some("009E73"));
+        assert_eq!(colors.error, None);
+
+        assert!(ColorConfig::default().validate().is_empty());
+    }
+}
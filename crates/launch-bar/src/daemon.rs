@@ -0,0 +1,298 @@
+//! Daemon mode: one long-lived bar that follows `launch-bar cd <path>`
+//!
+//! Clients send one message per line over a unix socket. The socket lives in
+//! a directory only the current user can open.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+#[cfg(unix)]
+use std::time::Duration;
+
+/// How often the accept loop checks whether it should stop
+#[cfg(unix)]
+const ACCEPT_POLL: Duration = Duration::from_millis(100);
+
+/// Longest a client may take to send its messages
+#[cfg(unix)]
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Message sent from a client to the daemon
+#[derive(Debug, Clone, PartialEq)]
+pub enum DaemonMessage {
+    /// Switch the bar to a directory
+    Cd(PathBuf),
+}
+
+impl DaemonMessage {
+    /// Parse a single protocol line (`cd <path>`)
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (verb, arg) = line.split_once(' ').unwrap_or((line, ""));
+        match verb {
+            "cd" if !arg.is_empty() => Ok(Self::Cd(PathBuf::from(arg))),
+            "cd" => Err("cd requires a path".to_string()),
+            "" => Err("empty message".to_string()),
+            other => Err(format!("unknown message '{}'", other)),
+        }
+    }
+
+    /// Encode as a protocol line, including the trailing newline
+    pub fn encode(&self) -> String {
+        match self {
+            Self::Cd(dir) => format!("cd {}\n", dir.display()),
+        }
+    }
+}
+
+/// Default socket location
+///
+/// `$XDG_RUNTIME_DIR/launch-bar/` when set, else a per-user directory in the
+/// temp dir (`launch-bar-<uid>/`).
+pub fn socket_path() -> PathBuf {
+    let dir = match dirs::runtime_dir() {
+        Some(runtime) => runtime.join("launch-bar"),
+        None => std::env::temp_dir().join(format!("launch-bar-{}", user_id())),
+    };
+    dir.join("launch-bar.sock")
+}
+
+#[cfg(unix)]
+fn user_id() -> u32 {
+    // SAFETY: getuid has no preconditions and cannot fail
+    unsafe { libc::getuid() }
+}
+
+#[cfg(not(unix))]
+fn user_id() -> String {
+    std::env::var("USERNAME").unwrap_or_default()
+}
+
+/// Create `dir` readable only by us, refusing one owned by another user
+#[cfg(unix)]
+fn ensure_private_dir(dir: &Path) -> io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+    match std::fs::DirBuilder::new().mode(0o700).create(dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(e),
+    }
+    let metadata = std::fs::symlink_metadata(dir)?;
+    if !metadata.is_dir() || metadata.uid() != user_id() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} is not a directory owned by this user", dir.display()),
+        ));
+    }
+    if metadata.permissions().mode() & 0o077 != 0 {
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+/// Bound daemon socket, ready to serve
+#[cfg(unix)]
+pub struct DaemonListener {
+    listener: std::os::unix::net::UnixListener,
+    path: PathBuf,
+}
+
+#[cfg(not(unix))]
+pub struct DaemonListener;
+
+/// Running accept loop; dropping it stops the loop and removes the socket
+pub struct DaemonServer {
+    stop: Arc<AtomicBool>,
+    path: PathBuf,
+}
+
+impl Drop for DaemonServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+impl DaemonListener {
+    /// Bind the socket, replacing a stale one left by a crashed daemon
+    ///
+    /// The socket's directory is created with mode 0700 first.
+    #[cfg(unix)]
+    pub fn bind(path: &Path) -> io::Result<Self> {
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        if let Some(dir) = path.parent() {
+            ensure_private_dir(dir)?;
+        }
+        let listener = match UnixListener::bind(path) {
+            Ok(listener) => listener,
+            Err(e) if e.kind() == io::ErrorKind::AddrInUse => {
+                if UnixStream::connect(path).is_ok() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AddrInUse,
+                        "a daemon is already running",
+                    ));
+                }
+                std::fs::remove_file(path)?;
+                UnixListener::bind(path)?
+            }
+            Err(e) => return Err(e),
+        };
+        // Polled so that dropping the DaemonServer ends the loop promptly
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            path: path.to_path_buf(),
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn bind(_path: &Path) -> io::Result<Self> {
+        Err(unsupported())
+    }
+
+    /// Accept clients on a background thread, calling `on_message` for each message
+    ///
+    /// The loop runs until the returned [`DaemonServer`] is dropped. A client
+    /// that stays silent is dropped after [`CLIENT_TIMEOUT`].
+    #[cfg(unix)]
+    pub fn serve(self, on_message: impl Fn(DaemonMessage) + Send + 'static) -> DaemonServer {
+        use std::io::BufRead;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let server = DaemonServer {
+            stop: stop.clone(),
+            path: self.path.clone(),
+        };
+        std::thread::spawn(move || {
+            while !stop.load(Ordering::SeqCst) {
+                let stream = match self.listener.accept() {
+                    Ok((stream, _)) => stream,
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                        std::thread::sleep(ACCEPT_POLL);
+                        continue;
+                    }
+                    Err(_) => continue,
+                };
+                if stream.set_nonblocking(false).is_err()
+                    || stream.set_read_timeout(Some(CLIENT_TIMEOUT)).is_err()
+                {
+                    continue;
+                }
+                for line in io::BufReader::new(stream).lines().map_while(Result::ok) {
+                    match DaemonMessage::parse(&line) {
+                        Ok(message) => on_message(message),
+                        Err(e) => eprintln!("[warn] Daemon: {}", e),
+                    }
+                }
+            }
+        });
+        server
+    }
+
+    #[cfg(not(unix))]
+    pub fn serve(self, _on_message: impl Fn(DaemonMessage) + Send + 'static) -> DaemonServer {
+        DaemonServer {
+            stop: Arc::new(AtomicBool::new(true)),
+            path: PathBuf::new(),
+        }
+    }
+}
+
+/// Send a message to a running daemon
+#[cfg(unix)]
+pub fn send(path: &Path, message: &DaemonMessage) -> io::Result<()> {
+    use std::io::Write;
+
+    let mut stream = std::os::unix::net::UnixStream::connect(path)?;
+    stream.write_all(message.encode().as_bytes())
+}
+
+#[cfg(not(unix))]
+pub fn send(_path: &Path, _message: &DaemonMessage) -> io::Result<()> {
+    Err(unsupported())
+}
+
+#[cfg(not(unix))]
+fn unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "daemon mode requires unix sockets",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cd() {
+        assert_eq!(
+            DaemonMessage::parse("cd /work/my project\n"),
+            Ok(DaemonMessage::Cd(PathBuf::from("/work/my project")))
+        );
+        assert_eq!(
+            DaemonMessage::parse("cd C:\\work\r\n"),
+            Ok(DaemonMessage::Cd(PathBuf::from("C:\\work")))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(DaemonMessage::parse("cd").is_err());
+        assert!(DaemonMessage::parse("cd ").is_err());
+        assert!(DaemonMessage::parse("").is_err());
+        assert!(DaemonMessage::parse("ls /tmp").is_err());
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let message = DaemonMessage::Cd(PathBuf::from("/tmp/a b"));
+        assert_eq!(message.encode(), "cd /tmp/a b\n");
+        assert_eq!(DaemonMessage::parse(&message.encode()), Ok(message));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_socket_delivers_messages() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("launch-bar-test-{}", std::process::id()));
+        let path = dir.join("launch-bar.sock");
+        let _ = std::fs::remove_dir_all(&dir);
+        let listener = DaemonListener::bind(&path).unwrap();
+        assert!(DaemonListener::bind(&path).is_err());
+        let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let server = listener.serve(move |message| {
+            let _ = tx.send(message);
+        });
+        send(&path, &DaemonMessage::Cd(PathBuf::from("/tmp"))).unwrap();
+
+        let received = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(received, DaemonMessage::Cd(PathBuf::from("/tmp")));
+
+        // Stopping removes the socket; the loop exits on its next poll
+        drop(server);
+        assert!(!path.exists());
+        assert!(send(&path, &DaemonMessage::Cd(PathBuf::from("/tmp"))).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_socket_path_is_per_user() {
+        let path = socket_path();
+        let dir = path
+            .parent()
+            .unwrap()
+            .file_name()
+            .unwrap()
+            .to_string_lossy();
+        assert!(dir == "launch-bar" || dir == format!("launch-bar-{}", user_id()));
+    }
+}
@@ -0,0 +1,343 @@
+//! Headless environment self-test (`launch-bar doctor`)
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::config::{read_config_file, ConfigSources, Preset, WindowSettings};
+use crate::platform::{resolve_path_entries, which_with, SHELL_BUILTINS};
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Pass => write!(f, "pass"),
+            Level::Warn => write!(f, "warn"),
+            Level::Fail => write!(f, "FAIL"),
+        }
+    }
+}
+
+/// A single line of the doctor report
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub level: Level,
+    pub message: String,
+}
+
+impl Check {
+    fn new(level: Level, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            message: message.into(),
+        }
+    }
+}
+
+/// Check that the config directory exists (or can be created) and is writable
+pub fn check_config_dir_writable(dir: &Path) -> Check {
+    let probe = dir.join(".launch-bar-doctor");
+    let result = std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&probe, b""))
+        .and_then(|_| std::fs::remove_file(&probe));
+    match result {
+        Ok(()) => Check::new(
+            Level::Pass,
+            format!("Config directory writable: {}", dir.display()),
+        ),
+        Err(e) => Check::new(
+            Level::Fail,
+            format!("Config directory not writable: {}: {}", dir.display(), e),
+        ),
+    }
+}
+
+/// Check that a config file parses (a missing file is fine)
+pub fn check_config_parses(path: &Path) -> Check {
+    if !path.exists() {
+        return Check::new(Level::Pass, format!("No config at {}", path.display()));
+    }
+    match read_config_file(path) {
+        Ok(_) => Check::new(Level::Pass, format!("Config parses: {}", path.display())),
+        Err(e) => Check::new(Level::Fail, e),
+    }
+}
+
+/// Check that a preset's detection rules can match anything
+pub fn check_preset_detection(preset: &Preset) -> Vec<Check> {
+    let mut checks = Vec::new();
+    if let Some(ref file) = preset.detect_file {
+        if file.trim().is_empty() {
+            checks.push(Check::new(
+                Level::Fail,
+                format!("Preset '{}': detect_file is empty", preset.name),
+            ));
+        } else if Path::new(file).is_absolute() {
+            checks.push(Check::new(
+                Level::Warn,
+                format!(
+                    "Preset '{}': detect_file '{}' is absolute and matches in every directory",
+                    preset.name, file
+                ),
+            ));
+        }
+    }
+    if let Some(ref pattern) = preset.cwd_pattern {
+        let expanded = shellexpand::tilde(pattern).to_string();
+        if expanded.trim_end_matches('*').contains('*') {
+            checks.push(Check::new(
+                Level::Warn,
+                format!(
+                    "Preset '{}': cwd_pattern '{}' uses '*' before the end (only a trailing '*' is supported)",
+                    preset.name, pattern
+                ),
+            ));
+        } else if !Path::new(&expanded).is_absolute() {
+            checks.push(Check::new(
+                Level::Warn,
+                format!(
+                    "Preset '{}': cwd_pattern '{}' is not absolute and never matches",
+                    preset.name, pattern
+                ),
+            ));
+        }
+    }
+    checks
+}
+
+/// Program a shell command line starts with, if it can be looked up on `PATH`
+fn command_program(cmd: &str) -> Option<&str> {
+    let program = cmd.split_whitespace().find(|word| !word.contains('='))?;
+    let skip = program.contains(['/', '\\', '$', '(', '{', '\'', '"'])
+        || SHELL_BUILTINS.contains(&program);
+    (!skip).then_some(program)
+}
+
+/// Check that the interpreters and binaries a preset's commands use are on `PATH`
+pub fn check_preset_binaries(
+    preset: &Preset,
+    window: &WindowSettings,
+    path_prepend: &[PathBuf],
+) -> Vec<Check> {
+    let mut checks = Vec::new();
+    for cmd in &preset.commands {
+        let Some(ref line) = cmd.cmd else {
+            continue;
+        };
+        if let Some(shell) = cmd.shell.or(window.default_shell) {
+            if which_with(shell.program(), path_prepend).is_none() {
+                checks.push(Check::new(
+                    Level::Fail,
+                    format!(
+                        "Preset '{}', command '{}': shell '{}' not found on PATH",
+                        preset.name,
+                        cmd.name,
+                        shell.program()
+                    ),
+                ));
+            }
+        }
+        if let Some(program) = command_program(line) {
+            if which_with(program, path_prepend).is_none() {
+                checks.push(Check::new(
+                    Level::Warn,
+                    format!(
+                        "Preset '{}', command '{}': '{}' not found on PATH",
+                        preset.name, cmd.name, program
+                    ),
+                ));
+            }
+        }
+    }
+    checks
+}
+
+/// Check that the AI CLI used by `claude()` / `ai()` is on `PATH`
+pub fn check_ai_command(window: &WindowSettings, path_prepend: &[PathBuf]) -> Check {
+    let command = &window.ai.command;
+    match which_with(command, path_prepend) {
+        Some(path) => Check::new(
+            Level::Pass,
+            format!("AI command '{}' found: {}", command, path.display()),
+        ),
+        None => Check::new(
+            Level::Warn,
+            format!(
+                "AI command '{}' not found (claude()/ai() will fail)",
+                command
+            ),
+        ),
+    }
+}
+
+/// Run every check against the given config sources
+pub fn run_checks(sources: &ConfigSources, working_dir: &Path) -> Vec<Check> {
+    let mut checks = Vec::new();
+    if let Some(dir) = sources.global_path.parent() {
+        checks.push(check_config_dir_writable(dir));
+    }
+    checks.push(check_config_parses(&sources.global_path));
+    checks.push(check_config_parses(&sources.local_path));
+
+    let resolved = sources.resolve();
+    let path_prepend = resolve_path_entries(&resolved.window.path_prepend, working_dir);
+    if resolved.presets.is_empty() {
+        checks.push(Check::new(Level::Warn, "No presets defined"));
+    }
+    for resolved_preset in &resolved.presets {
+        let preset = &resolved_preset.preset;
+        checks.extend(check_preset_detection(preset));
+        checks.extend(check_preset_binaries(
+            preset,
+            &resolved.window,
+            &path_prepend,
+        ));
+    }
+    checks.push(check_ai_command(&resolved.window, &path_prepend));
+    checks
+}
+
+/// Print the report; returns the process exit code (1 if anything failed)
+pub fn print_report(checks: &[Check]) -> i32 {
+    for check in checks {
+        println!("[{}] {}", check.level, check.message);
+    }
+    let count = |level| checks.iter().filter(|c| c.level == level).count();
+    let failed = count(Level::Fail);
+    println!();
+    println!(
+        "{} passed, {} warnings, {} failed",
+        count(Level::Pass),
+        count(Level::Warn),
+        failed
+    );
+    i32::from(failed > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("launch-bar-doctor-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn preset(detect_file: Option<&str>, cwd_pattern: Option<&str>) -> Preset {
+        let mut preset: Preset = toml::from_str("name = \"P\"").unwrap();
+        preset.detect_file = detect_file.map(String::from);
+        preset.cwd_pattern = cwd_pattern.map(String::from);
+        preset
+    }
+
+    #[test]
+    fn test_config_dir_writable() {
+        let dir = temp_dir("writable");
+        assert_eq!(check_config_dir_writable(&dir).level, Level::Pass);
+        assert!(!dir.join(".launch-bar-doctor").exists());
+
+        let file = dir.join("not-a-dir");
+        std::fs::write(&file, "").unwrap();
+        assert_eq!(check_config_dir_writable(&file).level, Level::Fail);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_config_parses() {
+        let dir = temp_dir("parses");
+        assert_eq!(
+            check_config_parses(&dir.join("missing.toml")).level,
+            Level::Pass
+        );
+
+        let good = dir.join("good.toml");
+        std::fs::write(&good, "[window]\nopacity = 0.5\n").unwrap();
+        assert_eq!(check_config_parses(&good).level, Level::Pass);
+
+        let bad = dir.join("bad.toml");
+        std::fs::write(&bad, "[window\n").unwrap();
+        assert_eq!(check_config_parses(&bad).level, Level::Fail);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_preset_detection() {
+        assert!(check_preset_detection(&preset(Some("Cargo.toml"), Some("~/work/*"))).is_empty());
+
+        let levels = |p: &Preset| {
+            check_preset_detection(p)
+                .iter()
+                .map(|c| c.level)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(levels(&preset(Some(" "), None)), vec![Level::Fail]);
+        assert_eq!(
+            levels(&preset(Some("/etc/passwd"), None)),
+            vec![Level::Warn]
+        );
+        assert_eq!(levels(&preset(None, Some("/src/*/app"))), vec![Level::Warn]);
+        assert_eq!(levels(&preset(None, Some("projects/*"))), vec![Level::Warn]);
+    }
+
+    #[test]
+    fn test_command_program() {
+        assert_eq!(command_program("cargo build --release"), Some("cargo"));
+        assert_eq!(command_program("RUST_LOG=debug cargo run"), Some("cargo"));
+        assert_eq!(command_program("cd sub && make"), None);
+        assert_eq!(command_program("./scripts/deploy.sh"), None);
+        assert_eq!(command_program("$EDITOR ."), None);
+        assert_eq!(command_program(""), None);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_preset_binaries() {
+        let dir = temp_dir("binaries");
+        std::fs::write(dir.join("mytool"), "").unwrap();
+        let preset: Preset = toml::from_str(
+            r#"
+name = "P"
+commands = [
+    { name = "Ok", cmd = "mytool --flag" },
+    { name = "Missing", cmd = "no-such-tool-xyz run" },
+    { name = "Script", run = "notify(\"hi\")" },
+]
+"#,
+        )
+        .unwrap();
+
+        let checks = check_preset_binaries(
+            &preset,
+            &WindowSettings::default(),
+            std::slice::from_ref(&dir),
+        );
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].level, Level::Warn);
+        assert!(checks[0].message.contains("no-such-tool-xyz"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_ai_command() {
+        let mut window = WindowSettings::default();
+        window.ai.command = "no-such-ai-cli-xyz".to_string();
+        assert_eq!(check_ai_command(&window, &[]).level, Level::Warn);
+    }
+
+    #[test]
+    fn test_print_report_exit_code() {
+        let pass = Check::new(Level::Pass, "ok");
+        let warn = Check::new(Level::Warn, "hmm");
+        let fail = Check::new(Level::Fail, "bad");
+        assert_eq!(print_report(&[pass.clone(), warn]), 0);
+        assert_eq!(print_report(&[pass, fail]), 1);
+    }
+}
@@ -7,32 +7,67 @@
 //! - Supports $clipboard variable in commands
 //!
 //! Usage:
-//!   launch-bar [--preset <name>]
+//!   launch-bar [--preset <name>] [--profile <name>] [--stdin <toml|json>] [--daemon] [--no-init]
+//!              [--no-detect] [--pick] [--forget-pick] [--cwd <path>]
+//!   launch-bar --cmd "name=command:icon" [--cmd ...]
+//!   launch-bar cd <path>
+//!   launch-bar run <command> [--preset <name>] [--profile <name>] [--no-init] [--no-detect]
+//!   launch-bar schema
+//!   launch-bar recent
+//!   launch-bar stats [--json]
+//!   launch-bar doctor
+//!   launch-bar --check-updates [URL]   (with the `http` feature)
 //!
 //! Environment:
 //!   LAUNCH_BAR_PRESET - Override preset selection
+//!   LAUNCH_BAR_PROFILE - Select a profile (--profile wins)
+//!   LAUNCH_BAR_NO_INIT - Same as --no-init when set (and not "0")
 
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
 use eframe::egui;
 
 mod app;
+mod command;
 mod config;
+mod daemon;
+mod doctor;
+mod picker;
 mod platform;
 mod script;
 mod ui;
+#[cfg(feature = "http")]
+mod update;
 
 use app::LaunchBarApp;
-use config::{Config, PresetResolver, ResolvedConfig};
-use platform::open_file_with_default_app;
-use script::ScriptConfig;
-use ui::{available_icons, parse_hex_color};
-
-/// Environment variable for preset override
-const ENV_PRESET: &str = "LAUNCH_BAR_PRESET";
+use command::{check_cwd, expand_command, expand_input, find_command, format_elapsed};
+use config::{
+    config_schema, parse_inline_cmd, parse_stdin_input, read_config_file, AppState, CommandStats,
+    ConfigDump, ConfigSources, PresetResolver, ResolvedConfig, StdinFormat, StdinInput, ENV_PRESET,
+};
+use daemon::{DaemonListener, DaemonMessage};
+use platform::{
+    focus_app, open_in_editor, prevent_activation, resolve_path_entries, run_shell_command,
+    show_on_all_spaces, spawn_bar, ShellOptions,
+};
+use script::{resolve_script_type, run_script, ScriptConfig, ScriptError};
+use ui::{available_icons, bar_size, preset_color, title_bar_buttons};
 
 fn main() -> eframe::Result<()> {
-    let working_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let args: Vec<String> = std::env::args().collect();
+    let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let working_dir = match resolve_working_dir(&args, current_dir) {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    std::env::set_current_dir(&working_dir).ok();
 
     // Config paths
     let global_config_path = dirs::config_dir()
@@ -42,14 +77,78 @@ fn main() -> eframe::Result<()> {
     let local_config_path = working_dir.join("launch-bar.toml");
 
     // Parse CLI arguments
-    let args: Vec<String> = std::env::args().collect();
     let mut arg_preset: Option<String> = None;
+    let mut arg_profile: Option<String> = None;
+    let mut stdin_input: Option<StdinInput> = None;
+    let mut daemon_mode = false;
+    let mut no_init = no_init_from_env(std::env::var(ENV_NO_INIT).ok().as_deref());
+    let mut no_detect = false;
+    let mut pick = false;
+    let mut forget_pick = false;
+    let mut inline_commands = Vec::new();
 
     // Handle 'config' subcommand
     if args.len() >= 2 && args[1] == "config" {
         handle_config_subcommand(&args, &global_config_path, &local_config_path);
     }
 
+    // Handle 'schema' subcommand
+    if args.len() >= 2 && args[1] == "schema" {
+        let schema = config_schema();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&schema).unwrap_or_default()
+        );
+        std::process::exit(0);
+    }
+
+    // Handle 'recent' subcommand
+    if args.len() >= 2 && args[1] == "recent" {
+        for dir in AppState::load().recent_dirs() {
+            println!("{}", dir);
+        }
+        std::process::exit(0);
+    }
+
+    // Handle 'stats' subcommand
+    if args.len() >= 2 && args[1] == "stats" {
+        let state = AppState::load();
+        if args.iter().any(|arg| arg == "--json") {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(state.all_stats()).unwrap_or_default()
+            );
+        } else {
+            print!("{}", format_stats_table(state.all_stats()));
+        }
+        std::process::exit(0);
+    }
+
+    // Handle 'doctor' subcommand
+    if args.len() >= 2 && args[1] == "doctor" {
+        let sources = ConfigSources {
+            global_path: global_config_path.clone(),
+            local_path: local_config_path.clone(),
+            arg_preset: None,
+            arg_profile: None,
+            stdin: None,
+            inline: Vec::new(),
+            no_detect: false,
+        };
+        let checks = doctor::run_checks(&sources, &working_dir);
+        std::process::exit(doctor::print_report(&checks));
+    }
+
+    // Handle 'cd' subcommand (client for --daemon)
+    if args.len() >= 2 && args[1] == "cd" {
+        handle_cd_subcommand(&args, &working_dir);
+    }
+
+    // Handle 'run' subcommand
+    if args.len() >= 2 && args[1] == "run" {
+        handle_run_subcommand(&args, &working_dir, &global_config_path, &local_config_path);
+    }
+
     let mut i = 1;
     while i < args.len() {
         match args[i].as_str() {
@@ -62,6 +161,68 @@ fn main() -> eframe::Result<()> {
                     std::process::exit(1);
                 }
             }
+            "--profile" => {
+                let Some(profile) = args.get(i + 1) else {
+                    eprintln!("Error: --profile requires a value");
+                    std::process::exit(1);
+                };
+                arg_profile = Some(profile.clone());
+                i += 2;
+            }
+            "--stdin" => {
+                let Some(format) = args.get(i + 1).and_then(|f| StdinFormat::parse(f)) else {
+                    eprintln!("Error: --stdin requires a format (toml or json)");
+                    std::process::exit(1);
+                };
+                stdin_input = Some(read_stdin_input(format));
+                i += 2;
+            }
+            "--daemon" => {
+                daemon_mode = true;
+                i += 1;
+            }
+            "--no-init" => {
+                no_init = true;
+                i += 1;
+            }
+            "--no-detect" => {
+                no_detect = true;
+                i += 1;
+            }
+            "--pick" => {
+                pick = true;
+                i += 1;
+            }
+            "--forget-pick" => {
+                forget_pick = true;
+                i += 1;
+            }
+            // Applied by `resolve_working_dir`
+            "--cwd" => i += 2,
+            "--cmd" => {
+                let Some(spec) = args.get(i + 1) else {
+                    eprintln!("Error: --cmd requires a value (name=command:icon)");
+                    std::process::exit(1);
+                };
+                match parse_inline_cmd(spec) {
+                    Ok(cmd) => inline_commands.push(cmd),
+                    Err(e) => {
+                        eprintln!("Error: --cmd {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                i += 2;
+            }
+            #[cfg(feature = "http")]
+            "--check-updates" => {
+                let url = args.get(i + 1).filter(|url| !url.starts_with('-'));
+                check_updates(url.map_or(update::DEFAULT_UPDATE_URL, |url| url.as_str()));
+            }
+            #[cfg(not(feature = "http"))]
+            "--check-updates" => {
+                eprintln!("Error: --check-updates is not available in this build (install with --features http)");
+                std::process::exit(1);
+            }
             "--init" => {
                 init_local_config(&local_config_path);
             }
@@ -75,27 +236,69 @@ fn main() -> eframe::Result<()> {
         }
     }
 
+    // Bind the daemon socket before opening a window so errors surface here
+    let daemon_listener = daemon_mode.then(|| {
+        let path = daemon::socket_path();
+        DaemonListener::bind(&path).unwrap_or_else(|e| {
+            eprintln!("Error: Cannot listen on {}: {}", path.display(), e);
+            std::process::exit(1);
+        })
+    });
+
     // Build resolved config using PresetResolver
-    let (resolved_config, config_path) =
-        resolve_config(&global_config_path, &local_config_path, arg_preset);
+    let mut sources = ConfigSources {
+        global_path: global_config_path,
+        local_path: local_config_path,
+        arg_preset,
+        arg_profile,
+        stdin: stdin_input,
+        inline: inline_commands,
+        no_detect,
+    };
+    // A preset chosen with --pick earlier is used until the next pick
+    let cwd_key = working_dir.to_string_lossy().to_string();
+    let mut state = AppState::load();
+    if forget_pick && state.forget_picked_preset(&cwd_key) {
+        if let Err(e) = state.save(&cwd_key) {
+            eprintln!("[warn] Cannot save state: {}", e);
+        }
+    }
+    if !pick {
+        let env_preset = std::env::var(ENV_PRESET).ok();
+        sources.arg_preset = sources.arg_preset.take().or_else(|| {
+            picker::remembered_preset(&state, &cwd_key, &sources, env_preset.as_deref())
+        });
+    }
+    let (mut resolved_config, config_path) = resolve_config(&sources, no_init);
+    if pick && sources.stdin.is_none() && sources.inline.is_empty() {
+        if let Some(name) = picker::pick_preset(&resolved_config, &working_dir) {
+            state.set_picked_preset(&cwd_key, &name);
+            if let Err(e) = state.save(&cwd_key) {
+                eprintln!("[warn] Cannot save state: {}", e);
+            }
+            resolved_config.select_preset(name.clone());
+            // Reloads keep the choice
+            sources.arg_preset = Some(name);
+        }
+    }
 
     // Detect or select initial preset
     let detected_preset_idx = resolved_config.detect_preset(&working_dir);
     let all_presets = resolved_config.presets();
 
-    let (commands, base_color, preset_name, preset_default_script) =
+    let (commands, base_color, preset_name, (preset_default_script, preset_prelude)) =
         if let Some(idx) = detected_preset_idx {
             let preset = &resolved_config.presets[idx].preset;
             let color = preset
                 .base_color
                 .as_ref()
-                .and_then(|c| parse_hex_color(c))
+                .and_then(|c| preset_color(c, &working_dir))
                 .unwrap_or(egui::Color32::from_rgb(26, 26, 30));
             (
-                preset.commands.clone(),
+                resolved_config.commands_for(idx),
                 color,
                 Some(preset.name.clone()),
-                preset.default_script,
+                (preset.default_script, preset.script_prelude.clone()),
             )
         } else if !all_presets.is_empty() {
             // Use first available preset (usually [Global])
@@ -103,22 +306,32 @@ fn main() -> eframe::Result<()> {
             let color = preset
                 .base_color
                 .as_ref()
-                .and_then(|c| parse_hex_color(c))
+                .and_then(|c| preset_color(c, &working_dir))
                 .unwrap_or(egui::Color32::from_rgb(26, 26, 30));
             (
-                preset.commands.clone(),
+                resolved_config.commands_for(0),
                 color,
                 Some(preset.name.clone()),
-                preset.default_script,
+                (preset.default_script, preset.script_prelude.clone()),
             )
         } else {
             eprintln!("No presets defined");
-            (vec![], egui::Color32::from_rgb(26, 26, 30), None, None)
+            (
+                vec![],
+                egui::Color32::from_rgb(26, 26, 30),
+                None,
+                (None, None),
+            )
         };
 
     let script_config = ScriptConfig {
         global_default: resolved_config.window.default_script,
         preset_default: preset_default_script,
+        ai: resolved_config.window.ai.clone(),
+        read_file_max_bytes: resolved_config.window.read_file_max_bytes,
+        prelude: preset_prelude,
+        detach: false,
+        watcher_paused: Arc::default(),
     };
 
     let commands: Vec<_> = commands
@@ -126,25 +339,54 @@ fn main() -> eframe::Result<()> {
         .take(resolved_config.window.max_icons)
         .collect();
 
-    let num_commands = commands.len().max(1);
-    let width = (num_commands as f32 * 56.0) + 48.0;
-    let height = 100.0;
+    let size = bar_size(
+        commands.len(),
+        title_bar_buttons(&resolved_config.window.title_bar, all_presets.len()),
+        resolved_config.window.padding,
+        resolved_config.window.spacing,
+    );
 
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_inner_size(size)
+        .with_decorations(false)
+        .with_transparent(true)
+        .with_drag_and_drop(true)
+        .with_always_on_top();
+    let no_activate = resolved_config.window.no_activate;
+    if no_activate {
+        viewport = viewport.with_active(false);
+    }
+    let x11_dock = resolved_config.window.x11_dock;
+    if x11_dock {
+        if cfg!(target_os = "linux") {
+            // X11 window managers don't focus docks
+            viewport = viewport.with_window_type(egui::X11WindowType::Dock);
+        } else {
+            eprintln!("[warn] x11_dock: only supported on Linux (X11)");
+        }
+    }
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([width, height])
-            .with_decorations(false)
-            .with_transparent(true)
-            .with_always_on_top(),
+        viewport,
         renderer: eframe::Renderer::Glow,
         ..Default::default()
     };
 
+    let all_spaces = resolved_config.window.all_spaces;
     eframe::run_native(
         "Launch Bar",
         options,
         Box::new(move |cc| {
-            Ok(Box::new(LaunchBarApp::new(
+            if all_spaces {
+                if let Err(e) = show_on_all_spaces(cc) {
+                    eprintln!("[warn] all_spaces: {}", e);
+                }
+            }
+            if no_activate {
+                if let Err(e) = prevent_activation(cc, x11_dock) {
+                    eprintln!("[warn] no_activate: {}", e);
+                }
+            }
+            let mut app = LaunchBarApp::new(
                 cc,
                 commands,
                 resolved_config.window,
@@ -155,47 +397,93 @@ fn main() -> eframe::Result<()> {
                 script_config,
                 all_presets,
                 detected_preset_idx,
-            )))
+                resolved_config.categories,
+                sources,
+            );
+            if let Some(listener) = daemon_listener {
+                app.serve_daemon(listener, &cc.egui_ctx);
+            }
+            Ok(Box::new(app))
         }),
     )
 }
 
-/// Resolve configuration from all sources using PresetResolver
-fn resolve_config(
-    global_config_path: &Path,
-    local_config_path: &Path,
-    arg_preset: Option<String>,
-) -> (ResolvedConfig, PathBuf) {
-    let mut resolver = PresetResolver::new();
+/// Environment variable that skips creating an example config (like `--no-init`)
+const ENV_NO_INIT: &str = "LAUNCH_BAR_NO_INIT";
 
-    // 1. Load global config (lowest priority)
-    if global_config_path.exists() {
-        if let Some(config) = load_config_file(global_config_path) {
-            resolver.add_global(config);
-        }
-    }
+/// Whether `LAUNCH_BAR_NO_INIT` is set to something other than empty or `0`
+fn no_init_from_env(value: Option<&str>) -> bool {
+    value.is_some_and(|value| !value.is_empty() && value != "0")
+}
 
-    // 2. Load project config (overrides global)
-    if local_config_path.exists() {
-        if let Some(config) = load_config_file(local_config_path) {
-            resolver.add_project(config);
+/// Table for `launch-bar stats`: one row per preset and command, durations formatted
+fn format_stats_table(stats: &BTreeMap<String, BTreeMap<String, CommandStats>>) -> String {
+    let ms = |ms: u64| format_elapsed(Duration::from_millis(ms));
+    let mut rows =
+        vec![["PRESET", "COMMAND", "RUNS", "AVG", "LAST", "MIN", "MAX"].map(String::from)];
+    for (preset, commands) in stats {
+        for (name, stats) in commands {
+            rows.push([
+                preset.clone(),
+                name.clone(),
+                stats.runs.to_string(),
+                format_elapsed(stats.average()),
+                ms(stats.last_ms),
+                ms(stats.min_ms),
+                ms(stats.max_ms),
+            ]);
         }
     }
-
-    // 3. CLI argument preset (overrides project)
-    if let Some(name) = arg_preset {
-        resolver.set_arg_preset(name);
+    if rows.len() == 1 {
+        return "No runs recorded yet\n".to_string();
     }
 
-    // 4. Environment variable (highest priority)
-    if let Ok(env_preset) = std::env::var(ENV_PRESET) {
-        if !env_preset.is_empty() {
-            resolver.set_env_preset(env_preset);
-        }
+    let widths: Vec<usize> = (0..7)
+        .map(|col| {
+            rows.iter()
+                .map(|row| row[col].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let mut table = String::new();
+    for row in &rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        table.push_str(cells.join("  ").trim_end());
+        table.push('\n');
     }
+    table
+}
 
-    // Resolve and determine active config path
-    let resolved = resolver.resolve();
+/// Working directory: `--cwd <path>` (relative to `current`), else `current`
+fn resolve_working_dir(args: &[String], current: PathBuf) -> Result<PathBuf, String> {
+    let Some(position) = args.iter().position(|arg| arg == "--cwd") else {
+        return Ok(current);
+    };
+    let Some(path) = args.get(position + 1) else {
+        return Err("--cwd requires a path".to_string());
+    };
+    let dir = current.join(shellexpand::tilde(path).as_ref());
+    check_cwd(&dir)?;
+    Ok(dir.canonicalize().unwrap_or(dir))
+}
+
+/// Resolve configuration from all sources using PresetResolver
+///
+/// Without any presets an example global config is written, unless `no_init`
+/// is set (`--no-init`), in which case the bar starts empty.
+fn resolve_config(sources: &ConfigSources, no_init: bool) -> (ResolvedConfig, PathBuf) {
+    let global_config_path = &sources.global_path;
+    let resolved = sources.resolve();
+
+    if resolved.presets.is_empty() && no_init {
+        eprintln!("No config found. Run 'launch-bar --init' or '--init-global' to create one.");
+        return (resolved, sources.active_path());
+    }
 
     // If no presets resolved, create example config
     if resolved.presets.is_empty() {
@@ -211,27 +499,29 @@ fn resolve_config(
 
         // Re-resolve with the new config
         let mut resolver = PresetResolver::new();
-        if let Some(config) = load_config_file(global_config_path) {
+        if let Ok(config) = read_config_file(global_config_path) {
             resolver.add_global(config);
         }
         return (resolver.resolve(), global_config_path.to_path_buf());
     }
 
-    // Determine which config path to show (prefer local if exists)
-    let config_path = if local_config_path.exists() {
-        local_config_path.to_path_buf()
-    } else {
-        global_config_path.to_path_buf()
-    };
-
-    (resolved, config_path)
+    (resolved, sources.active_path())
 }
 
-/// Load a config file, returning None on error
-fn load_config_file(path: &Path) -> Option<Config> {
-    std::fs::read_to_string(path)
-        .ok()
-        .and_then(|content| toml::from_str(&content).ok())
+/// Read and parse a preset or config from stdin, exiting on error
+fn read_stdin_input(format: StdinFormat) -> StdinInput {
+    let mut content = String::new();
+    if let Err(e) = std::io::stdin().read_to_string(&mut content) {
+        eprintln!("Error: failed to read stdin: {}", e);
+        std::process::exit(1);
+    }
+    match parse_stdin_input(&content, format) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("Error: invalid preset/config on stdin: {}", e);
+            std::process::exit(1);
+        }
+    }
 }
 
 /// Handle 'config' subcommand
@@ -265,8 +555,18 @@ fn handle_config_subcommand(args: &[String], global_config_path: &Path, local_co
                     std::process::exit(1);
                 }
             };
+            let sources = ConfigSources {
+                global_path: global_config_path.to_path_buf(),
+                local_path: local_config_path.to_path_buf(),
+                arg_preset: None,
+                arg_profile: None,
+                stdin: None,
+                inline: Vec::new(),
+                no_detect: false,
+            };
+            let editor = sources.resolve().window.editor;
             println!("Opening: {}", target_path.display());
-            if let Err(e) = open_file_with_default_app(&target_path) {
+            if let Err(e) = open_in_editor(&target_path, editor.as_deref()) {
                 eprintln!("Failed to open config: {}", e);
                 std::process::exit(1);
             }
@@ -290,23 +590,278 @@ fn handle_config_subcommand(args: &[String], global_config_path: &Path, local_co
             }
             std::process::exit(0);
         }
+        Some("validate") => {
+            let mut failed = false;
+            for path in [global_config_path, local_config_path] {
+                if !path.exists() {
+                    continue;
+                }
+                match read_config_file(path) {
+                    Ok(mut config) => {
+                        let warnings = config.window.validate();
+                        if warnings.is_empty() {
+                            println!("{}: ok", path.display());
+                        }
+                        for warning in warnings {
+                            println!("{}: warning: {}", path.display(), warning);
+                        }
+                    }
+                    Err(e) => {
+                        println!("error: {}", e);
+                        failed = true;
+                    }
+                }
+            }
+            std::process::exit(if failed { 1 } else { 0 });
+        }
+        Some("dump") => {
+            let sources = ConfigSources {
+                global_path: global_config_path.to_path_buf(),
+                local_path: local_config_path.to_path_buf(),
+                arg_preset: None,
+                arg_profile: None,
+                stdin: None,
+                inline: Vec::new(),
+                no_detect: false,
+            };
+            let resolved = sources.resolve();
+            let dump = ConfigDump::new(&resolved);
+            let output = if sub_args.contains(&"--json") {
+                dump.to_json()
+            } else {
+                dump.to_toml()
+            };
+            match output {
+                Ok(text) => print!("{}", text),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            std::process::exit(0);
+        }
         Some(cmd) => {
             eprintln!("Unknown config subcommand: {}", cmd);
-            eprintln!("Available: open, path");
+            eprintln!("Available: open, path, validate, dump");
             std::process::exit(1);
         }
         None => {
             println!("Usage: launch-bar config <COMMAND>");
             println!();
             println!("Commands:");
-            println!("  open [--global|-g] [--local|-l]  Open config in default editor");
+            println!("  open [--global|-g] [--local|-l]  Open config in window.editor, $EDITOR or the default app");
             println!("  path [--global|-g] [--local|-l]  Show config file path(s)");
+            println!("  validate                         Check config files and window settings");
+            println!("  dump [--json]                    Print the merged config with each preset's source");
             std::process::exit(0);
         }
     }
 }
 
+/// Handle 'run' subcommand: execute a command of the detected preset headlessly
+fn handle_run_subcommand(
+    args: &[String],
+    working_dir: &Path,
+    global_config_path: &Path,
+    local_config_path: &Path,
+) {
+    let mut name: Option<&str> = None;
+    let mut arg_preset: Option<String> = None;
+    let mut arg_profile: Option<String> = None;
+    let mut no_init = no_init_from_env(std::env::var(ENV_NO_INIT).ok().as_deref());
+    let mut no_detect = false;
+    let mut i = 2;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--preset" | "-p" => {
+                let Some(preset) = args.get(i + 1) else {
+                    eprintln!("Error: --preset requires a value");
+                    std::process::exit(1);
+                };
+                arg_preset = Some(preset.clone());
+                i += 2;
+            }
+            "--profile" => {
+                let Some(profile) = args.get(i + 1) else {
+                    eprintln!("Error: --profile requires a value");
+                    std::process::exit(1);
+                };
+                arg_profile = Some(profile.clone());
+                i += 2;
+            }
+            "--no-init" => {
+                no_init = true;
+                i += 1;
+            }
+            "--no-detect" => {
+                no_detect = true;
+                i += 1;
+            }
+            "--cwd" => i += 2,
+            other => {
+                name.get_or_insert(other);
+                i += 1;
+            }
+        }
+    }
+    let Some(name) = name else {
+        eprintln!(
+            "Usage: launch-bar run <COMMAND> [--preset <NAME>] [--profile <NAME>] [--no-init] [--no-detect]"
+        );
+        std::process::exit(1);
+    };
+
+    let sources = ConfigSources {
+        global_path: global_config_path.to_path_buf(),
+        local_path: local_config_path.to_path_buf(),
+        arg_preset,
+        arg_profile,
+        stdin: None,
+        inline: Vec::new(),
+        no_detect,
+    };
+    let (resolved_config, _) = resolve_config(&sources, no_init);
+    let preset_idx =
+        resolved_config
+            .detect_preset(working_dir)
+            .or(if resolved_config.presets.is_empty() {
+                None
+            } else {
+                Some(0)
+            });
+    let Some(preset) = preset_idx.map(|idx| &resolved_config.presets[idx].preset) else {
+        eprintln!("No presets defined");
+        std::process::exit(1);
+    };
+
+    let Some(idx) = find_command(&preset.commands, name) else {
+        eprintln!("Command '{}' not found in preset '{}'", name, preset.name);
+        std::process::exit(1);
+    };
+    let cmd_config = &preset.commands[idx];
+    let cwd = cmd_config
+        .cwd
+        .as_ref()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| working_dir.to_path_buf());
+    if let Err(e) = check_cwd(&cwd) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+
+    // Scripts exit 0, or a code for the kind of failure (ScriptError::exit_code)
+    if let Some(ref script) = cmd_config.run {
+        let script_config = ScriptConfig {
+            global_default: resolved_config.window.default_script,
+            preset_default: preset.default_script,
+            ai: resolved_config.window.ai.clone(),
+            read_file_max_bytes: resolved_config.window.read_file_max_bytes,
+            prelude: preset.script_prelude.clone(),
+            detach: cmd_config.detach,
+            watcher_paused: Arc::default(),
+        };
+        let script_type = resolve_script_type(cmd_config.script_type, script, &script_config);
+        let result = run_script(script, script_type, Arc::new(cwd), &script_config);
+        if result.success {
+            println!("{}", result.message);
+            std::process::exit(0);
+        }
+        eprintln!("{}", result.message);
+        std::process::exit(result.error.map_or(1, ScriptError::exit_code));
+    }
+
+    if let Some(ref app) = cmd_config.focus_app {
+        let focused = focus_app(app);
+        if cmd_config.cmd.is_none() {
+            std::process::exit(if focused { 0 } else { 1 });
+        }
+    }
+
+    if let Some(ref path) = cmd_config.spawn_bar {
+        match spawn_bar(&cwd, path) {
+            Ok(dir) => {
+                println!("Opened bar: {}", dir.display());
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Shell commands propagate their exit code
+    let Some(ref cmd) = cmd_config.cmd else {
+        eprintln!(
+            "Command '{}' has no command or script defined",
+            cmd_config.name
+        );
+        std::process::exit(1);
+    };
+    let cmd_str = match expand_command(cmd) {
+        Ok(cmd_str) => cmd_str,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    // Ask for $input on the terminal; end of input cancels
+    let cmd_str = match cmd_config.prompt {
+        Some(ref prompt) => {
+            eprint!("{}: ", prompt);
+            std::io::stderr().flush().ok();
+            let mut line = String::new();
+            if std::io::stdin().read_line(&mut line).unwrap_or(0) == 0 {
+                eprintln!("Cancelled");
+                std::process::exit(1);
+            }
+            expand_input(&cmd_str, Some(line.trim_end_matches(['\r', '\n'])))
+        }
+        None => cmd_str,
+    };
+    let options = ShellOptions {
+        login: cmd_config
+            .login_shell
+            .unwrap_or(resolved_config.window.login_shell),
+        path_prepend: resolve_path_entries(&resolved_config.window.path_prepend, working_dir),
+        shell: cmd_config.shell.or(resolved_config.window.default_shell),
+        elevate: cmd_config.elevate,
+        nice: cmd_config.nice,
+        ..Default::default()
+    };
+    match run_shell_command(&cmd_str, &cwd, &options) {
+        Ok(status) => std::process::exit(status.code().unwrap_or(1)),
+        Err(e) => {
+            eprintln!("Failed to run '{}': {}", cmd_config.name, e);
+            std::process::exit(1);
+        }
+    }
+}
+
 /// Initialize local config
+/// Send a directory to the running daemon (`launch-bar cd <path>`)
+fn handle_cd_subcommand(args: &[String], working_dir: &Path) -> ! {
+    let Some(target) = args.get(2) else {
+        eprintln!("Usage: launch-bar cd <path>");
+        std::process::exit(1);
+    };
+    let dir = match working_dir.join(target).canonicalize() {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("Error: {}: {}", target, e);
+            std::process::exit(1);
+        }
+    };
+    let path = daemon::socket_path();
+    match daemon::send(&path, &DaemonMessage::Cd(dir)) {
+        Ok(()) => std::process::exit(0),
+        Err(e) => {
+            eprintln!("Error: No daemon at {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
+
 fn init_local_config(local_config_path: &Path) {
     if local_config_path.exists() {
         eprintln!(
@@ -332,27 +887,64 @@ fn init_global_config(global_config_path: &Path) {
     std::process::exit(0);
 }
 
+/// Print whether a newer version is available at `url`, then exit
+#[cfg(feature = "http")]
+fn check_updates(url: &str) -> ! {
+    match update::check_updates(url) {
+        Ok(message) => {
+            println!("{}", message);
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
 /// Print help message
 fn print_help() {
     println!("Usage: launch-bar [OPTIONS] [COMMAND]");
     println!();
     println!("Commands:");
+    println!("  cd <PATH>            Switch a running --daemon bar to PATH");
     println!("  config               Manage configuration files");
+    println!("  doctor               Check the environment and config, exit 1 on failures");
+    println!("  recent               List recently used directories (newest first)");
+    println!("  stats [--json]       Show run times per command (runs, average, last, min, max)");
+    println!("  run <COMMAND>        Run a command (name, alias or 1-based index) without GUI");
+    println!("  schema               Print the config JSON Schema (for editor autocomplete)");
     println!();
     println!("Options:");
     println!("  -p, --preset <NAME>  Use specific preset");
+    println!("      --profile <NAME> Apply a [profiles.<NAME>] override");
+    println!("      --stdin <FORMAT> Read a preset or config from stdin (toml or json)");
+    println!("      --daemon         Follow directories sent by 'launch-bar cd'");
+    println!("      --no-init        Don't create an example config when none exists");
+    println!("      --no-detect      Start in [Global] instead of the auto-detected preset");
+    println!(
+        "      --pick           Choose the preset from a list (remembered for this directory)"
+    );
+    println!("      --forget-pick    Forget the preset remembered by --pick for this directory");
+    println!("      --cwd <PATH>     Start in PATH instead of the current directory");
+    println!("      --cmd <SPEC>     Ad-hoc command \"name=command:icon\" (repeatable; ignores config files)");
     println!("      --init           Create local config (./launch-bar.toml)");
+    #[cfg(feature = "http")]
+    println!("      --check-updates [URL] Compare with the latest version (crates.io by default)");
     println!("      --init-global    Create/reset global config");
     println!("  -h, --help           Show this help");
     println!();
     println!("Environment:");
-    println!("  LAUNCH_BAR_PRESET    Override preset selection (highest priority)");
+    println!("  LAUNCH_BAR_PRESET    Override preset selection");
+    println!("  LAUNCH_BAR_PROFILE   Select a profile (--profile wins)");
+    println!("  LAUNCH_BAR_NO_INIT   Same as --no-init when set (and not \"0\")");
     println!();
     println!("Priority order (later overrides earlier):");
     println!("  1. Global config (~/.config/launch-bar/config.toml)");
     println!("  2. Project config (./launch-bar.toml)");
     println!("  3. CLI argument (--preset)");
     println!("  4. Environment variable (LAUNCH_BAR_PRESET)");
+    println!("  5. Stdin (--stdin), always selected");
     println!();
     println!("Run 'launch-bar config' for config subcommand help");
     std::process::exit(0);
@@ -374,10 +966,56 @@ fn generate_example_config() -> String {
 [window]
 max_icons = 5              # Maximum icons to display
 opacity = 0.8              # Background opacity (0.0 - 1.0)
+# blur_opacity = 0.4       # Fade to this opacity while unfocused
+# hover_opacity = 1.0      # Ease to this opacity while the pointer is over the bar
 border = "auto"            # "auto", "show", "hide"
 title_bar = "auto"         # "auto" (hover), "show", "hide"
 accent_line = "auto"       # "auto" (highlight on change/hover), "show", "hide"
+# accent_from_result = true # Accent line turns green/red after a command, then fades back
+# summary_status = true     # While several commands run, show "3 running, 1 failed" (click for results)
+# preview_expanded = true  # Hover shows commands with $clipboard and env vars filled in
 # default_script = "rhai"  # Global default: "rhai" or "lua"
+badge_interval_secs = 30   # How often badge commands are refreshed
+# max_output_bytes = 262144  # Output kept per command for output patterns (tail, at most 1000 lines; 0 = no byte limit)
+# login_shell = true        # Run commands via $SHELL -lc (loads nvm/pyenv/asdf)
+# default_shell = "fish"    # sh, bash, zsh, fish, nu or cmd (default: sh / cmd)
+# path_prepend = ["node_modules/.bin", "bin"]  # Prepended to PATH (relative to cwd)
+# redact_patterns = ["ghp_\\w+"]  # Shown as **** in the status line and tooltips
+# merge_global = true       # Append [[commands]] after the detected preset's commands
+# auto_detect = false       # Always start in [Global] (or --preset); same as --no-detect
+# drag_anywhere = false     # Drag only from the title bar and empty space
+# start_position = "corner" # "saved" (default), "corner", "center" or "cursor"
+# show_git_branch = true    # Show the git branch next to the preset name
+# max_concurrent = 2        # Queue further launches until a running command finishes (0 = unlimited)
+# all_spaces = true         # Stay visible on every Space (macOS only)
+# no_activate = true        # Don't steal keyboard focus on launch (and click on Windows)
+# x11_dock = true           # Create the bar as an X11 dock, never focused (Linux/X11 only)
+# click_through = true      # Clicks on empty areas reach the window below
+# remember_last_command = true  # Enter re-runs the last command, even after restart
+# on_close = "docker compose stop"  # Run before the bar closes (killed after 5s)
+# editor = "code --wait"    # Open the config in this editor (default: $EDITOR, then the OS default)
+# webhook_url = "https://dash.example.com/hook"  # POST each final result (after retries) as JSON (needs the http feature)
+# poll_interval_ms = 100    # How often running commands are checked
+# max_fps = 30              # Frame rate cap for fades and polling
+# theme = "system"         # "dark" (default), "light" or "system" (follow the OS)
+# high_contrast = true      # Stronger text/status colors, thicker lines
+# colors = {{ running = "#0072B2", success = "#009E73", error = "#D55E00" }}  # Override status colors (hex)
+padding = 12               # Margin around the bar content (0-127)
+spacing = 16               # Gap between command buttons
+# read_file_max_bytes = 10485760  # read_file() refuses larger files (0 = no limit)
+
+# [window.ai]               # CLI used by claude(prompt) / ai(prompt)
+# command = "claude"
+# args = ["-p", "{{{{prompt}}}}"]  # {{{{prompt}}}} is replaced with the prompt
+# timeout_secs = 120        # Kill the process after this long (0 = no limit)
+
+# ============================================================================
+# Profiles - select with --profile <name> or LAUNCH_BAR_PROFILE
+# ============================================================================
+# [profiles.work]
+# opacity = 1.0
+# accent_line = "show"
+# presets = ["RustDev", "NodeDev"]   # Only these presets are available
 
 # ============================================================================
 # Scripting
@@ -390,8 +1028,9 @@ accent_line = "auto"       # "auto" (highlight on change/hover), "show", "hide"
 # 5. Fallback: rhai
 #
 # Available functions: clipboard(), clipboard_set(text), shell(cmd),
-#   shell_spawn(cmd), claude(prompt), notify(msg), open(path),
-#   env(name), read_file(path), write_file(path, content)
+#   clipboard_push(), clipboard_pop(), shell_spawn(cmd), claude(prompt),
+#   ai(prompt), notify(msg), open(path), env(name), read_file(path),
+#   read_file_lines(path, start, count), write_file(path, content)
 
 # ============================================================================
 # Presets - Auto-detected by file or path pattern
@@ -402,6 +1041,8 @@ name = "RustDev"
 detect_file = "Cargo.toml"
 base_color = "#FF7043"     # Deep Orange
 # default_script = "rhai"  # Preset default script type
+# script_prelude = "@scripts/helpers.rhai"  # Code run before every script of this preset
+# readonly = true          # Display only: show badges/results, never run on click
 commands = [
     {{ name = "Run", cmd = "cargo run", icon = "play" }},
     {{ name = "Test", cmd = "cargo test", icon = "check" }},
@@ -461,3 +1102,96 @@ icon = "folder"
         icons = icons_list
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources_in(dir: &Path) -> ConfigSources {
+        ConfigSources {
+            global_path: dir.join("global").join("config.toml"),
+            local_path: dir.join("launch-bar.toml"),
+            arg_preset: None,
+            arg_profile: None,
+            stdin: None,
+            inline: Vec::new(),
+            no_detect: false,
+        }
+    }
+
+    #[test]
+    fn test_no_init_skips_example_config() {
+        let dir = std::env::temp_dir().join(format!("launch-bar-no-init-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let sources = sources_in(&dir);
+
+        let (resolved, _) = resolve_config(&sources, true);
+        assert!(resolved.presets.is_empty());
+        assert!(!sources.global_path.exists());
+
+        // Without the flag the example config is written
+        let (resolved, path) = resolve_config(&sources, false);
+        assert!(!resolved.presets.is_empty());
+        assert_eq!(path, sources.global_path);
+        assert!(sources.global_path.exists());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_format_stats_table() {
+        assert_eq!(
+            format_stats_table(&BTreeMap::new()),
+            "No runs recorded yet\n"
+        );
+
+        let mut test = CommandStats::default();
+        test.record(Duration::from_secs(2));
+        test.record(Duration::from_secs(4));
+        let stats = BTreeMap::from([(
+            "Rust".to_string(),
+            BTreeMap::from([("Test".to_string(), test)]),
+        )]);
+        assert_eq!(
+            format_stats_table(&stats),
+            "PRESET  COMMAND  RUNS  AVG  LAST  MIN  MAX\n\
+             Rust    Test     2     3s   4s    2s   4s\n"
+        );
+    }
+
+    #[test]
+    fn test_resolve_working_dir() {
+        let current = std::env::temp_dir();
+        let args = |extra: &[&str]| {
+            let mut args = vec!["launch-bar".to_string()];
+            args.extend(extra.iter().map(|s| s.to_string()));
+            args
+        };
+        assert_eq!(
+            resolve_working_dir(&args(&["--preset", "Web"]), current.clone()),
+            Ok(current.clone())
+        );
+
+        let sub = current.join(format!("launch-bar-cwd-arg-{}", std::process::id()));
+        std::fs::create_dir_all(&sub).unwrap();
+        let name = sub.file_name().unwrap().to_str().unwrap();
+        assert_eq!(
+            resolve_working_dir(&args(&["--cwd", name]), current.clone()),
+            Ok(sub.canonicalize().unwrap())
+        );
+        assert!(
+            resolve_working_dir(&args(&["--cwd", "no-such-dir-xyz"]), current.clone()).is_err()
+        );
+        assert!(resolve_working_dir(&args(&["--cwd"]), current).is_err());
+        std::fs::remove_dir_all(&sub).ok();
+    }
+
+    #[test]
+    fn test_no_init_from_env() {
+        assert!(!no_init_from_env(None));
+        assert!(!no_init_from_env(Some("")));
+        assert!(!no_init_from_env(Some("0")));
+        assert!(no_init_from_env(Some("1")));
+        assert!(no_init_from_env(Some("yes")));
+    }
+}
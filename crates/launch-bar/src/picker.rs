@@ -0,0 +1,223 @@
+//! Preset choice on startup (`launch-bar --pick`)
+//!
+//! The list is shown on the terminal, or in a small window when stdin isn't
+//! one (e.g. started from a desktop launcher). The choice is remembered per
+//! directory and used on later starts until another pick or `--forget-pick`.
+
+use std::cell::Cell;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+use eframe::egui;
+
+use crate::config::{AppState, ConfigSources, PickCandidate, ResolvedConfig};
+
+/// Numbered candidate list; `default` (0-based) is marked with `*`
+pub fn format_candidates(candidates: &[PickCandidate], default: Option<usize>) -> String {
+    candidates
+        .iter()
+        .enumerate()
+        .map(|(i, candidate)| {
+            format!(
+                "{} {}. {} ({}{})\n",
+                if default == Some(i) { "*" } else { " " },
+                i + 1,
+                candidate.name,
+                candidate.source,
+                if candidate.detected { ", detected" } else { "" }
+            )
+        })
+        .collect()
+}
+
+/// Candidate chosen by `input`: its number, its name (case-insensitive), or
+/// `default` for an empty answer
+pub fn parse_choice(
+    input: &str,
+    candidates: &[PickCandidate],
+    default: Option<usize>,
+) -> Option<usize> {
+    let input = input.trim();
+    if input.is_empty() {
+        return default;
+    }
+    if let Ok(number) = input.parse::<usize>() {
+        return (1..=candidates.len()).contains(&number).then(|| number - 1);
+    }
+    candidates
+        .iter()
+        .position(|candidate| candidate.name.eq_ignore_ascii_case(input))
+}
+
+/// Ask which preset to start with: on the terminal, or in a window when
+/// stdin isn't a terminal
+///
+/// `None` when there is nothing to pick, input ends or the window is closed.
+pub fn pick_preset(resolved: &ResolvedConfig, working_dir: &Path) -> Option<String> {
+    let candidates = resolved.pick_candidates(working_dir);
+    if candidates.is_empty() {
+        return None;
+    }
+    // The preset that would be used without --pick
+    let default = resolved.detect_preset(working_dir).and_then(|idx| {
+        let name = &resolved.presets[idx].preset.name;
+        candidates.iter().position(|c| &c.name == name)
+    });
+    let choice = if std::io::stdin().is_terminal() {
+        pick_on_terminal(&candidates, default)
+    } else {
+        pick_in_window(&candidates, default)
+    };
+    choice.map(|i| candidates[i].name.clone())
+}
+
+/// Numbered list on stderr, answer read from stdin
+fn pick_on_terminal(candidates: &[PickCandidate], default: Option<usize>) -> Option<usize> {
+    eprint!("{}", format_candidates(candidates, default));
+
+    let mut lines = std::io::stdin().lock().lines();
+    loop {
+        eprint!("Preset [1-{}, Enter = *]: ", candidates.len());
+        std::io::stderr().flush().ok();
+        let line = lines.next()?.ok()?;
+        match parse_choice(&line, candidates, default) {
+            Some(i) => return Some(i),
+            None => eprintln!("No preset '{}'", line.trim()),
+        }
+    }
+}
+
+/// One button per candidate; Enter takes `default`, Escape cancels
+fn pick_in_window(candidates: &[PickCandidate], default: Option<usize>) -> Option<usize> {
+    let choice = Rc::new(Cell::new(None));
+    let labels: Vec<String> = format_candidates(candidates, default)
+        .lines()
+        .map(str::to_string)
+        .collect();
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_title("Launch Bar: pick a preset")
+            .with_inner_size([320.0, 40.0 + 26.0 * candidates.len().min(16) as f32])
+            .with_always_on_top(),
+        renderer: eframe::Renderer::Glow,
+        ..Default::default()
+    };
+    let picked = Rc::clone(&choice);
+    let result = eframe::run_simple_native("Launch Bar", options, move |ctx, _frame| {
+        let (enter, escape) = ctx.input(|i| {
+            (
+                i.key_pressed(egui::Key::Enter),
+                i.key_pressed(egui::Key::Escape),
+            )
+        });
+        if enter && default.is_some() {
+            picked.set(default);
+        }
+        egui::CentralPanel::default().show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (i, label) in labels.iter().enumerate() {
+                    if ui.selectable_label(false, label).clicked() {
+                        picked.set(Some(i));
+                    }
+                }
+            });
+        });
+        if escape || picked.get().is_some() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+    });
+    if let Err(e) = result {
+        eprintln!("[warn] --pick: cannot open the preset list: {}", e);
+    }
+    choice.get()
+}
+
+/// Preset picked earlier in `cwd`, when nothing else selects one
+///
+/// `--preset`, `LAUNCH_BAR_PRESET` (`env_preset`), stdin, `--cmd` and
+/// `--no-detect` all take precedence; `--forget-pick` clears it.
+pub fn remembered_preset(
+    state: &AppState,
+    cwd: &str,
+    sources: &ConfigSources,
+    env_preset: Option<&str>,
+) -> Option<String> {
+    let selected = sources.arg_preset.is_some()
+        || env_preset.is_some_and(|name| !name.is_empty())
+        || sources.stdin.is_some()
+        || !sources.inline.is_empty()
+        || sources.no_detect;
+    if selected {
+        return None;
+    }
+    state.get_picked_preset(cwd).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn candidates() -> Vec<PickCandidate> {
+        vec![
+            PickCandidate {
+                name: "[Global]".to_string(),
+                source: "global",
+                detected: false,
+            },
+            PickCandidate {
+                name: "Rust".to_string(),
+                source: "project",
+                detected: true,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_format_candidates() {
+        assert_eq!(
+            format_candidates(&candidates(), Some(1)),
+            "  1. [Global] (global)\n* 2. Rust (project, detected)\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_choice() {
+        let candidates = candidates();
+        assert_eq!(parse_choice("1", &candidates, Some(1)), Some(0));
+        assert_eq!(parse_choice(" rust\n", &candidates, None), Some(1));
+        assert_eq!(parse_choice("", &candidates, Some(1)), Some(1));
+        assert_eq!(parse_choice("", &candidates, None), None);
+        assert_eq!(parse_choice("3", &candidates, None), None);
+        assert_eq!(parse_choice("0", &candidates, None), None);
+        assert_eq!(parse_choice("Go", &candidates, None), None);
+    }
+
+    #[test]
+    fn test_remembered_preset() {
+        let mut state = AppState::default();
+        state.set_picked_preset("/project", "Rust");
+        let mut sources = ConfigSources {
+            global_path: PathBuf::from("global.toml"),
+            local_path: PathBuf::from("launch-bar.toml"),
+            arg_preset: None,
+            arg_profile: None,
+            stdin: None,
+            inline: Vec::new(),
+            no_detect: false,
+        };
+        assert_eq!(
+            remembered_preset(&state, "/project", &sources, None).as_deref(),
+            Some("Rust")
+        );
+        assert_eq!(remembered_preset(&state, "/other", &sources, None), None);
+        assert_eq!(
+            remembered_preset(&state, "/project", &sources, Some("Web")),
+            None
+        );
+        sources.arg_preset = Some("Web".to_string());
+        assert_eq!(remembered_preset(&state, "/project", &sources, None), None);
+    }
+}
@@ -1,23 +1,932 @@
 //! Platform-specific utilities
 
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use raw_window_handle::HasWindowHandle;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Command interpreter for shell commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Shell {
+    Sh,
+    Bash,
+    Zsh,
+    Fish,
+    Nu,
+    Cmd,
+}
+
+impl Shell {
+    /// Executable name looked up on `PATH`
+    pub fn program(self) -> &'static str {
+        match self {
+            Shell::Sh => "sh",
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+            Shell::Nu => "nu",
+            Shell::Cmd => "cmd",
+        }
+    }
+
+    /// Program and arguments that run `cmd` with this interpreter
+    fn argv(self, cmd: &str, login: bool) -> Vec<String> {
+        let program = self.program().to_string();
+        match self {
+            Shell::Cmd => vec![program, "/C".to_string(), cmd.to_string()],
+            _ if login => vec![program, "-l".to_string(), "-c".to_string(), cmd.to_string()],
+            _ => vec![program, "-c".to_string(), cmd.to_string()],
+        }
+    }
+}
+
+/// Options controlling how shell commands are spawned
+#[derive(Debug, Clone, Default)]
+pub struct ShellOptions {
+    /// Run through the user's login shell (`$SHELL -lc`) on unix
+    pub login: bool,
+    /// Directories placed ahead of the inherited `PATH`
+    pub path_prepend: Vec<PathBuf>,
+    /// Interpreter to use instead of the platform default (`sh` / `cmd`)
+    pub shell: Option<Shell>,
+    /// Start in a new process group with no output pipes, so the command
+    /// outlives the launcher
+    pub detach: bool,
+    /// Run with elevated privileges (`sudo -A` on unix, a UAC prompt on Windows)
+    pub elevate: bool,
+    /// CPU priority as a niceness (`nice`), clamped to -20..=19
+    pub nice: Option<i32>,
+}
+
+/// How a detached child is separated from the launcher
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DetachFlags {
+    /// New session (`setsid`): own process group and no controlling
+    /// terminal, away from the launcher's signals and terminal hangups
+    #[cfg(unix)]
+    NewSession,
+    /// `CreateProcess` flags: `CREATE_NEW_PROCESS_GROUP | DETACHED_PROCESS`
+    #[cfg(windows)]
+    CreationFlags(u32),
+}
+
+#[cfg(windows)]
+const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+#[cfg(windows)]
+const DETACHED_PROCESS: u32 = 0x0000_0008;
+
+/// Spawn settings for a detached child on this platform (None if unsupported)
+fn detach_flags() -> Option<DetachFlags> {
+    #[cfg(unix)]
+    {
+        Some(DetachFlags::NewSession)
+    }
+    #[cfg(windows)]
+    {
+        Some(DetachFlags::CreationFlags(
+            CREATE_NEW_PROCESS_GROUP | DETACHED_PROCESS,
+        ))
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        None
+    }
+}
+
+/// Make `command` survive the launcher exiting (own session, no inherited stdio)
+pub fn detach_command(command: &mut Command) -> &mut Command {
+    match detach_flags() {
+        #[cfg(unix)]
+        Some(DetachFlags::NewSession) => {
+            use std::os::unix::process::CommandExt;
+            // SAFETY: setsid is async-signal-safe, and only fails if the child
+            // already leads a process group, which a fresh fork never does
+            unsafe {
+                command.pre_exec(|| {
+                    if libc::setsid() == -1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+        #[cfg(windows)]
+        Some(DetachFlags::CreationFlags(flags)) => {
+            use std::os::windows::process::CommandExt;
+            command.creation_flags(flags);
+        }
+        None => {}
+    }
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+}
+
+/// Arguments for a bar opened in `path` (relative to `base`): `--cwd <dir>`
+pub fn spawn_bar_argv(base: &Path, path: &str) -> Vec<String> {
+    let dir = base.join(shellexpand::tilde(path).as_ref());
+    vec!["--cwd".to_string(), dir.display().to_string()]
+}
+
+/// Start another launch-bar in `path` (relative to `base`), detached so it outlives this one
+///
+/// Returns the directory the new bar opened in.
+pub fn spawn_bar(base: &Path, path: &str) -> Result<PathBuf, String> {
+    let argv = spawn_bar_argv(base, path);
+    let dir = PathBuf::from(&argv[1]);
+    if !dir.is_dir() {
+        return Err(format!("spawn_bar: not a directory: {}", dir.display()));
+    }
+    let exe = std::env::current_exe().map_err(|e| format!("spawn_bar: {}", e))?;
+    detach_command(Command::new(exe).args(&argv))
+        .spawn()
+        .map_err(|e| format!("spawn_bar: {}", e))?;
+    Ok(dir)
+}
+
+/// Shell builtins and keywords that never appear on `PATH`
+pub const SHELL_BUILTINS: &[&str] = &[
+    ".", ":", "[", "alias", "cd", "echo", "eval", "exec", "exit", "export", "false", "for", "if",
+    "printf", "pwd", "read", "set", "source", "test", "true", "type", "unset", "while",
+];
+
+/// Find an executable on `PATH`, searching `prepend` first
+pub fn which_with(program: &str, prepend: &[PathBuf]) -> Option<PathBuf> {
+    let inherited = std::env::var_os("PATH");
+    let path = compose_path(prepend, inherited.as_deref()).or(inherited);
+    which_in(program, path.as_deref())
+}
+
+/// Find an executable in the directories of a `PATH` value
+fn which_in(program: &str, path: Option<&OsStr>) -> Option<PathBuf> {
+    let mut names = vec![program.to_string()];
+    if cfg!(target_os = "windows") {
+        names.push(format!("{}.exe", program));
+    }
+    std::env::split_paths(path?)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Resolve `path_prepend` entries, relative ones against `base`
+pub fn resolve_path_entries(entries: &[String], base: &Path) -> Vec<PathBuf> {
+    entries.iter().map(|entry| base.join(entry)).collect()
+}
+
+/// Build a `PATH` value with `prepend` ahead of the inherited entries
+fn compose_path(prepend: &[PathBuf], inherited: Option<&OsStr>) -> Option<OsString> {
+    let inherited = inherited.into_iter().flat_map(std::env::split_paths);
+    match std::env::join_paths(prepend.iter().cloned().chain(inherited)) {
+        Ok(path) => Some(path),
+        Err(e) => {
+            eprintln!("[warn] Cannot apply path_prepend: {}", e);
+            None
+        }
+    }
+}
+
+/// Build the program and arguments used to run a shell command
+pub fn shell_argv(cmd: &str, options: &ShellOptions) -> Vec<String> {
+    shell_argv_with(cmd, options, std::env::var("SHELL").ok().as_deref())
+}
+
+/// Build shell argv given the value of `$SHELL`
+fn shell_argv_with(cmd: &str, options: &ShellOptions, user_shell: Option<&str>) -> Vec<String> {
+    if let Some(shell) = options.shell {
+        return shell.argv(cmd, options.login);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let _ = (options, user_shell);
+        vec!["cmd".to_string(), "/C".to_string(), cmd.to_string()]
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        match user_shell.filter(|s| options.login && !s.is_empty()) {
+            Some(shell) => vec![shell.to_string(), "-lc".to_string(), cmd.to_string()],
+            None => vec!["sh".to_string(), "-c".to_string(), cmd.to_string()],
+        }
+    }
+}
+
+/// Highest and lowest niceness; `nice` values are clamped to this range
+const NICE_RANGE: (i32, i32) = (-20, 19);
+
+fn clamp_nice(nice: i32) -> i32 {
+    nice.clamp(NICE_RANGE.0, NICE_RANGE.1)
+}
+
+/// Wrap shell argv in `nice -n <nice>`
+#[cfg(unix)]
+fn nice_argv(argv: Vec<String>, nice: i32) -> Vec<String> {
+    [
+        "nice".to_string(),
+        "-n".to_string(),
+        clamp_nice(nice).to_string(),
+    ]
+    .into_iter()
+    .chain(argv)
+    .collect()
+}
+
+#[cfg(windows)]
+const HIGH_PRIORITY_CLASS: u32 = 0x0000_0080;
+#[cfg(windows)]
+const ABOVE_NORMAL_PRIORITY_CLASS: u32 = 0x0000_8000;
+#[cfg(windows)]
+const NORMAL_PRIORITY_CLASS: u32 = 0x0000_0020;
+#[cfg(windows)]
+const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x0000_4000;
+#[cfg(windows)]
+const IDLE_PRIORITY_CLASS: u32 = 0x0000_0040;
+
+/// `CreateProcess` priority class closest to a niceness (never realtime)
+#[cfg(windows)]
+fn priority_class(nice: i32) -> u32 {
+    match clamp_nice(nice) {
+        -20..=-10 => HIGH_PRIORITY_CLASS,
+        -9..=-1 => ABOVE_NORMAL_PRIORITY_CLASS,
+        0 => NORMAL_PRIORITY_CLASS,
+        1..=9 => BELOW_NORMAL_PRIORITY_CLASS,
+        _ => IDLE_PRIORITY_CLASS,
+    }
+}
+
+/// Set the priority class for `options.nice`
+///
+/// Creation flags replace each other, so the detach flags are included again.
+#[cfg(windows)]
+fn apply_priority(command: &mut Command, options: &ShellOptions) {
+    use std::os::windows::process::CommandExt;
+    if let Some(nice) = options.nice {
+        let detach = if options.detach {
+            CREATE_NEW_PROCESS_GROUP | DETACHED_PROCESS
+        } else {
+            0
+        };
+        command.creation_flags(priority_class(nice) | detach);
+    }
+}
+
+/// Create a `Command` for a shell command line
+///
+/// Fails only when `options.elevate` is set and elevation isn't available.
+fn shell_command(cmd: &str, cwd: &Path, options: &ShellOptions) -> std::io::Result<Command> {
+    let mut argv = shell_argv(cmd, options);
+    let mut path = std::env::var_os("PATH");
+    let mut composed_path = None;
+    if !options.path_prepend.is_empty() {
+        if let Some(composed) = compose_path(&options.path_prepend, path.as_deref()) {
+            composed_path = Some(composed.clone());
+            path = Some(composed);
+        }
+    }
+    if let Some(shell) = options.shell {
+        if which_in(shell.program(), path.as_deref()).is_none() {
+            eprintln!("[warn] Shell '{}' not found on PATH", shell.program());
+        }
+    }
+    #[cfg(unix)]
+    if let Some(nice) = options.nice {
+        argv = nice_argv(argv, nice);
+    }
+    let mut askpass = None;
+    if options.elevate {
+        (argv, askpass) = elevate(argv, path.as_deref(), composed_path.as_deref())?;
+    }
+    let mut command = Command::new(&argv[0]);
+    command.args(&argv[1..]).current_dir(cwd);
+    if let Some(composed) = composed_path {
+        command.env("PATH", composed);
+    }
+    if let Some(askpass) = askpass {
+        command.env("SUDO_ASKPASS", askpass);
+    }
+    #[cfg(windows)]
+    apply_priority(&mut command, options);
+    Ok(command)
+}
+
+/// Askpass helpers tried for `sudo -A` when `$SUDO_ASKPASS` is unset
+#[cfg(unix)]
+const ASKPASS_PROGRAMS: &[&str] = &[
+    "ssh-askpass",
+    "ksshaskpass",
+    "lxqt-openssh-askpass",
+    "x11-ssh-askpass",
+];
+
+/// GUI password helper for `sudo -A`: `$SUDO_ASKPASS`, else a known one on `path`
+#[cfg(unix)]
+fn find_askpass(env_askpass: Option<OsString>, path: Option<&OsStr>) -> Option<PathBuf> {
+    env_askpass
+        .filter(|askpass| !askpass.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            ASKPASS_PROGRAMS
+                .iter()
+                .find_map(|program| which_in(program, path))
+        })
+}
+
+/// Wrap shell argv in `sudo -A`, which asks for the password through the askpass helper
+///
+/// sudo resets `PATH` (`secure_path`), so a composed `path` is passed on via `env`.
+#[cfg(unix)]
+fn sudo_argv(argv: Vec<String>, path: Option<&OsStr>) -> Vec<String> {
+    let env = path.map(|path| {
+        vec![
+            "env".to_string(),
+            format!("PATH={}", path.to_string_lossy()),
+        ]
+    });
+    ["sudo", "-A", "--"]
+        .into_iter()
+        .map(str::to_string)
+        .chain(env.into_iter().flatten())
+        .chain(argv)
+        .collect()
+}
+
+/// Wrap shell argv in PowerShell `Start-Process -Verb RunAs`, which shows the UAC prompt
+///
+/// The elevated process gets its own console, so its output isn't captured;
+/// its exit code is passed through.
+#[cfg(windows)]
+fn runas_argv(argv: &[String]) -> Vec<String> {
+    let ps_quote = |s: &str| format!("'{}'", s.replace('\'', "''"));
+    // One command line, so PowerShell doesn't re-join the arguments unquoted
+    let args: Vec<String> = argv[1..].iter().map(|arg| windows_quote_arg(arg)).collect();
+    let script = format!(
+        "$p = Start-Process -FilePath {} -ArgumentList {} -Verb RunAs -Wait -PassThru; exit $p.ExitCode",
+        ps_quote(&argv[0]),
+        ps_quote(&args.join(" "))
+    );
+    vec![
+        "powershell".to_string(),
+        "-NoProfile".to_string(),
+        "-Command".to_string(),
+        script,
+    ]
+}
+
+/// Quote one argument for a Windows command line (the `CommandLineToArgvW` rules)
+///
+/// Backslashes are literal except before a `"`, where they are doubled.
+pub fn windows_quote_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || c == '"') {
+        return arg.to_string();
+    }
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                backslashes = 0;
+            }
+            _ => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                backslashes = 0;
+            }
+        }
+        if c != '\\' {
+            quoted.push(c);
+        }
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// Elevated argv plus the askpass helper to export (`elevate = true`)
+///
+/// `composed_path` is the `PATH` with `path_prepend`, kept across sudo.
+#[cfg(unix)]
+fn elevate(
+    argv: Vec<String>,
+    path: Option<&OsStr>,
+    composed_path: Option<&OsStr>,
+) -> std::io::Result<(Vec<String>, Option<PathBuf>)> {
+    if which_in("sudo", path).is_none() {
+        return Err(std::io::Error::other("elevate: sudo not found"));
+    }
+    let askpass = find_askpass(std::env::var_os("SUDO_ASKPASS"), path).ok_or_else(|| {
+        std::io::Error::other(
+            "elevate: no askpass helper (set SUDO_ASKPASS or install ssh-askpass)",
+        )
+    })?;
+    Ok((sudo_argv(argv, composed_path), Some(askpass)))
+}
+
+/// Elevated argv plus the askpass helper to export (`elevate = true`)
+#[cfg(windows)]
+fn elevate(
+    argv: Vec<String>,
+    _path: Option<&OsStr>,
+    _composed_path: Option<&OsStr>,
+) -> std::io::Result<(Vec<String>, Option<PathBuf>)> {
+    Ok((runas_argv(&argv), None))
+}
 
 /// Execute a shell command on the current platform
-pub fn spawn_shell_command(cmd: &str, cwd: &PathBuf) -> std::io::Result<std::process::Child> {
+///
+/// stdout/stderr are piped, unless `options.detach` is set.
+pub fn spawn_shell_command(
+    cmd: &str,
+    cwd: &Path,
+    options: &ShellOptions,
+) -> std::io::Result<std::process::Child> {
+    let mut command = shell_command(cmd, cwd, options)?;
+    if options.detach {
+        detach_command(&mut command);
+        #[cfg(windows)]
+        apply_priority(&mut command, options);
+    } else {
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    }
+    command.spawn()
+}
+
+/// Execute a shell command with stdout/stderr written to `log`
+pub fn spawn_shell_command_logged(
+    cmd: &str,
+    cwd: &Path,
+    options: &ShellOptions,
+    log: File,
+) -> std::io::Result<std::process::Child> {
+    shell_command(cmd, cwd, options)?
+        .stdout(Stdio::from(log.try_clone()?))
+        .stderr(Stdio::from(log))
+        .spawn()
+}
+
+/// Execute a shell command with no stdin/stdout/stderr
+pub fn spawn_shell_command_silent(
+    cmd: &str,
+    cwd: &Path,
+    options: &ShellOptions,
+) -> std::io::Result<std::process::Child> {
+    shell_command(cmd, cwd, options)?
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+}
+
+/// Marker printed after each session command, followed by its exit code
+const SESSION_SENTINEL: &str = "__launch_bar_session_done__";
+
+/// How often a waiting session command checks for cancellation
+const SESSION_POLL: Duration = Duration::from_millis(100);
+
+/// When a running session command is given up on
+///
+/// Giving up kills the session shell; the next command starts a new one.
+#[derive(Debug, Clone, Default)]
+pub struct SessionLimits {
+    /// Longest the command may run (`session_timeout_secs`)
+    pub timeout: Option<Duration>,
+    /// Set to stop waiting, e.g. when the preset changes or the bar closes
+    pub cancel: Arc<AtomicBool>,
+}
+
+/// Long-lived shell that `session` commands are written to
+///
+/// Shell state (variables, `cd`, an activated venv) carries over between
+/// commands. Each command is passed to `eval` and followed by a sentinel line
+/// carrying its exit code, so a syntax error can't swallow the sentinel;
+/// output is read up to that line. stdin of the command itself is
+/// `/dev/null`, so it can't swallow the next command.
+pub struct ShellSession {
+    child: Child,
+    stdin: ChildStdin,
+    /// Output lines, read on a background thread; disconnected once the shell exits
+    lines: Receiver<String>,
+}
+
+impl ShellSession {
+    /// Start a session shell in `cwd` (POSIX shells only; fish/nu/cmd fall back to `sh`)
+    ///
+    /// With `options.login` the shell starts as a login shell (`-l`).
+    pub fn spawn(cwd: &Path, options: &ShellOptions) -> std::io::Result<Self> {
+        if cfg!(windows) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "shell sessions are not supported on Windows",
+            ));
+        }
+        let program = match options.shell {
+            Some(shell @ (Shell::Bash | Shell::Zsh)) => shell.program(),
+            _ => "sh",
+        };
+        let mut command = Command::new(program);
+        if options.login {
+            command.arg("-l");
+        }
+        command.current_dir(cwd);
+        if !options.path_prepend.is_empty() {
+            let inherited = std::env::var_os("PATH");
+            if let Some(path) = compose_path(&options.path_prepend, inherited.as_deref()) {
+                command.env("PATH", path);
+            }
+        }
+        Self::with_command(command)
+    }
+
+    /// Start a session from a prepared shell `Command` (stdio is overridden)
+    fn with_command(mut command: Command) -> std::io::Result<Self> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            let _ = child.kill();
+            return Err(std::io::Error::other("session shell has no pipes"));
+        };
+        let (tx, lines) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            let mut buf = Vec::new();
+            while matches!(reader.read_until(b'\n', &mut buf), Ok(n) if n > 0) {
+                let line = String::from_utf8_lossy(&buf);
+                if tx
+                    .send(line.trim_end_matches(['\n', '\r']).to_string())
+                    .is_err()
+                {
+                    break;
+                }
+                buf.clear();
+            }
+        });
+        Ok(Self {
+            child,
+            stdin,
+            lines,
+        })
+    }
+
+    /// Run `cmd` in the session, passing each output line (stdout and stderr)
+    /// to `on_line`, and return its exit code
+    ///
+    /// `cwd` (a command's own `cwd`) is entered first; without it the command
+    /// runs wherever the previous one left the shell. Fails if the shell exits
+    /// (e.g. the command ran `exit`), or is killed because `limits` gave up on
+    /// the command; start a new session in that case.
+    pub fn run(
+        &mut self,
+        cmd: &str,
+        cwd: Option<&Path>,
+        limits: &SessionLimits,
+        mut on_line: impl FnMut(&str),
+    ) -> std::io::Result<i32> {
+        let cd = cwd.map_or(String::new(), |dir| {
+            format!("cd {} && ", sh_quote(&dir.to_string_lossy()))
+        });
+        let script = format!(
+            "{{\n{}eval {}\n}} </dev/null 2>&1\nprintf '%s %d\\n' {} \"$?\"\n",
+            cd,
+            sh_quote(cmd),
+            SESSION_SENTINEL
+        );
+        self.stdin.write_all(script.as_bytes())?;
+        self.stdin.flush()?;
+
+        let deadline = limits.timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            if limits.cancel.load(Ordering::SeqCst) {
+                let _ = self.child.kill();
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Interrupted,
+                    "session command cancelled",
+                ));
+            }
+            let now = Instant::now();
+            if deadline.is_some_and(|deadline| now >= deadline) {
+                let _ = self.child.kill();
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!(
+                        "session command timed out after {}s",
+                        limits.timeout.unwrap_or_default().as_secs()
+                    ),
+                ));
+            }
+            let wait = deadline.map_or(SESSION_POLL, |deadline| (deadline - now).min(SESSION_POLL));
+            let text = match self.lines.recv_timeout(wait) {
+                Ok(text) => text,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::UnexpectedEof,
+                        "session shell exited",
+                    ));
+                }
+            };
+            // Output without a trailing newline shares the sentinel's line
+            if let Some(pos) = text.rfind(SESSION_SENTINEL) {
+                if let Ok(code) = text[pos + SESSION_SENTINEL.len()..].trim().parse() {
+                    if pos > 0 {
+                        on_line(&text[..pos]);
+                    }
+                    return Ok(code);
+                }
+            }
+            on_line(&text);
+        }
+    }
+}
+
+/// Run `cmd` in the session held by `slot`, starting one in `cwd` if needed
+///
+/// `explicit_cwd` is the command's own `cwd`, entered before it runs. A
+/// session whose shell died (or was killed by `limits`) is dropped, so the
+/// next command starts fresh.
+pub fn run_in_session(
+    slot: &mut Option<ShellSession>,
+    cmd: &str,
+    cwd: &Path,
+    explicit_cwd: Option<&Path>,
+    options: &ShellOptions,
+    limits: &SessionLimits,
+    on_line: impl FnMut(&str),
+) -> std::io::Result<i32> {
+    if limits.cancel.load(Ordering::SeqCst) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Interrupted,
+            "session command cancelled",
+        ));
+    }
+    let session = match slot {
+        Some(session) => session,
+        None => slot.insert(ShellSession::spawn(cwd, options)?),
+    };
+    let result = session.run(cmd, explicit_cwd, limits, on_line);
+    if result.is_err() {
+        *slot = None;
+    }
+    result
+}
+
+/// Quote `s` as one POSIX shell word
+fn sh_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+impl Drop for ShellSession {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Run a shell command to completion with inherited stdio (for the CLI)
+pub fn run_shell_command(
+    cmd: &str,
+    cwd: &Path,
+    options: &ShellOptions,
+) -> std::io::Result<std::process::ExitStatus> {
+    shell_command(cmd, cwd, options)?.status()
+}
+
+/// Run a shell command to completion, capturing its output
+pub fn shell_output(cmd: &str, cwd: &Path) -> std::io::Result<std::process::Output> {
     #[cfg(target_os = "windows")]
     {
         Command::new("cmd")
             .args(["/C", cmd])
             .current_dir(cwd)
-            .spawn()
+            .output()
     }
     #[cfg(not(target_os = "windows"))]
     {
         Command::new("sh")
             .args(["-c", cmd])
             .current_dir(cwd)
-            .spawn()
+            .output()
+    }
+}
+
+/// Current git branch of `dir` (None outside a repository)
+pub fn git_branch(dir: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .current_dir(dir)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_git_branch(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Git directory of the repository containing `dir`
+///
+/// Searches `dir` and its parents for `.git`, following the `gitdir:` file of
+/// worktrees and submodules.
+pub fn find_git_dir(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors().find_map(|ancestor| {
+        let dot_git = ancestor.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+        let contents = std::fs::read_to_string(&dot_git).ok()?;
+        let git_dir = contents.lines().next()?.strip_prefix("gitdir:")?.trim();
+        Some(ancestor.join(git_dir))
+    })
+}
+
+/// Branch name from `git rev-parse --abbrev-ref HEAD` output
+fn parse_git_branch(stdout: &str) -> Option<String> {
+    match stdout.lines().next()?.trim() {
+        "" => None,
+        "HEAD" => Some("(detached)".to_string()),
+        branch => Some(branch.to_string()),
+    }
+}
+
+/// Keep a window visible on every Space (macOS only)
+#[cfg(target_os = "macos")]
+pub fn show_on_all_spaces(window: &impl HasWindowHandle) -> Result<(), String> {
+    use objc2::msg_send;
+    use objc2::runtime::AnyObject;
+    use raw_window_handle::RawWindowHandle;
+
+    /// `NSWindowCollectionBehaviorCanJoinAllSpaces`
+    const CAN_JOIN_ALL_SPACES: usize = 1 << 0;
+
+    let handle = window.window_handle().map_err(|e| e.to_string())?;
+    let RawWindowHandle::AppKit(appkit) = handle.as_raw() else {
+        return Err("not an AppKit window".to_string());
+    };
+    // SAFETY: the view pointer comes from a live window handle, and these
+    // NSView/NSWindow selectors take and return plain values.
+    unsafe {
+        let view: &AnyObject = appkit.ns_view.cast::<AnyObject>().as_ref();
+        let ns_window: *mut AnyObject = msg_send![view, window];
+        let Some(ns_window) = ns_window.as_ref() else {
+            return Err("view has no window".to_string());
+        };
+        let behavior: usize = msg_send![ns_window, collectionBehavior];
+        let _: () = msg_send![ns_window, setCollectionBehavior: behavior | CAN_JOIN_ALL_SPACES];
+    }
+    Ok(())
+}
+
+/// Keep a window visible on every Space (macOS only)
+#[cfg(not(target_os = "macos"))]
+pub fn show_on_all_spaces(_window: &impl HasWindowHandle) -> Result<(), String> {
+    Err("only supported on macOS".to_string())
+}
+
+/// Keep clicks from activating (focusing) a window
+///
+/// On Windows this sets `WS_EX_NOACTIVATE`. On X11 only a window created as a
+/// dock (`x11_dock`, see `main.rs`) stays unfocused; nothing is left to do here.
+#[cfg(all(windows, target_pointer_width = "64"))]
+pub fn prevent_activation(window: &impl HasWindowHandle, _x11_dock: bool) -> Result<(), String> {
+    use raw_window_handle::RawWindowHandle;
+
+    const GWL_EXSTYLE: i32 = -20;
+    const WS_EX_NOACTIVATE: isize = 0x0800_0000;
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetWindowLongPtrW(hwnd: isize, index: i32) -> isize;
+        fn SetWindowLongPtrW(hwnd: isize, index: i32, value: isize) -> isize;
+    }
+
+    let handle = window.window_handle().map_err(|e| e.to_string())?;
+    let RawWindowHandle::Win32(win32) = handle.as_raw() else {
+        return Err("not a Win32 window".to_string());
+    };
+    let hwnd = win32.hwnd.get();
+    // SAFETY: the HWND comes from a live window handle; only its extended
+    // style bits are read and updated.
+    unsafe {
+        let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+        SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style | WS_EX_NOACTIVATE);
+    }
+    Ok(())
+}
+
+/// Keep clicks from activating (focusing) a window
+#[cfg(target_os = "linux")]
+pub fn prevent_activation(window: &impl HasWindowHandle, x11_dock: bool) -> Result<(), String> {
+    use raw_window_handle::RawWindowHandle;
+
+    let handle = window.window_handle().map_err(|e| e.to_string())?;
+    match handle.as_raw() {
+        RawWindowHandle::Xlib(_) | RawWindowHandle::Xcb(_) if x11_dock => Ok(()),
+        RawWindowHandle::Xlib(_) | RawWindowHandle::Xcb(_) => {
+            Err("clicks still focus the bar on X11 unless x11_dock = true".to_string())
+        }
+        _ => Err("not supported on Wayland".to_string()),
+    }
+}
+
+/// Keep clicks from activating (focusing) a window
+#[cfg(not(any(all(windows, target_pointer_width = "64"), target_os = "linux")))]
+pub fn prevent_activation(_window: &impl HasWindowHandle, _x11_dock: bool) -> Result<(), String> {
+    Err(
+        "clicks still focus the bar on this platform; only the initial focus is skipped"
+            .to_string(),
+    )
+}
+
+/// Title of desktop notifications
+const NOTIFY_TITLE: &str = "Launch Bar";
+
+/// Command that shows `message` as a desktop notification (None if no helper is installed)
+///
+/// Notification Center on macOS, `notify-send` on Linux and a toast on Windows.
+fn notify_argv(message: &str, has_program: impl Fn(&str) -> bool) -> Option<Vec<String>> {
+    let message = message.replace(['\r', '\n'], " ");
+    #[cfg(target_os = "macos")]
+    {
+        let escaped = message.replace('\\', "\\\\").replace('"', "\\\"");
+        has_program("osascript").then(|| {
+            vec![
+                "osascript".to_string(),
+                "-e".to_string(),
+                format!(
+                    "display notification \"{}\" with title \"{}\"",
+                    escaped, NOTIFY_TITLE
+                ),
+            ]
+        })
+    }
+    #[cfg(target_os = "windows")]
+    {
+        // Shown as PowerShell, whose app id is registered on every install
+        let script = format!(
+            "[void][Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime]; \
+             $xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+             $text = $xml.GetElementsByTagName('text'); \
+             [void]$text[0].AppendChild($xml.CreateTextNode('{}')); \
+             [void]$text[1].AppendChild($xml.CreateTextNode('{}')); \
+             [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('{{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}}\\WindowsPowerShell\\v1.0\\powershell.exe').Show([Windows.UI.Notifications.ToastNotification]::new($xml))",
+            NOTIFY_TITLE,
+            message.replace('\'', "''")
+        );
+        has_program("powershell").then(|| {
+            vec![
+                "powershell".to_string(),
+                "-NoProfile".to_string(),
+                "-Command".to_string(),
+                script,
+            ]
+        })
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        has_program("notify-send").then(|| {
+            vec![
+                "notify-send".to_string(),
+                "--app-name".to_string(),
+                NOTIFY_TITLE.to_string(),
+                NOTIFY_TITLE.to_string(),
+                message,
+            ]
+        })
+    }
+}
+
+/// Show a desktop notification without waiting for it
+///
+/// Logged to stderr when no notification helper is installed (e.g. Linux
+/// without `notify-send`) or it can't be started.
+pub fn notify(message: &str) {
+    let Some(argv) = notify_argv(message, |program| which_with(program, &[]).is_some()) else {
+        eprintln!("[notify] {}", message);
+        return;
+    };
+    let child = Command::new(&argv[0])
+        .args(&argv[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match child {
+        // Reap it off the calling thread
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+        }
+        Err(_) => eprintln!("[notify] {}", message),
     }
 }
 
@@ -37,6 +946,295 @@ pub fn open_file(path: &PathBuf) {
     }
 }
 
+/// Show a file selected in Finder / Explorer, or its folder on Linux
+pub fn reveal_in_file_manager(path: &Path) -> std::io::Result<()> {
+    let argv = reveal_argv(path);
+    Command::new(&argv[0]).args(&argv[1..]).spawn()?;
+    Ok(())
+}
+
+fn reveal_argv(path: &Path) -> Vec<String> {
+    #[cfg(target_os = "macos")]
+    {
+        vec!["open".into(), "-R".into(), path.display().to_string()]
+    }
+    #[cfg(target_os = "windows")]
+    {
+        vec!["explorer".into(), format!("/select,{}", path.display())]
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        // No portable "select" on Linux; open the containing folder
+        let dir = path.parent().unwrap_or(path);
+        vec!["xdg-open".into(), dir.display().to_string()]
+    }
+}
+
+/// Command that brings the application window named `app` to the front
+///
+/// `has_program` tells whether a helper is installed; `None` if none is.
+fn focus_app_argv(app: &str, has_program: impl Fn(&str) -> bool) -> Option<Vec<String>> {
+    #[cfg(target_os = "macos")]
+    {
+        let app = app.replace('\\', "\\\\").replace('"', "\\\"");
+        has_program("osascript").then(|| {
+            vec![
+                "osascript".to_string(),
+                "-e".to_string(),
+                format!("tell application \"{}\" to activate", app),
+            ]
+        })
+    }
+    #[cfg(target_os = "windows")]
+    {
+        // AppActivate matches the start of a window title and calls SetForegroundWindow
+        let script = format!(
+            "$null = (New-Object -ComObject WScript.Shell).AppActivate('{}')",
+            app.replace('\'', "''")
+        );
+        has_program("powershell").then(|| {
+            vec![
+                "powershell".to_string(),
+                "-NoProfile".to_string(),
+                "-Command".to_string(),
+                script,
+            ]
+        })
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        // Both match window titles; wmctrl is tried first
+        if has_program("wmctrl") {
+            Some(vec![
+                "wmctrl".to_string(),
+                "-a".to_string(),
+                app.to_string(),
+            ])
+        } else if has_program("xdotool") {
+            Some(
+                [
+                    "xdotool",
+                    "search",
+                    "--onlyvisible",
+                    "--name",
+                    app,
+                    "windowactivate",
+                ]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            )
+        } else {
+            None
+        }
+    }
+}
+
+/// Bring an already open application window forward (`focus_app`)
+///
+/// The helper is started without waiting for it; returns false (a no-op)
+/// when no helper is installed or it can't be started.
+pub fn focus_app(app: &str) -> bool {
+    let Some(argv) = focus_app_argv(app, |program| which_with(program, &[]).is_some()) else {
+        eprintln!(
+            "[warn] focus_app: no window helper found{}",
+            if cfg!(any(target_os = "macos", target_os = "windows")) {
+                ""
+            } else {
+                " (install wmctrl or xdotool)"
+            }
+        );
+        return false;
+    };
+    let child = Command::new(&argv[0])
+        .args(&argv[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match child {
+        Ok(mut child) => {
+            // Reap it off the UI thread
+            std::thread::spawn(move || child.wait());
+            true
+        }
+        Err(e) => {
+            eprintln!("[warn] focus_app: cannot run {}: {}", argv[0], e);
+            false
+        }
+    }
+}
+
+/// Editor command for opening files: `window.editor`, then `$VISUAL` / `$EDITOR`
+///
+/// `None` means the OS default app.
+pub fn select_editor(
+    configured: Option<&str>,
+    env: impl Fn(&str) -> Option<String>,
+) -> Option<String> {
+    configured
+        .map(str::to_string)
+        .into_iter()
+        .chain(["VISUAL", "EDITOR"].into_iter().filter_map(env))
+        .find(|editor| !editor.trim().is_empty())
+}
+
+/// Open a file at `line` in `editor` (`window.editor`) or the editor named by `$VISUAL` / `$EDITOR`
+///
+/// A configured editor is always used, with its go-to-line syntax when known.
+/// Otherwise falls back to [`open_file`] when no line is given, no editor is set,
+/// or the editor has no known go-to-line syntax. Terminal editors (`nvim`) run
+/// in a new terminal window, or fall back to [`open_file`] if none is found.
+pub fn open_file_at_line(path: &PathBuf, line: Option<usize>, editor: Option<&str>) {
+    let configured = editor.filter(|e| !e.trim().is_empty());
+    let argv = configured
+        .map(str::to_string)
+        .or_else(|| line.and(select_editor(None, |var| std::env::var(var).ok())))
+        .and_then(|editor| {
+            let at_line = line.and_then(|line| editor_argv(&editor, path, line));
+            let argv = match configured {
+                Some(_) => at_line.or_else(|| editor_command(&editor, path)),
+                None => at_line,
+            }?;
+            if is_terminal_editor(&editor) {
+                terminal_argv(argv, |program| which_with(program, &[]).is_some())
+            } else {
+                Some(argv)
+            }
+        });
+    match argv {
+        Some(argv) if Command::new(&argv[0]).args(&argv[1..]).spawn().is_ok() => {}
+        _ => open_file(path),
+    }
+}
+
+/// Argv running `editor` (program plus its own args, e.g. `code --wait`) on `path`
+fn editor_command(editor: &str, path: &Path) -> Option<Vec<String>> {
+    let mut argv: Vec<String> = editor.split_whitespace().map(str::to_string).collect();
+    if argv.is_empty() {
+        return None;
+    }
+    argv.push(path.display().to_string());
+    Some(argv)
+}
+
+/// Open `path` in the editor picked by [`select_editor`], waiting for it to exit
+///
+/// Terminal editors (`nvim`) take over the current terminal; without an editor
+/// the OS default app is used.
+pub fn open_in_editor(path: &Path, configured: Option<&str>) -> std::io::Result<()> {
+    let argv = select_editor(configured, |var| std::env::var(var).ok())
+        .and_then(|editor| editor_command(&editor, path));
+    match argv {
+        Some(argv) => {
+            Command::new(&argv[0]).args(&argv[1..]).status()?;
+            Ok(())
+        }
+        None => open_file_with_default_app(path),
+    }
+}
+
+/// Editors that need a terminal to draw in
+const TERMINAL_EDITORS: &[&str] = &[
+    "vi", "vim", "nvim", "nano", "pico", "micro", "hx", "helix", "kak", "emacs", "ne", "joe",
+];
+
+/// Whether `editor` runs inside a terminal (`nvim`, `nano`, `emacs -nw`, ...)
+///
+/// Plain `emacs` opens its own window when a display is available.
+fn is_terminal_editor(editor: &str) -> bool {
+    let mut words = editor.split_whitespace();
+    let Some(name) = words
+        .next()
+        .and_then(|program| Path::new(program).file_stem())
+        .and_then(|name| name.to_str())
+    else {
+        return false;
+    };
+    match name {
+        "emacs" | "emacsclient" => words.any(|arg| matches!(arg, "-nw" | "-t" | "--tty")),
+        _ => TERMINAL_EDITORS.contains(&name),
+    }
+}
+
+/// Argv running `argv` in a new terminal window (None if no terminal is found)
+///
+/// Terminal.app on macOS, a new console on Windows, and the first terminal
+/// emulator found on `PATH` elsewhere.
+fn terminal_argv(argv: Vec<String>, has_program: impl Fn(&str) -> bool) -> Option<Vec<String>> {
+    #[cfg(target_os = "macos")]
+    {
+        let line: Vec<String> = argv.iter().map(|arg| sh_quote(arg)).collect();
+        let escaped = line.join(" ").replace('\\', "\\\\").replace('"', "\\\"");
+        has_program("osascript").then(|| {
+            vec![
+                "osascript".to_string(),
+                "-e".to_string(),
+                format!("tell application \"Terminal\" to do script \"{}\"", escaped),
+                "-e".to_string(),
+                "tell application \"Terminal\" to activate".to_string(),
+            ]
+        })
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let _ = has_program;
+        // `start` opens a new console window; the empty string is its title
+        Some(
+            ["cmd", "/C", "start", ""]
+                .into_iter()
+                .map(str::to_string)
+                .chain(argv)
+                .collect(),
+        )
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        /// Terminal emulators and the arguments that precede the command
+        const TERMINALS: &[(&str, &[&str])] = &[
+            ("x-terminal-emulator", &["-e"]),
+            ("gnome-terminal", &["--"]),
+            ("konsole", &["-e"]),
+            ("xfce4-terminal", &["-x"]),
+            ("kitty", &[]),
+            ("alacritty", &["-e"]),
+            ("wezterm", &["start", "--"]),
+            ("foot", &[]),
+            ("xterm", &["-e"]),
+        ];
+        let (terminal, args) = TERMINALS
+            .iter()
+            .find(|(terminal, _)| has_program(terminal))?;
+        Some(
+            std::iter::once(*terminal)
+                .chain(args.iter().copied())
+                .map(str::to_string)
+                .chain(argv)
+                .collect(),
+        )
+    }
+}
+
+/// Argv opening `path` at `line` in a known editor (None if unknown)
+fn editor_argv(editor: &str, path: &Path, line: usize) -> Option<Vec<String>> {
+    let program = editor.split_whitespace().next()?;
+    let name = Path::new(program).file_stem()?.to_str()?;
+    let at_line = format!("{}:{}", path.display(), line);
+    match name {
+        "code" | "code-insiders" | "codium" | "cursor" | "windsurf" => {
+            Some(vec![program.to_string(), "-g".to_string(), at_line])
+        }
+        "subl" | "zed" => Some(vec![program.to_string(), at_line]),
+        "mate" | "gvim" | "mvim" | "vi" | "vim" | "nvim" | "nano" | "pico" | "micro" | "kak"
+        | "emacs" | "emacsclient" => Some(vec![
+            program.to_string(),
+            format!("+{}", line),
+            path.display().to_string(),
+        ]),
+        _ => None,
+    }
+}
+
 /// Open a file with the default application (blocking version for CLI)
 pub fn open_file_with_default_app(path: &Path) -> std::io::Result<()> {
     #[cfg(target_os = "macos")]
@@ -55,3 +1253,665 @@ pub fn open_file_with_default_app(path: &Path) -> std::io::Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_editor_precedence() {
+        let env = |var: &str| match var {
+            "EDITOR" => Some("vim".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            select_editor(Some("code --wait"), env).as_deref(),
+            Some("code --wait")
+        );
+        assert_eq!(select_editor(None, env).as_deref(), Some("vim"));
+        // A blank setting falls through
+        assert_eq!(select_editor(Some("  "), env).as_deref(), Some("vim"));
+        // $VISUAL wins over $EDITOR
+        let both = |var: &str| Some(var.to_lowercase());
+        assert_eq!(select_editor(None, both).as_deref(), Some("visual"));
+        // Nothing set: OS default
+        assert_eq!(select_editor(None, |_| None), None);
+    }
+
+    #[test]
+    fn test_editor_command() {
+        let path = Path::new("/tmp/launch-bar.toml");
+        assert_eq!(
+            editor_command("code --wait", path).unwrap(),
+            vec!["code", "--wait", "/tmp/launch-bar.toml"]
+        );
+        assert_eq!(editor_command(" ", path), None);
+    }
+
+    #[test]
+    fn test_editor_argv() {
+        let path = Path::new("/tmp/launch-bar.toml");
+        assert_eq!(
+            editor_argv("code --wait", path, 12).unwrap(),
+            vec!["code", "-g", "/tmp/launch-bar.toml:12"]
+        );
+        assert_eq!(
+            editor_argv("/usr/local/bin/zed", path, 3).unwrap(),
+            vec!["/usr/local/bin/zed", "/tmp/launch-bar.toml:3"]
+        );
+        assert_eq!(
+            editor_argv("gvim", path, 7).unwrap(),
+            vec!["gvim", "+7", "/tmp/launch-bar.toml"]
+        );
+        assert_eq!(
+            editor_argv("nvim", path, 7).unwrap(),
+            vec!["nvim", "+7", "/tmp/launch-bar.toml"]
+        );
+        // Unknown editors fall back to a plain open
+        assert_eq!(editor_argv("ed", path, 7), None);
+        assert_eq!(editor_argv("", path, 7), None);
+    }
+
+    #[test]
+    fn test_is_terminal_editor() {
+        assert!(is_terminal_editor("nvim"));
+        assert!(is_terminal_editor("/usr/bin/vim -u NONE"));
+        assert!(is_terminal_editor("emacs -nw"));
+        assert!(!is_terminal_editor("emacs"));
+        assert!(!is_terminal_editor("code --wait"));
+        assert!(!is_terminal_editor("gvim"));
+        assert!(!is_terminal_editor(""));
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[test]
+    fn test_terminal_argv() {
+        let argv = || vec!["nvim".to_string(), "/tmp/a.toml".to_string()];
+        assert_eq!(
+            terminal_argv(argv(), |program| program == "gnome-terminal").unwrap(),
+            vec!["gnome-terminal", "--", "nvim", "/tmp/a.toml"]
+        );
+        assert_eq!(
+            terminal_argv(argv(), |program| program == "kitty").unwrap(),
+            vec!["kitty", "nvim", "/tmp/a.toml"]
+        );
+        assert_eq!(terminal_argv(argv(), |_| false), None);
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_terminal_argv() {
+        let argv = vec!["nvim".to_string(), "/tmp/it's.toml".to_string()];
+        assert_eq!(
+            terminal_argv(argv, |_| true).unwrap()[2],
+            "tell application \"Terminal\" to do script \"'nvim' '/tmp/it'\\\\''s.toml'\""
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_detached_command_gets_own_session() {
+        let mut child = detach_command(Command::new("sleep").arg("5"))
+            .spawn()
+            .unwrap();
+        let pid = child.id() as libc::pid_t;
+        // SAFETY: getsid only reads process state
+        let (child_sid, own_sid) = unsafe { (libc::getsid(pid), libc::getsid(0)) };
+        child.kill().ok();
+        child.wait().ok();
+        assert_eq!(child_sid, pid);
+        assert_ne!(child_sid, own_sid);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_detach_flags() {
+        assert_eq!(detach_flags(), Some(DetachFlags::CreationFlags(0x208)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_detached_command_runs_without_pipes() {
+        let options = ShellOptions {
+            detach: true,
+            ..Default::default()
+        };
+        let mut child = spawn_shell_command("exit 3", Path::new("."), &options).unwrap();
+        assert!(child.stdout.is_none());
+        assert_eq!(child.wait().unwrap().code(), Some(3));
+    }
+
+    #[test]
+    fn test_spawn_bar_argv() {
+        let base = Path::new("/work/repo");
+        assert_eq!(
+            spawn_bar_argv(base, "crates/core"),
+            vec![
+                "--cwd".to_string(),
+                base.join("crates/core").display().to_string()
+            ]
+        );
+        // Absolute paths replace the base
+        assert_eq!(spawn_bar_argv(base, "/srv/app")[1], "/srv/app");
+        assert!(spawn_bar(base, "no-such-dir").is_err());
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_reveal_argv() {
+        assert_eq!(
+            reveal_argv(Path::new("/tmp/cfg/launch-bar.toml")),
+            vec!["open", "-R", "/tmp/cfg/launch-bar.toml"]
+        );
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_reveal_argv() {
+        assert_eq!(
+            reveal_argv(Path::new(r"C:\cfg\launch-bar.toml")),
+            vec!["explorer", r"/select,C:\cfg\launch-bar.toml"]
+        );
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[test]
+    fn test_reveal_argv() {
+        assert_eq!(
+            reveal_argv(Path::new("/tmp/cfg/launch-bar.toml")),
+            vec!["xdg-open", "/tmp/cfg"]
+        );
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_notify_argv() {
+        assert_eq!(
+            notify_argv("Done: \"Build\"\nok", |_| true).unwrap(),
+            vec![
+                "osascript",
+                "-e",
+                "display notification \"Done: \\\"Build\\\" ok\" with title \"Launch Bar\""
+            ]
+        );
+        assert_eq!(notify_argv("hi", |_| false), None);
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_notify_argv() {
+        let argv = notify_argv("Bob's build", |_| true).unwrap();
+        assert_eq!(argv[..3], ["powershell", "-NoProfile", "-Command"]);
+        assert!(argv[3].contains("CreateTextNode('Bob''s build')"));
+        assert_eq!(notify_argv("hi", |_| false), None);
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[test]
+    fn test_notify_argv() {
+        assert_eq!(
+            notify_argv("Done: Build\n(3s)", |program| program == "notify-send").unwrap(),
+            vec![
+                "notify-send",
+                "--app-name",
+                "Launch Bar",
+                "Launch Bar",
+                "Done: Build (3s)"
+            ]
+        );
+        assert_eq!(notify_argv("hi", |_| false), None);
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_focus_app_argv() {
+        assert_eq!(
+            focus_app_argv("Visual Studio Code", |_| true).unwrap(),
+            vec![
+                "osascript",
+                "-e",
+                "tell application \"Visual Studio Code\" to activate"
+            ]
+        );
+        assert_eq!(
+            focus_app_argv("My \"App\"", |_| true).unwrap()[2],
+            "tell application \"My \\\"App\\\"\" to activate"
+        );
+        assert_eq!(focus_app_argv("Safari", |_| false), None);
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_focus_app_argv() {
+        assert_eq!(
+            focus_app_argv("Bob's Editor", |_| true).unwrap(),
+            vec![
+                "powershell",
+                "-NoProfile",
+                "-Command",
+                "$null = (New-Object -ComObject WScript.Shell).AppActivate('Bob''s Editor')"
+            ]
+        );
+        assert_eq!(focus_app_argv("Code", |_| false), None);
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[test]
+    fn test_focus_app_argv() {
+        assert_eq!(
+            focus_app_argv("Visual Studio Code", |_| true).unwrap(),
+            vec!["wmctrl", "-a", "Visual Studio Code"]
+        );
+        assert_eq!(
+            focus_app_argv("Firefox", |program| program == "xdotool").unwrap(),
+            vec![
+                "xdotool",
+                "search",
+                "--onlyvisible",
+                "--name",
+                "Firefox",
+                "windowactivate"
+            ]
+        );
+        // Neither helper installed: nothing to run
+        assert_eq!(focus_app_argv("Firefox", |_| false), None);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_shell_argv_non_login() {
+        let options = ShellOptions::default();
+        assert_eq!(
+            shell_argv_with("echo hi", &options, Some("/bin/zsh")),
+            vec!["sh", "-c", "echo hi"]
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_shell_argv_login() {
+        let options = ShellOptions {
+            login: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            shell_argv_with("echo hi", &options, Some("/bin/zsh")),
+            vec!["/bin/zsh", "-lc", "echo hi"]
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_shell_argv_login_without_shell_env() {
+        let options = ShellOptions {
+            login: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            shell_argv_with("echo hi", &options, None),
+            vec!["sh", "-c", "echo hi"]
+        );
+        assert_eq!(
+            shell_argv_with("echo hi", &options, Some("")),
+            vec!["sh", "-c", "echo hi"]
+        );
+    }
+
+    #[test]
+    fn test_shell_argv_explicit_interpreters() {
+        let cases = [
+            (Shell::Sh, vec!["sh", "-c", "echo hi"]),
+            (Shell::Bash, vec!["bash", "-c", "echo hi"]),
+            (Shell::Zsh, vec!["zsh", "-c", "echo hi"]),
+            (Shell::Fish, vec!["fish", "-c", "echo hi"]),
+            (Shell::Nu, vec!["nu", "-c", "echo hi"]),
+            (Shell::Cmd, vec!["cmd", "/C", "echo hi"]),
+        ];
+        for (shell, expected) in cases {
+            let options = ShellOptions {
+                shell: Some(shell),
+                ..Default::default()
+            };
+            assert_eq!(
+                shell_argv_with("echo hi", &options, Some("/bin/zsh")),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_shell_argv_explicit_login() {
+        let options = ShellOptions {
+            login: true,
+            shell: Some(Shell::Fish),
+            ..Default::default()
+        };
+        assert_eq!(
+            shell_argv_with("echo hi", &options, Some("/bin/zsh")),
+            vec!["fish", "-l", "-c", "echo hi"]
+        );
+    }
+
+    #[test]
+    fn test_which_in_finds_executable() {
+        let dir = std::env::temp_dir().join(format!("launch-bar-which-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let name = if cfg!(target_os = "windows") {
+            "fish.exe"
+        } else {
+            "fish"
+        };
+        std::fs::write(dir.join(name), "").unwrap();
+        let path = std::env::join_paths([&dir]).unwrap();
+
+        assert_eq!(which_in("fish", Some(&path)), Some(dir.join(name)));
+        assert_eq!(which_in("nu", Some(&path)), None);
+        assert_eq!(which_in("fish", None), None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_parse_git_branch() {
+        assert_eq!(parse_git_branch("main\n"), Some("main".to_string()));
+        assert_eq!(
+            parse_git_branch("feature/login\r\n"),
+            Some("feature/login".to_string())
+        );
+        assert_eq!(parse_git_branch("HEAD\n"), Some("(detached)".to_string()));
+        assert_eq!(parse_git_branch(""), None);
+        assert_eq!(parse_git_branch("\n"), None);
+    }
+
+    #[test]
+    fn test_find_git_dir_from_subdirectory() {
+        let root = std::env::temp_dir().join(format!("launch-bar-gitdir-{}", std::process::id()));
+        let sub = root.join("repo/src/deep");
+        std::fs::create_dir_all(&sub).unwrap();
+        std::fs::create_dir_all(root.join("repo/.git")).unwrap();
+        assert_eq!(find_git_dir(&sub), Some(root.join("repo/.git")));
+
+        // Worktrees point at their git directory from a `.git` file
+        let worktree = root.join("worktree");
+        std::fs::create_dir_all(&worktree).unwrap();
+        std::fs::write(worktree.join(".git"), "gitdir: ../repo/.git/worktrees/wt\n").unwrap();
+        assert_eq!(
+            find_git_dir(&worktree),
+            Some(worktree.join("../repo/.git/worktrees/wt"))
+        );
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_compose_path_prepends_entries() {
+        let base = Path::new("/work/project");
+        let prepend = resolve_path_entries(
+            &["node_modules/.bin".to_string(), "/opt/tools".to_string()],
+            base,
+        );
+        let inherited = std::env::join_paths(["/usr/bin", "/bin"]).unwrap();
+
+        let path = compose_path(&prepend, Some(&inherited)).unwrap();
+        let entries: Vec<PathBuf> = std::env::split_paths(&path).collect();
+        assert_eq!(
+            entries,
+            vec![
+                PathBuf::from("/work/project/node_modules/.bin"),
+                PathBuf::from("/opt/tools"),
+                PathBuf::from("/usr/bin"),
+                PathBuf::from("/bin"),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sudo_argv() {
+        let argv = vec!["sh".to_string(), "-c".to_string(), "apt update".to_string()];
+        assert_eq!(
+            sudo_argv(argv.clone(), None),
+            vec!["sudo", "-A", "--", "sh", "-c", "apt update"]
+        );
+        assert_eq!(
+            sudo_argv(argv, Some(OsStr::new("/opt/tools/bin:/usr/bin"))),
+            vec![
+                "sudo",
+                "-A",
+                "--",
+                "env",
+                "PATH=/opt/tools/bin:/usr/bin",
+                "sh",
+                "-c",
+                "apt update"
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_nice_argv() {
+        let argv = || vec!["sh".to_string(), "-c".to_string(), "make".to_string()];
+        assert_eq!(
+            nice_argv(argv(), 10),
+            vec!["nice", "-n", "10", "sh", "-c", "make"]
+        );
+        assert_eq!(nice_argv(argv(), 99)[..3], ["nice", "-n", "19"]);
+        assert_eq!(nice_argv(argv(), -40)[..3], ["nice", "-n", "-20"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_nice_command_runs() {
+        let options = ShellOptions {
+            nice: Some(5),
+            ..Default::default()
+        };
+        let output = shell_command("nice", Path::new("."), &options)
+            .unwrap()
+            .output()
+            .unwrap();
+        // `nice` with no arguments prints the current niceness
+        let niceness: i32 = String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse()
+            .unwrap();
+        assert!(niceness >= 5);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_priority_class() {
+        assert_eq!(priority_class(-20), HIGH_PRIORITY_CLASS);
+        assert_eq!(priority_class(-100), HIGH_PRIORITY_CLASS);
+        assert_eq!(priority_class(-5), ABOVE_NORMAL_PRIORITY_CLASS);
+        assert_eq!(priority_class(0), NORMAL_PRIORITY_CLASS);
+        assert_eq!(priority_class(5), BELOW_NORMAL_PRIORITY_CLASS);
+        assert_eq!(priority_class(10), IDLE_PRIORITY_CLASS);
+        assert_eq!(priority_class(99), IDLE_PRIORITY_CLASS);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_askpass() {
+        let dir = std::env::temp_dir().join(format!("launch-bar-askpass-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.clone().into_os_string();
+        assert_eq!(find_askpass(None, Some(&path)), None);
+
+        std::fs::write(dir.join("ksshaskpass"), "").unwrap();
+        assert_eq!(
+            find_askpass(None, Some(&path)),
+            Some(dir.join("ksshaskpass"))
+        );
+        // $SUDO_ASKPASS wins; an empty one is ignored
+        assert_eq!(
+            find_askpass(Some("/opt/askpass".into()), Some(&path)),
+            Some(PathBuf::from("/opt/askpass"))
+        );
+        assert_eq!(
+            find_askpass(Some("".into()), Some(&path)),
+            Some(dir.join("ksshaskpass"))
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_runas_argv() {
+        let argv = vec![
+            "cmd".to_string(),
+            "/C".to_string(),
+            "net stop spooler".to_string(),
+        ];
+        assert_eq!(
+            runas_argv(&argv),
+            vec![
+                "powershell",
+                "-NoProfile",
+                "-Command",
+                "$p = Start-Process -FilePath 'cmd' -ArgumentList '/C \"net stop spooler\"' -Verb RunAs -Wait -PassThru; exit $p.ExitCode",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_windows_quote_arg() {
+        assert_eq!(windows_quote_arg("plain"), "plain");
+        assert_eq!(windows_quote_arg(""), "\"\"");
+        assert_eq!(windows_quote_arg("a b"), "\"a b\"");
+        assert_eq!(windows_quote_arg("say \"hi\""), r#""say \"hi\"""#);
+        assert_eq!(windows_quote_arg(r"C:\My Dir\"), r#""C:\My Dir\\""#);
+        assert_eq!(windows_quote_arg(r#"a\"b"#), r#""a\\\"b""#);
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_shell_argv_windows() {
+        let options = ShellOptions {
+            login: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            shell_argv_with("echo hi", &options, None),
+            vec!["cmd", "/C", "echo hi"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shell_session_keeps_state() {
+        let mut command = Command::new("sh");
+        command.current_dir(std::env::temp_dir());
+        let mut session = ShellSession::with_command(command).unwrap();
+        let limits = SessionLimits::default();
+
+        let mut lines = Vec::new();
+        let code = session
+            .run("GREETING=hello; echo first", None, &limits, |l| {
+                lines.push(l.to_string())
+            })
+            .unwrap();
+        assert_eq!(code, 0);
+        assert_eq!(lines, vec!["first"]);
+
+        lines.clear();
+        let code = session
+            .run(
+                "echo $GREETING; echo oops >&2; printf partial; false",
+                None,
+                &limits,
+                |l| lines.push(l.to_string()),
+            )
+            .unwrap();
+        assert_eq!(code, 1);
+        assert_eq!(lines, vec!["hello", "oops", "partial"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shell_session_exit() {
+        let mut session = ShellSession::with_command(Command::new("sh")).unwrap();
+        let limits = SessionLimits::default();
+        let err = session.run("exit 3", None, &limits, |_| {}).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shell_session_syntax_error_does_not_hang() {
+        let mut slot = Some(ShellSession::with_command(Command::new("sh")).unwrap());
+        let limits = SessionLimits {
+            timeout: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+        let dir = std::env::temp_dir();
+        // dash exits on an `eval` syntax error, bash reports it; neither hangs
+        let result = run_in_session(
+            &mut slot,
+            "echo \"unterminated",
+            &dir,
+            None,
+            &ShellOptions::default(),
+            &limits,
+            |_| {},
+        );
+        assert!(!matches!(result, Ok(0)));
+        assert!(!result.is_err_and(|e| e.kind() == std::io::ErrorKind::TimedOut));
+
+        let mut lines = Vec::new();
+        let code = run_in_session(
+            &mut slot,
+            "echo 'still here'",
+            &dir,
+            None,
+            &ShellOptions::default(),
+            &limits,
+            |l| lines.push(l.to_string()),
+        )
+        .unwrap();
+        assert_eq!((code, lines), (0, vec!["still here".to_string()]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shell_session_timeout_and_cancel() {
+        let mut session = ShellSession::with_command(Command::new("sh")).unwrap();
+        let limits = SessionLimits {
+            timeout: Some(Duration::from_millis(200)),
+            ..Default::default()
+        };
+        let err = session.run("sleep 30", None, &limits, |_| {}).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+
+        let mut session = ShellSession::with_command(Command::new("sh")).unwrap();
+        let limits = SessionLimits::default();
+        let cancel = limits.cancel.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            cancel.store(true, Ordering::SeqCst);
+        });
+        let err = session.run("sleep 30", None, &limits, |_| {}).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Interrupted);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shell_session_enters_command_cwd() {
+        let base = std::env::temp_dir().join(format!("launch-bar-session-{}", std::process::id()));
+        let sub = base.join("it's here");
+        std::fs::create_dir_all(&sub).unwrap();
+        let mut command = Command::new("sh");
+        command.current_dir(&base);
+        let mut session = ShellSession::with_command(command).unwrap();
+        let limits = SessionLimits::default();
+
+        let mut lines = Vec::new();
+        session
+            .run("basename \"$PWD\"", Some(&sub), &limits, |l| {
+                lines.push(l.to_string())
+            })
+            .unwrap();
+        assert_eq!(lines, vec!["it's here"]);
+        std::fs::remove_dir_all(&base).ok();
+    }
+}
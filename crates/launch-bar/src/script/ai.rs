@@ -0,0 +1,153 @@
+//! External AI CLI used by the `claude()` / `ai()` script functions
+
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Placeholder in `args` replaced with the prompt
+pub const PROMPT_PLACEHOLDER: &str = "{{prompt}}";
+
+/// How long to sleep between checks for process exit
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// AI CLI settings (`[window.ai]`)
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub struct AiConfig {
+    /// Program to run
+    #[serde(default = "default_command")]
+    pub command: String,
+    /// Arguments; `{{prompt}}` is replaced with the prompt
+    #[serde(default = "default_args")]
+    pub args: Vec<String>,
+    /// Kill the process after this many seconds (0 = no limit)
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_command() -> String {
+    "claude".to_string()
+}
+
+fn default_args() -> Vec<String> {
+    vec!["-p".to_string(), PROMPT_PLACEHOLDER.to_string()]
+}
+
+fn default_timeout_secs() -> u64 {
+    120
+}
+
+impl Default for AiConfig {
+    fn default() -> Self {
+        Self {
+            command: default_command(),
+            args: default_args(),
+            timeout_secs: default_timeout_secs(),
+        }
+    }
+}
+
+impl AiConfig {
+    /// Program and arguments for a prompt
+    pub fn argv(&self, prompt: &str) -> Vec<String> {
+        std::iter::once(self.command.clone())
+            .chain(
+                self.args
+                    .iter()
+                    .map(|arg| arg.replace(PROMPT_PLACEHOLDER, prompt)),
+            )
+            .collect()
+    }
+
+    /// Run the CLI with a prompt and return its stdout
+    ///
+    /// The process is killed if it outlives `timeout_secs`.
+    #[cfg_attr(
+        not(any(feature = "rhai-script", feature = "lua-script")),
+        allow(dead_code)
+    )]
+    pub fn run(&self, prompt: &str, cwd: &Path) -> Result<String, String> {
+        let argv = self.argv(prompt);
+        let mut child = Command::new(&argv[0])
+            .args(&argv[1..])
+            .current_dir(cwd)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| e.to_string())?;
+
+        // Drain stdout on a separate thread so a full pipe can't block the child
+        let mut stdout = child.stdout.take().ok_or("stdout not captured")?;
+        let reader = std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = stdout.read_to_end(&mut buf);
+            buf
+        });
+
+        let deadline = (self.timeout_secs > 0)
+            .then(|| Instant::now() + Duration::from_secs(self.timeout_secs));
+        loop {
+            if child.try_wait().map_err(|e| e.to_string())?.is_some() {
+                break;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {}s", self.timeout_secs));
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+
+        let buf = reader.join().unwrap_or_default();
+        Ok(String::from_utf8_lossy(&buf).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_argv() {
+        assert_eq!(
+            AiConfig::default().argv("explain this"),
+            vec!["claude", "-p", "explain this"]
+        );
+    }
+
+    #[test]
+    fn test_argv_template_substitutes_prompt() {
+        let config = AiConfig {
+            command: "llm".to_string(),
+            args: vec![
+                "--model".to_string(),
+                "gpt-4o".to_string(),
+                "--system={{prompt}}!".to_string(),
+                "{{prompt}}".to_string(),
+            ],
+            timeout_secs: 10,
+        };
+        assert_eq!(
+            config.argv("hi there"),
+            vec!["llm", "--model", "gpt-4o", "--system=hi there!", "hi there"]
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_run_kills_hung_process() {
+        let config = AiConfig {
+            command: "sleep".to_string(),
+            args: vec!["5".to_string()],
+            timeout_secs: 1,
+        };
+        let started = Instant::now();
+        let result = config.run("ignored", Path::new("."));
+        assert!(result.unwrap_err().contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+}
@@ -0,0 +1,80 @@
+//! Clipboard history stack shared across script runs
+
+use std::sync::Mutex;
+
+use arboard::Clipboard;
+
+/// Process-lifetime stack used by `clipboard_push()` / `clipboard_pop()`
+static HISTORY: Mutex<ClipboardStack> = Mutex::new(ClipboardStack::new());
+
+/// LIFO stack of saved clipboard values
+#[derive(Debug, Default)]
+pub struct ClipboardStack {
+    items: Vec<String>,
+}
+
+impl ClipboardStack {
+    pub const fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    pub fn push(&mut self, text: String) {
+        self.items.push(text);
+    }
+
+    pub fn pop(&mut self) -> Option<String> {
+        self.items.pop()
+    }
+}
+
+/// Save the current clipboard text onto the shared stack
+///
+/// Returns false if the clipboard could not be read.
+pub fn clipboard_push() -> bool {
+    match Clipboard::new().and_then(|mut cb| cb.get_text()) {
+        Ok(text) => {
+            HISTORY.lock().unwrap_or_else(|e| e.into_inner()).push(text);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Restore the most recently pushed value to the clipboard and return it
+///
+/// Returns an empty string (leaving the clipboard untouched) if the stack is empty.
+pub fn clipboard_pop() -> String {
+    let popped = HISTORY.lock().unwrap_or_else(|e| e.into_inner()).pop();
+    match popped {
+        Some(text) => {
+            let _ = Clipboard::new().and_then(|mut cb| cb.set_text(text.clone()));
+            text
+        }
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stack_pops_in_reverse_order() {
+        let mut stack = ClipboardStack::new();
+        stack.push("first".to_string());
+        stack.push("second".to_string());
+
+        assert_eq!(stack.pop().as_deref(), Some("second"));
+        assert_eq!(stack.pop().as_deref(), Some("first"));
+    }
+
+    #[test]
+    fn test_empty_pop_returns_none() {
+        let mut stack = ClipboardStack::new();
+        assert_eq!(stack.pop(), None);
+
+        stack.push("only".to_string());
+        stack.pop();
+        assert_eq!(stack.pop(), None);
+    }
+}
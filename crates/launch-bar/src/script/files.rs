@@ -0,0 +1,120 @@
+//! File reading shared by the script engines (`read_file`, `read_file_lines`)
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// Resolve a script-supplied path against the script's working directory
+pub fn resolve_path(cwd: &Path, path: &str) -> PathBuf {
+    if path.starts_with('/') {
+        PathBuf::from(path)
+    } else {
+        cwd.join(path)
+    }
+}
+
+/// Read a whole file, or an `[ERROR:read_file]` marker if it is larger than `max_bytes`
+///
+/// `max_bytes` of 0 disables the cap. Invalid UTF-8 is replaced rather than rejected.
+/// The cap applies to the bytes actually read, so devices, FIFOs and `/proc`
+/// files (which report a size of 0) can't be read without bound.
+pub fn read_file(cwd: &Path, path: &str, max_bytes: u64) -> String {
+    let result = File::open(resolve_path(cwd, path)).and_then(|file| {
+        let mut bytes = Vec::new();
+        if max_bytes == 0 {
+            BufReader::new(file).read_to_end(&mut bytes)?;
+            return Ok(Some(bytes));
+        }
+        file.take(max_bytes + 1).read_to_end(&mut bytes)?;
+        Ok((bytes.len() as u64 <= max_bytes).then_some(bytes))
+    });
+    match result {
+        Ok(Some(bytes)) => String::from_utf8_lossy(&bytes).into_owned(),
+        Ok(None) => "[ERROR:read_file] too large".to_string(),
+        Err(e) => format!("[ERROR:read_file] {}: {}", path, e),
+    }
+}
+
+/// Read `count` lines starting at 1-based line `start`, joined with `\n`
+///
+/// Lines are streamed, so only the requested range is kept in memory.
+pub fn read_file_lines(cwd: &Path, path: &str, start: i64, count: i64) -> String {
+    let skip = usize::try_from(start.saturating_sub(1)).unwrap_or(0);
+    let take = usize::try_from(count).unwrap_or(0);
+    let result = File::open(resolve_path(cwd, path)).and_then(|file| {
+        BufReader::new(file)
+            .split(b'\n')
+            .skip(skip)
+            .take(take)
+            .map(|line| {
+                line.map(|bytes| {
+                    let bytes = bytes.strip_suffix(b"\r").unwrap_or(&bytes);
+                    String::from_utf8_lossy(bytes).into_owned()
+                })
+            })
+            .collect::<std::io::Result<Vec<_>>>()
+    });
+    match result {
+        Ok(lines) => lines.join("\n"),
+        Err(e) => format!("[ERROR:read_file_lines] {}: {}", path, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("launch-bar-files-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_read_file_lines_range() {
+        let dir = temp_dir("lines");
+        std::fs::write(dir.join("f.txt"), "one\r\ntwo\nthree\nfour\n").unwrap();
+
+        assert_eq!(read_file_lines(&dir, "f.txt", 2, 2), "two\nthree");
+        assert_eq!(read_file_lines(&dir, "f.txt", 1, 1), "one");
+        assert_eq!(read_file_lines(&dir, "f.txt", 0, 1), "one");
+        assert_eq!(read_file_lines(&dir, "f.txt", 4, 10), "four");
+        assert_eq!(read_file_lines(&dir, "f.txt", 9, 3), "");
+        assert_eq!(read_file_lines(&dir, "f.txt", 1, 0), "");
+        assert_eq!(read_file_lines(&dir, "f.txt", 1, -1), "");
+        assert!(read_file_lines(&dir, "missing.txt", 1, 1).starts_with("[ERROR:read_file_lines]"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_read_file_size_cap_boundary() {
+        let dir = temp_dir("cap");
+        std::fs::write(dir.join("f.txt"), "12345").unwrap();
+
+        assert_eq!(read_file(&dir, "f.txt", 5), "12345");
+        assert_eq!(read_file(&dir, "f.txt", 4), "[ERROR:read_file] too large");
+        assert_eq!(read_file(&dir, "f.txt", 0), "12345");
+        assert!(read_file(&dir, "missing.txt", 5).starts_with("[ERROR:read_file] missing.txt"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_file_cap_applies_to_unsized_files() {
+        let dir = temp_dir("dev");
+        assert_eq!(
+            read_file(&dir, "/dev/zero", 1024),
+            "[ERROR:read_file] too large"
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_read_file_binary_is_lossy() {
+        let dir = temp_dir("binary");
+        std::fs::write(dir.join("b.bin"), [b'a', 0xff, b'b']).unwrap();
+        assert_eq!(read_file(&dir, "b.bin", 0), "a\u{fffd}b");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
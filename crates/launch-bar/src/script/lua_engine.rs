@@ -2,15 +2,19 @@
 
 use std::path::PathBuf;
 use std::process::Command;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use arboard::Clipboard;
 use mlua::{Lua, Result as LuaResult};
 
-use super::ScriptResult;
+use super::clipboard_stack::{clipboard_pop, clipboard_push};
+use super::files::{read_file, read_file_lines, resolve_path};
+use super::{ScriptConfig, ScriptError, ScriptResult};
+use crate::platform::{detach_command, notify};
 
 /// Create a Lua instance with registered functions
-fn create_lua(cwd: Arc<PathBuf>) -> LuaResult<Lua> {
+fn create_lua(cwd: Arc<PathBuf>, config: &ScriptConfig) -> LuaResult<Lua> {
     let lua = Lua::new();
 
     // Register global functions
@@ -36,6 +40,18 @@ fn create_lua(cwd: Arc<PathBuf>) -> LuaResult<Lua> {
         })?,
     )?;
 
+    // clipboard_push() -> boolean
+    globals.set(
+        "clipboard_push",
+        lua.create_function(|_, ()| Ok(clipboard_push()))?,
+    )?;
+
+    // clipboard_pop() -> string
+    globals.set(
+        "clipboard_pop",
+        lua.create_function(|_, ()| Ok(clipboard_pop()))?,
+    )?;
+
     // shell(cmd) -> string
     let cwd_for_shell = Arc::clone(&cwd);
     globals.set(
@@ -54,54 +70,59 @@ fn create_lua(cwd: Arc<PathBuf>) -> LuaResult<Lua> {
 
     // shell_spawn(cmd) -> boolean
     let cwd_for_spawn = Arc::clone(&cwd);
+    let detach = config.detach;
     globals.set(
         "shell_spawn",
         lua.create_function(move |_, cmd: String| {
-            Ok(Command::new("sh")
+            let mut command = Command::new("sh");
+            command
                 .args(["-c", &cmd])
-                .current_dir(cwd_for_spawn.as_ref())
-                .spawn()
-                .is_ok())
+                .current_dir(cwd_for_spawn.as_ref());
+            if detach {
+                detach_command(&mut command);
+            }
+            Ok(command.spawn().is_ok())
         })?,
     )?;
 
-    // claude(prompt) -> string
-    let cwd_for_claude = Arc::clone(&cwd);
-    globals.set(
-        "claude",
-        lua.create_function(move |_, prompt: String| {
-            let output = Command::new("claude")
-                .args(["-p", &prompt])
-                .current_dir(cwd_for_claude.as_ref())
-                .output();
-            Ok(match output {
-                Ok(out) => String::from_utf8_lossy(&out.stdout).to_string(),
-                Err(e) => format!("[ERROR:claude] {}", e),
-            })
-        })?,
-    )?;
+    // claude(prompt) / ai(prompt) -> string
+    let ai = Arc::new(config.ai.clone());
+    for name in ["claude", "ai"] {
+        let ai = Arc::clone(&ai);
+        let cwd_for_ai = Arc::clone(&cwd);
+        globals.set(
+            name,
+            lua.create_function(move |_, prompt: String| {
+                Ok(ai
+                    .run(&prompt, &cwd_for_ai)
+                    .unwrap_or_else(|e| format!("[ERROR:{}] {}", name, e)))
+            })?,
+        )?;
+    }
 
     // notify(message)
-    #[cfg(target_os = "macos")]
     globals.set(
         "notify",
         lua.create_function(|_, msg: String| {
-            let escaped = msg
-                .replace("\\", "\\\\")
-                .replace("\"", "\\\"")
-                .replace("\n", " ")
-                .replace("\r", "");
-            let script = format!(r#"display alert "Launch Bar" message "{}""#, escaped);
-            let _ = Command::new("osascript").args(["-e", &script]).spawn();
+            notify(&msg);
             Ok(())
         })?,
     )?;
 
-    #[cfg(not(target_os = "macos"))]
+    // watcher_pause() / watcher_resume()
+    let paused = Arc::clone(&config.watcher_paused);
     globals.set(
-        "notify",
-        lua.create_function(|_, msg: String| {
-            eprintln!("[notify] {}", msg);
+        "watcher_pause",
+        lua.create_function(move |_, ()| {
+            paused.store(true, Ordering::SeqCst);
+            Ok(())
+        })?,
+    )?;
+    let paused = Arc::clone(&config.watcher_paused);
+    globals.set(
+        "watcher_resume",
+        lua.create_function(move |_, ()| {
+            paused.store(false, Ordering::SeqCst);
             Ok(())
         })?,
     )?;
@@ -128,16 +149,18 @@ fn create_lua(cwd: Arc<PathBuf>) -> LuaResult<Lua> {
 
     // read_file(path) -> string
     let cwd_for_read = Arc::clone(&cwd);
+    let max_bytes = config.read_file_max_bytes;
     globals.set(
         "read_file",
-        lua.create_function(move |_, path: String| {
-            let full_path = if path.starts_with('/') {
-                PathBuf::from(&path)
-            } else {
-                cwd_for_read.join(&path)
-            };
-            Ok(std::fs::read_to_string(&full_path)
-                .unwrap_or_else(|e| format!("[ERROR:read_file] {}: {}", path, e)))
+        lua.create_function(move |_, path: String| Ok(read_file(&cwd_for_read, &path, max_bytes)))?,
+    )?;
+
+    // read_file_lines(path, start, count) -> string
+    let cwd_for_lines = Arc::clone(&cwd);
+    globals.set(
+        "read_file_lines",
+        lua.create_function(move |_, (path, start, count): (String, i64, i64)| {
+            Ok(read_file_lines(&cwd_for_lines, &path, start, count))
         })?,
     )?;
 
@@ -146,34 +169,44 @@ fn create_lua(cwd: Arc<PathBuf>) -> LuaResult<Lua> {
     globals.set(
         "write_file",
         lua.create_function(move |_, (path, content): (String, String)| {
-            let full_path = if path.starts_with('/') {
-                PathBuf::from(&path)
-            } else {
-                cwd_for_write.join(&path)
-            };
-            Ok(std::fs::write(full_path, content).is_ok())
+            Ok(std::fs::write(resolve_path(&cwd_for_write, &path), content).is_ok())
         })?,
     )?;
 
     Ok(lua)
 }
 
-/// Execute a Lua script
-pub fn run(script: &str, cwd: Arc<PathBuf>) -> ScriptResult {
-    match create_lua(cwd) {
-        Ok(lua) => match lua.load(script).exec() {
-            Ok(_) => ScriptResult {
-                success: true,
-                message: "Script completed".to_string(),
-            },
-            Err(e) => ScriptResult {
-                success: false,
-                message: format!("Script error: {}", e),
-            },
-        },
-        Err(e) => ScriptResult {
-            success: false,
-            message: format!("Failed to initialize Lua: {}", e),
-        },
+/// Execute a Lua script, running the preset prelude (if any) first in the same state
+pub fn run(
+    script: &str,
+    prelude: Option<&str>,
+    cwd: Arc<PathBuf>,
+    config: &ScriptConfig,
+) -> ScriptResult {
+    let lua = match create_lua(cwd, config) {
+        Ok(lua) => lua,
+        Err(e) => {
+            return ScriptResult::failed(
+                ScriptError::Runtime,
+                format!("Failed to initialize Lua: {}", e),
+            )
+        }
+    };
+    if let Some(prelude) = prelude {
+        if let Err(e) = lua.load(prelude).set_name("prelude").exec() {
+            return ScriptResult::failed(error_kind(&e), format!("Prelude error: {}", e));
+        }
+    }
+    match lua.load(script).exec() {
+        Ok(_) => ScriptResult::completed(),
+        Err(e) => ScriptResult::failed(error_kind(&e), format!("Script error: {}", e)),
+    }
+}
+
+/// Classify a Lua error: syntax errors come from loading, everything else from running
+fn error_kind(e: &mlua::Error) -> ScriptError {
+    match e {
+        mlua::Error::SyntaxError { .. } => ScriptError::Parse,
+        _ => ScriptError::Runtime,
     }
 }
@@ -3,17 +3,26 @@
 //! Supports Rhai and Lua scripting with configurable defaults.
 
 use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
+mod ai;
+#[cfg(any(feature = "rhai-script", feature = "lua-script"))]
+mod clipboard_stack;
+#[cfg(any(feature = "rhai-script", feature = "lua-script"))]
+mod files;
 #[cfg(feature = "lua-script")]
 mod lua_engine;
 #[cfg(feature = "rhai-script")]
 mod rhai_engine;
 
+pub use ai::AiConfig;
+
 /// Script language type
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ScriptType {
     #[default]
@@ -39,6 +48,16 @@ impl ScriptType {
 pub struct ScriptConfig {
     pub global_default: Option<ScriptType>,
     pub preset_default: Option<ScriptType>,
+    /// CLI invoked by `claude()` / `ai()`
+    pub ai: AiConfig,
+    /// Largest file `read_file()` returns, in bytes (0 = no limit)
+    pub read_file_max_bytes: u64,
+    /// Active preset's `script_prelude` (inline code or `@file`), run before every script
+    pub prelude: Option<String>,
+    /// Start `shell_spawn()` processes detached from the launcher (command's `detach`)
+    pub detach: bool,
+    /// Set by `watcher_pause()` / `watcher_resume()`; shared with the bar's file watcher
+    pub watcher_paused: Arc<AtomicBool>,
 }
 
 /// Resolve script type with priority:
@@ -78,62 +97,144 @@ pub fn resolve_script_type(
     ScriptType::Rhai
 }
 
+/// Why a script failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptError {
+    /// The `@file` script or prelude couldn't be read
+    FileRead,
+    /// The script or prelude doesn't compile
+    #[cfg_attr(
+        not(any(feature = "rhai-script", feature = "lua-script")),
+        allow(dead_code)
+    )]
+    Parse,
+    /// The script raised an error while running
+    #[cfg_attr(
+        not(any(feature = "rhai-script", feature = "lua-script")),
+        allow(dead_code)
+    )]
+    Runtime,
+    /// The script's engine isn't compiled into this build
+    #[cfg(not(all(feature = "rhai-script", feature = "lua-script")))]
+    NotCompiled,
+    /// The engine panicked
+    Panicked,
+}
+
+impl ScriptError {
+    /// Exit code for `launch-bar run` (sysexits-style, 1 for script errors)
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Runtime => 1,
+            Self::Parse => 65,
+            Self::FileRead => 66,
+            #[cfg(not(all(feature = "rhai-script", feature = "lua-script")))]
+            Self::NotCompiled => 69,
+            Self::Panicked => 70,
+        }
+    }
+}
+
 /// Script execution result
 pub struct ScriptResult {
     pub success: bool,
+    /// Display message
     pub message: String,
+    /// Failure kind (None on success)
+    pub error: Option<ScriptError>,
 }
 
-/// Execute a script with the specified type
-pub fn run_script(script: &str, script_type: ScriptType, cwd: Arc<PathBuf>) -> ScriptResult {
-    // Handle file reference (@path)
-    let (actual_script, actual_cwd) = if let Some(path) = script.strip_prefix('@') {
-        let full_path = if path.starts_with('/') {
-            PathBuf::from(path)
-        } else {
-            cwd.join(path)
-        };
+impl ScriptResult {
+    #[cfg_attr(
+        not(any(feature = "rhai-script", feature = "lua-script")),
+        allow(dead_code)
+    )]
+    pub fn completed() -> Self {
+        Self {
+            success: true,
+            message: "Script completed".to_string(),
+            error: None,
+        }
+    }
 
-        match std::fs::read_to_string(&full_path) {
-            Ok(content) => {
-                let script_dir = full_path
-                    .parent()
-                    .map(|p| p.to_path_buf())
-                    .unwrap_or_else(|| cwd.as_ref().clone());
-                (content, Arc::new(script_dir))
-            }
-            Err(e) => {
-                return ScriptResult {
-                    success: false,
-                    message: format!("Failed to read script file: {}", e),
-                };
-            }
+    pub fn failed(error: ScriptError, message: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            message: message.into(),
+            error: Some(error),
         }
-    } else {
-        (script.to_string(), cwd)
+    }
+}
+
+/// Execute a script with the specified type
+pub fn run_script(
+    script: &str,
+    script_type: ScriptType,
+    cwd: Arc<PathBuf>,
+    config: &ScriptConfig,
+) -> ScriptResult {
+    let prelude = match config.prelude.as_deref().map(|p| load_source(p, &cwd)) {
+        Some(Ok((prelude, _))) => Some(prelude),
+        Some(Err(e)) => {
+            return ScriptResult::failed(
+                ScriptError::FileRead,
+                format!("Failed to read script prelude: {}", e),
+            );
+        }
+        None => None,
     };
+    let (actual_script, actual_cwd) = match load_source(script, &cwd) {
+        Ok(source) => source,
+        Err(e) => {
+            return ScriptResult::failed(
+                ScriptError::FileRead,
+                format!("Failed to read script file: {}", e),
+            );
+        }
+    };
+    #[cfg(not(any(feature = "rhai-script", feature = "lua-script")))]
+    let _ = (prelude, actual_script, actual_cwd, config);
 
     match script_type {
         #[cfg(feature = "rhai-script")]
-        ScriptType::Rhai => rhai_engine::run(&actual_script, actual_cwd),
+        ScriptType::Rhai => {
+            rhai_engine::run(&actual_script, prelude.as_deref(), actual_cwd, config)
+        }
 
         #[cfg(not(feature = "rhai-script"))]
-        ScriptType::Rhai => ScriptResult {
-            success: false,
-            message: "Rhai support not compiled in".to_string(),
-        },
+        ScriptType::Rhai => {
+            ScriptResult::failed(ScriptError::NotCompiled, "Rhai support not compiled in")
+        }
 
         #[cfg(feature = "lua-script")]
-        ScriptType::Lua => lua_engine::run(&actual_script, actual_cwd),
+        ScriptType::Lua => lua_engine::run(&actual_script, prelude.as_deref(), actual_cwd, config),
 
         #[cfg(not(feature = "lua-script"))]
-        ScriptType::Lua => ScriptResult {
-            success: false,
-            message: "Lua support not compiled in".to_string(),
-        },
+        ScriptType::Lua => {
+            ScriptResult::failed(ScriptError::NotCompiled, "Lua support not compiled in")
+        }
     }
 }
 
+/// Script source and its working directory; `@path` is read from disk and
+/// runs in the file's directory
+fn load_source(script: &str, cwd: &Arc<PathBuf>) -> Result<(String, Arc<PathBuf>), String> {
+    let Some(path) = script.strip_prefix('@') else {
+        return Ok((script.to_string(), Arc::clone(cwd)));
+    };
+    let full_path = if path.starts_with('/') {
+        PathBuf::from(path)
+    } else {
+        cwd.join(path)
+    };
+    let content = std::fs::read_to_string(&full_path).map_err(|e| e.to_string())?;
+    let script_dir = full_path
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or_else(|| cwd.as_ref().clone());
+    Ok((content, Arc::new(script_dir)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -172,8 +273,8 @@ mod tests {
     #[test]
     fn test_resolve_script_type_preset_default() {
         let config = ScriptConfig {
-            global_default: None,
             preset_default: Some(ScriptType::Lua),
+            ..Default::default()
         };
         assert_eq!(
             resolve_script_type(None, "inline code", &config),
@@ -185,7 +286,7 @@ mod tests {
     fn test_resolve_script_type_global_default() {
         let config = ScriptConfig {
             global_default: Some(ScriptType::Lua),
-            preset_default: None,
+            ..Default::default()
         };
         assert_eq!(
             resolve_script_type(None, "inline code", &config),
@@ -193,6 +294,113 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "rhai-script")]
+    #[test]
+    fn test_rhai_prelude_function_is_callable() {
+        let config = ScriptConfig {
+            prelude: Some("fn deploy(env) { \"deployed \" + env }".to_string()),
+            ..Default::default()
+        };
+        let script = r#"if deploy("prod") != "deployed prod" { throw "wrong"; }"#;
+        let result = run_script(
+            script,
+            ScriptType::Rhai,
+            Arc::new(PathBuf::from(".")),
+            &config,
+        );
+        assert!(result.success, "{}", result.message);
+    }
+
+    #[cfg(feature = "lua-script")]
+    #[test]
+    fn test_lua_prelude_function_is_callable() {
+        let config = ScriptConfig {
+            prelude: Some("function deploy(env) return 'deployed ' .. env end".to_string()),
+            ..Default::default()
+        };
+        let script = "assert(deploy('prod') == 'deployed prod')";
+        let result = run_script(
+            script,
+            ScriptType::Lua,
+            Arc::new(PathBuf::from(".")),
+            &config,
+        );
+        assert!(result.success, "{}", result.message);
+    }
+
+    #[cfg(feature = "rhai-script")]
+    #[test]
+    fn test_prelude_parse_error_is_reported_as_prelude() {
+        let config = ScriptConfig {
+            prelude: Some("fn broken( {".to_string()),
+            ..Default::default()
+        };
+        let result = run_script(
+            "1 + 1",
+            ScriptType::Rhai,
+            Arc::new(PathBuf::from(".")),
+            &config,
+        );
+        assert!(!result.success);
+        assert!(
+            result.message.starts_with("Prelude error:"),
+            "{}",
+            result.message
+        );
+    }
+
+    #[test]
+    fn test_exit_codes_per_error() {
+        assert_eq!(ScriptError::Runtime.exit_code(), 1);
+        assert_eq!(ScriptError::Parse.exit_code(), 65);
+        assert_eq!(ScriptError::FileRead.exit_code(), 66);
+        assert_eq!(ScriptError::Panicked.exit_code(), 70);
+    }
+
+    #[test]
+    fn test_missing_file_is_file_read_error() {
+        let result = run_script(
+            "@no-such-script.rhai",
+            ScriptType::Rhai,
+            Arc::new(PathBuf::from(".")),
+            &ScriptConfig::default(),
+        );
+        assert!(!result.success);
+        assert_eq!(result.error, Some(ScriptError::FileRead));
+    }
+
+    #[cfg(feature = "rhai-script")]
+    #[test]
+    fn test_rhai_error_kinds() {
+        let run = |script| {
+            run_script(
+                script,
+                ScriptType::Rhai,
+                Arc::new(PathBuf::from(".")),
+                &ScriptConfig::default(),
+            )
+        };
+        assert_eq!(run("let x = ;").error, Some(ScriptError::Parse));
+        assert_eq!(run("throw \"boom\";").error, Some(ScriptError::Runtime));
+        assert_eq!(run("let x = 1;").error, None);
+    }
+
+    #[cfg(feature = "lua-script")]
+    #[test]
+    fn test_lua_error_kinds() {
+        let run = |script| {
+            run_script(
+                script,
+                ScriptType::Lua,
+                Arc::new(PathBuf::from(".")),
+                &ScriptConfig::default(),
+            )
+        };
+        assert_eq!(run("local x = = 1").error, Some(ScriptError::Parse));
+        assert_eq!(run("error('boom')").error, Some(ScriptError::Runtime));
+        assert_eq!(run("local x = 1").error, None);
+    }
+
     #[test]
     fn test_resolve_script_type_fallback() {
         let config = ScriptConfig::default();
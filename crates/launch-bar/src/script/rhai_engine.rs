@@ -2,15 +2,19 @@
 
 use std::path::PathBuf;
 use std::process::Command;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use arboard::Clipboard;
 use rhai::{Engine, Scope};
 
-use super::ScriptResult;
+use super::clipboard_stack::{clipboard_pop, clipboard_push};
+use super::files::{read_file, read_file_lines, resolve_path};
+use super::{ScriptConfig, ScriptError, ScriptResult};
+use crate::platform::{detach_command, notify};
 
 /// Create a Rhai engine with registered functions
-fn create_engine(cwd: Arc<PathBuf>) -> Engine {
+fn create_engine(cwd: Arc<PathBuf>, config: &ScriptConfig) -> Engine {
     let mut engine = Engine::new();
 
     // clipboard() -> String
@@ -27,6 +31,12 @@ fn create_engine(cwd: Arc<PathBuf>) -> Engine {
             .is_ok()
     });
 
+    // clipboard_push() -> bool
+    engine.register_fn("clipboard_push", clipboard_push);
+
+    // clipboard_pop() -> String
+    engine.register_fn("clipboard_pop", clipboard_pop);
+
     // shell(cmd) -> String
     let cwd_for_shell = Arc::clone(&cwd);
     engine.register_fn("shell", move |cmd: String| -> String {
@@ -42,42 +52,40 @@ fn create_engine(cwd: Arc<PathBuf>) -> Engine {
 
     // shell_spawn(cmd) -> bool
     let cwd_for_spawn = Arc::clone(&cwd);
+    let detach = config.detach;
     engine.register_fn("shell_spawn", move |cmd: String| -> bool {
-        Command::new("sh")
+        let mut command = Command::new("sh");
+        command
             .args(["-c", &cmd])
-            .current_dir(cwd_for_spawn.as_ref())
-            .spawn()
-            .is_ok()
-    });
-
-    // claude(prompt) -> String
-    let cwd_for_claude = Arc::clone(&cwd);
-    engine.register_fn("claude", move |prompt: String| -> String {
-        let output = Command::new("claude")
-            .args(["-p", &prompt])
-            .current_dir(cwd_for_claude.as_ref())
-            .output();
-        match output {
-            Ok(out) => String::from_utf8_lossy(&out.stdout).to_string(),
-            Err(e) => format!("[ERROR:claude] {}", e),
+            .current_dir(cwd_for_spawn.as_ref());
+        if detach {
+            detach_command(&mut command);
         }
+        command.spawn().is_ok()
     });
 
+    // claude(prompt) / ai(prompt) -> String
+    let ai = Arc::new(config.ai.clone());
+    for name in ["claude", "ai"] {
+        let ai = Arc::clone(&ai);
+        let cwd_for_ai = Arc::clone(&cwd);
+        engine.register_fn(name, move |prompt: String| -> String {
+            ai.run(&prompt, &cwd_for_ai)
+                .unwrap_or_else(|e| format!("[ERROR:{}] {}", name, e))
+        });
+    }
+
     // notify(message)
-    #[cfg(target_os = "macos")]
-    engine.register_fn("notify", |msg: String| {
-        let escaped = msg
-            .replace("\\", "\\\\")
-            .replace("\"", "\\\"")
-            .replace("\n", " ")
-            .replace("\r", "");
-        let script = format!(r#"display alert "Launch Bar" message "{}""#, escaped);
-        let _ = Command::new("osascript").args(["-e", &script]).spawn();
-    });
+    engine.register_fn("notify", |msg: String| notify(&msg));
 
-    #[cfg(not(target_os = "macos"))]
-    engine.register_fn("notify", |msg: String| {
-        eprintln!("[notify] {}", msg);
+    // watcher_pause() / watcher_resume()
+    let paused = Arc::clone(&config.watcher_paused);
+    engine.register_fn("watcher_pause", move || {
+        paused.store(true, Ordering::SeqCst)
+    });
+    let paused = Arc::clone(&config.watcher_paused);
+    engine.register_fn("watcher_resume", move || {
+        paused.store(false, Ordering::SeqCst)
     });
 
     // open(path)
@@ -97,42 +105,57 @@ fn create_engine(cwd: Arc<PathBuf>) -> Engine {
 
     // read_file(path) -> String
     let cwd_for_read = Arc::clone(&cwd);
+    let max_bytes = config.read_file_max_bytes;
     engine.register_fn("read_file", move |path: String| -> String {
-        let full_path = if path.starts_with('/') {
-            PathBuf::from(&path)
-        } else {
-            cwd_for_read.join(&path)
-        };
-        std::fs::read_to_string(&full_path)
-            .unwrap_or_else(|e| format!("[ERROR:read_file] {}: {}", path, e))
+        read_file(&cwd_for_read, &path, max_bytes)
     });
 
+    // read_file_lines(path, start, count) -> String
+    let cwd_for_lines = Arc::clone(&cwd);
+    engine.register_fn(
+        "read_file_lines",
+        move |path: String, start: i64, count: i64| -> String {
+            read_file_lines(&cwd_for_lines, &path, start, count)
+        },
+    );
+
     // write_file(path, content) -> bool
     engine.register_fn("write_file", move |path: String, content: String| -> bool {
-        let full_path = if path.starts_with('/') {
-            PathBuf::from(path)
-        } else {
-            cwd.join(path)
-        };
-        std::fs::write(full_path, content).is_ok()
+        std::fs::write(resolve_path(&cwd, &path), content).is_ok()
     });
 
     engine
 }
 
-/// Execute a Rhai script
-pub fn run(script: &str, cwd: Arc<PathBuf>) -> ScriptResult {
-    let engine = create_engine(cwd);
+/// Execute a Rhai script, with the preset prelude (if any) merged in front
+///
+/// The two are compiled separately so error positions refer to the right source.
+pub fn run(
+    script: &str,
+    prelude: Option<&str>,
+    cwd: Arc<PathBuf>,
+    config: &ScriptConfig,
+) -> ScriptResult {
+    let engine = create_engine(cwd, config);
     let mut scope = Scope::new();
 
-    match engine.run_with_scope(&mut scope, script) {
-        Ok(_) => ScriptResult {
-            success: true,
-            message: "Script completed".to_string(),
-        },
-        Err(e) => ScriptResult {
-            success: false,
-            message: format!("Script error: {}", e),
-        },
+    let prelude = match prelude.map(|prelude| engine.compile(prelude)).transpose() {
+        Ok(prelude) => prelude,
+        Err(e) => {
+            return ScriptResult::failed(ScriptError::Parse, format!("Prelude error: {}", e));
+        }
+    };
+    let ast = match engine.compile(script) {
+        Ok(ast) => ast,
+        Err(e) => return ScriptResult::failed(ScriptError::Parse, format!("Script error: {}", e)),
+    };
+    let ast = match prelude {
+        Some(prelude) => prelude.merge(&ast),
+        None => ast,
+    };
+
+    match engine.run_ast_with_scope(&mut scope, &ast) {
+        Ok(_) => ScriptResult::completed(),
+        Err(e) => ScriptResult::failed(ScriptError::Runtime, format!("Script error: {}", e)),
     }
 }
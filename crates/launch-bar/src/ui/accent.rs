@@ -0,0 +1,83 @@
+//! Accent line reflecting the last command result (`accent_from_result`)
+
+use std::time::Duration;
+
+use eframe::egui;
+
+use super::colors::Palette;
+
+/// How long the result color is shown at full strength
+pub const RESULT_ACCENT_HOLD: Duration = Duration::from_secs(10);
+
+/// Blend back to the preset color over this long after the hold
+pub const RESULT_ACCENT_FADE: Duration = Duration::from_secs(2);
+
+/// Accent color for the most recent result, or None once it has faded out
+///
+/// `last` is the latest result (`true` = success) and the time since it
+/// finished.
+pub fn result_accent(
+    preset: egui::Color32,
+    last: Option<(bool, Duration)>,
+    palette: &Palette,
+) -> Option<egui::Color32> {
+    let (success, elapsed) = last?;
+    let color = if success {
+        palette.success_underline
+    } else {
+        palette.error_underline
+    };
+    let fading = elapsed.saturating_sub(RESULT_ACCENT_HOLD);
+    if fading >= RESULT_ACCENT_FADE {
+        return None;
+    }
+    let t = fading.as_secs_f32() / RESULT_ACCENT_FADE.as_secs_f32();
+    Some(color.lerp_to_gamma(preset, t))
+}
+
+/// Whether a result `elapsed` ago is blending back to the preset color
+pub fn result_accent_fading(elapsed: Duration) -> bool {
+    elapsed >= RESULT_ACCENT_HOLD && elapsed < RESULT_ACCENT_HOLD + RESULT_ACCENT_FADE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRESET: egui::Color32 = egui::Color32::from_rgb(255, 112, 67);
+
+    fn accent(last: Option<(bool, Duration)>) -> Option<egui::Color32> {
+        result_accent(PRESET, last, &Palette::DARK)
+    }
+
+    #[test]
+    fn test_result_accent_selection() {
+        assert_eq!(accent(None), None);
+        assert_eq!(
+            accent(Some((true, Duration::ZERO))),
+            Some(Palette::DARK.success_underline)
+        );
+        assert_eq!(
+            accent(Some((false, Duration::from_secs(5)))),
+            Some(Palette::DARK.error_underline)
+        );
+        // Gone after hold + fade
+        assert_eq!(
+            accent(Some((false, RESULT_ACCENT_HOLD + RESULT_ACCENT_FADE))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_result_accent_fades_to_preset() {
+        let halfway = RESULT_ACCENT_HOLD + RESULT_ACCENT_FADE / 2;
+        let color = accent(Some((false, halfway))).unwrap();
+        assert_ne!(color, Palette::DARK.error_underline);
+        assert_ne!(color, PRESET);
+        assert!(result_accent_fading(halfway));
+        assert!(!result_accent_fading(Duration::from_secs(1)));
+        assert!(!result_accent_fading(
+            RESULT_ACCENT_HOLD + RESULT_ACCENT_FADE
+        ));
+    }
+}
@@ -0,0 +1,78 @@
+//! Mouse passthrough over the bar's empty regions
+
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+
+/// How long passthrough stays on before input is re-enabled to re-sample the pointer
+pub const CLICK_THROUGH_RECHECK: Duration = Duration::from_millis(500);
+
+/// Tracks whether the window currently lets clicks pass through
+///
+/// While passthrough is on the window receives no pointer events, so it is
+/// switched off again after [`CLICK_THROUGH_RECHECK`] to see where the pointer is.
+#[derive(Debug, Default)]
+pub struct ClickThrough {
+    enabled_at: Option<Instant>,
+}
+
+impl ClickThrough {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decide this frame's passthrough state, returning the new state when it changes
+    pub fn update(&mut self, pointer_over_empty: bool, now: Instant) -> Option<bool> {
+        match self.enabled_at {
+            Some(at) if now.saturating_duration_since(at) >= CLICK_THROUGH_RECHECK => {
+                self.enabled_at = None;
+                Some(false)
+            }
+            Some(_) => None,
+            None if pointer_over_empty => {
+                self.enabled_at = Some(now);
+                Some(true)
+            }
+            None => None,
+        }
+    }
+
+    /// Turn passthrough off, returning true if it was on
+    pub fn reset(&mut self) -> bool {
+        self.enabled_at.take().is_some()
+    }
+}
+
+/// Whether a pointer position falls outside every interactive region
+pub fn is_over_empty(pointer: Option<egui::Pos2>, interactive: &[egui::Rect]) -> bool {
+    pointer.is_some_and(|pos| !interactive.iter().any(|r| r.contains(pos)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_click_through_cycles_over_empty_area() {
+        let start = Instant::now();
+        let mut click_through = ClickThrough::new();
+
+        assert_eq!(click_through.update(false, start), None);
+        assert_eq!(click_through.update(true, start), Some(true));
+        // Stays on until the recheck interval passes
+        assert_eq!(click_through.update(false, start), None);
+        assert_eq!(
+            click_through.update(true, start + CLICK_THROUGH_RECHECK),
+            Some(false)
+        );
+        assert!(!click_through.reset());
+    }
+
+    #[test]
+    fn test_is_over_empty() {
+        let button = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(40.0, 40.0));
+        assert!(!is_over_empty(Some(egui::pos2(10.0, 10.0)), &[button]));
+        assert!(is_over_empty(Some(egui::pos2(60.0, 10.0)), &[button]));
+        assert!(!is_over_empty(None, &[button]));
+    }
+}
@@ -1,21 +1,150 @@
 //! Color utilities and constants
 
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
+use std::path::Path;
 
+use super::image_color::{image_color, IMAGE_COLOR_PREFIX};
+use crate::config::ColorConfig;
 use eframe::egui;
 
-/// Color constants for UI elements
-pub mod palette {
-    use eframe::egui;
-
-    pub const BUTTON_ICON: egui::Color32 = egui::Color32::from_rgb(200, 200, 200);
-    pub const STATUS_TEXT: egui::Color32 = egui::Color32::from_rgb(200, 200, 200);
-    pub const PRESET_LABEL: egui::Color32 = egui::Color32::from_rgb(150, 150, 150);
-    pub const RUNNING_ICON: egui::Color32 = egui::Color32::from_rgb(255, 200, 100);
-    pub const SUCCESS_UNDERLINE: egui::Color32 = egui::Color32::from_rgb(100, 200, 100);
-    pub const ERROR_UNDERLINE: egui::Color32 = egui::Color32::from_rgb(255, 100, 100);
-    pub const ERROR_TEXT: egui::Color32 = egui::Color32::from_rgb(255, 200, 200);
-    pub const BASE_BG: egui::Color32 = egui::Color32::from_rgb(26, 26, 30);
+/// Colors and line widths for UI elements, chosen from `theme` / `high_contrast`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub command_icon: egui::Color32,
+    pub button_icon: egui::Color32,
+    pub status_message: egui::Color32,
+    pub status_text: egui::Color32,
+    pub preset_label: egui::Color32,
+    pub running_icon: egui::Color32,
+    pub disabled_icon: egui::Color32,
+    pub queued_icon: egui::Color32,
+    pub success_underline: egui::Color32,
+    pub error_underline: egui::Color32,
+    pub error_text: egui::Color32,
+    pub badge_bg: egui::Color32,
+    pub base_bg: egui::Color32,
+    /// Width of the preset-colored line along the top edge
+    pub accent_width: f32,
+    /// Width of the success/failure line under a command icon
+    pub underline_width: f32,
+}
+
+impl Palette {
+    pub const DARK: Palette = Palette {
+        command_icon: egui::Color32::WHITE,
+        button_icon: egui::Color32::from_rgb(200, 200, 200),
+        status_message: egui::Color32::WHITE,
+        status_text: egui::Color32::from_rgb(200, 200, 200),
+        preset_label: egui::Color32::from_rgb(150, 150, 150),
+        running_icon: egui::Color32::from_rgb(255, 200, 100),
+        disabled_icon: egui::Color32::from_rgb(90, 90, 90),
+        queued_icon: egui::Color32::from_rgb(150, 130, 90),
+        success_underline: egui::Color32::from_rgb(100, 200, 100),
+        error_underline: egui::Color32::from_rgb(255, 100, 100),
+        error_text: egui::Color32::from_rgb(255, 200, 200),
+        badge_bg: egui::Color32::from_rgb(220, 60, 60),
+        base_bg: egui::Color32::from_rgb(26, 26, 30),
+        accent_width: 3.0,
+        underline_width: 2.0,
+    };
+
+    /// Dark theme with white text, brighter status colors and thicker lines
+    pub const DARK_HIGH_CONTRAST: Palette = Palette {
+        command_icon: egui::Color32::WHITE,
+        button_icon: egui::Color32::WHITE,
+        status_message: egui::Color32::WHITE,
+        status_text: egui::Color32::WHITE,
+        preset_label: egui::Color32::from_rgb(230, 230, 230),
+        running_icon: egui::Color32::from_rgb(255, 230, 60),
+        disabled_icon: egui::Color32::from_rgb(140, 140, 140),
+        queued_icon: egui::Color32::from_rgb(230, 190, 110),
+        success_underline: egui::Color32::from_rgb(90, 255, 90),
+        error_underline: egui::Color32::from_rgb(255, 120, 120),
+        error_text: egui::Color32::from_rgb(255, 225, 225),
+        badge_bg: egui::Color32::from_rgb(230, 30, 30),
+        base_bg: egui::Color32::from_rgb(26, 26, 30),
+        accent_width: 5.0,
+        underline_width: 3.0,
+    };
+
+    pub const LIGHT: Palette = Palette {
+        command_icon: egui::Color32::from_rgb(30, 30, 34),
+        button_icon: egui::Color32::from_rgb(70, 70, 75),
+        status_message: egui::Color32::from_rgb(30, 30, 34),
+        status_text: egui::Color32::from_rgb(70, 70, 75),
+        preset_label: egui::Color32::from_rgb(110, 110, 115),
+        running_icon: egui::Color32::from_rgb(200, 120, 0),
+        disabled_icon: egui::Color32::from_rgb(180, 180, 180),
+        queued_icon: egui::Color32::from_rgb(150, 120, 60),
+        success_underline: egui::Color32::from_rgb(40, 150, 40),
+        error_underline: egui::Color32::from_rgb(210, 50, 50),
+        error_text: egui::Color32::from_rgb(170, 30, 30),
+        badge_bg: egui::Color32::from_rgb(220, 60, 60),
+        base_bg: egui::Color32::from_rgb(242, 242, 245),
+        accent_width: 3.0,
+        underline_width: 2.0,
+    };
+
+    /// Light theme with black text, deeper status colors and thicker lines
+    pub const LIGHT_HIGH_CONTRAST: Palette = Palette {
+        command_icon: egui::Color32::BLACK,
+        button_icon: egui::Color32::BLACK,
+        status_message: egui::Color32::BLACK,
+        status_text: egui::Color32::BLACK,
+        preset_label: egui::Color32::from_rgb(40, 40, 40),
+        running_icon: egui::Color32::from_rgb(150, 80, 0),
+        disabled_icon: egui::Color32::from_rgb(120, 120, 120),
+        queued_icon: egui::Color32::from_rgb(100, 75, 20),
+        success_underline: egui::Color32::from_rgb(0, 110, 0),
+        error_underline: egui::Color32::from_rgb(170, 0, 0),
+        error_text: egui::Color32::from_rgb(130, 0, 0),
+        badge_bg: egui::Color32::from_rgb(190, 0, 0),
+        base_bg: egui::Color32::from_rgb(242, 242, 245),
+        accent_width: 5.0,
+        underline_width: 3.0,
+    };
+
+    /// Palette for `window.theme` ("dark", "light" or "system")
+    ///
+    /// "system" follows the OS appearance when known, else dark. Unknown
+    /// values are treated as dark.
+    pub fn new(theme: &str, high_contrast: bool, system: Option<egui::Theme>) -> Self {
+        let light = match theme {
+            "light" => true,
+            "system" => system == Some(egui::Theme::Light),
+            _ => false,
+        };
+        match (light, high_contrast) {
+            (false, false) => Self::DARK,
+            (false, true) => Self::DARK_HIGH_CONTRAST,
+            (true, false) => Self::LIGHT,
+            (true, true) => Self::LIGHT_HIGH_CONTRAST,
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+impl Palette {
+    /// This palette with the colors set in `colors`; invalid entries keep the default
+    pub fn with_colors(mut self, colors: &ColorConfig) -> Self {
+        let parse = |value: &Option<String>| value.as_deref().and_then(parse_hex_color);
+        if let Some(color) = parse(&colors.running) {
+            self.running_icon = color;
+        }
+        if let Some(color) = parse(&colors.success) {
+            self.success_underline = color;
+        }
+        if let Some(color) = parse(&colors.error) {
+            self.error_underline = color;
+        }
+        self
+    }
 }
 
 /// Parse a hex color string (e.g., "#FF7043" or "FF7043")
@@ -31,10 +160,39 @@ pub fn parse_hex_color(hex: &str) -> Option<egui::Color32> {
     }
 }
 
+/// A preset's `base_color`: a hex color, or `image:<path>` for the dominant
+/// color of an image (relative to `dir`)
+pub fn preset_color(value: &str, dir: &Path) -> Option<egui::Color32> {
+    match value.strip_prefix(IMAGE_COLOR_PREFIX) {
+        Some(path) => image_color(path.trim(), dir),
+        None => parse_hex_color(value),
+    }
+}
+
+/// Dot color for a command category: its `[categories]` entry (names match
+/// case-insensitively), or `fallback` if missing or not a valid hex color
+pub fn category_color(
+    category: &str,
+    categories: &HashMap<String, String>,
+    fallback: egui::Color32,
+) -> egui::Color32 {
+    categories
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(category))
+        .and_then(|(_, hex)| parse_hex_color(hex))
+        .unwrap_or(fallback)
+}
+
 /// Vary color hue based on path hash for visual distinction
-pub fn vary_color_by_path(base_color: egui::Color32, path: &str) -> egui::Color32 {
+///
+/// A non-zero `hue_offset` (stored per directory) re-seeds the hash, so a
+/// project can be given a different but stable color.
+pub fn vary_color_by_path(base_color: egui::Color32, path: &str, hue_offset: u32) -> egui::Color32 {
     let mut hasher = std::collections::hash_map::DefaultHasher::new();
     path.hash(&mut hasher);
+    if hue_offset != 0 {
+        hue_offset.hash(&mut hasher);
+    }
     let hash = hasher.finish();
 
     // Convert to HSV, shift hue slightly, convert back
@@ -94,3 +252,166 @@ fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (u8, u8, u8) {
         ((b + m) * 255.0) as u8,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Relative luminance (sRGB approximation without gamma decoding)
+    fn luminance(c: egui::Color32) -> f32 {
+        0.2126 * c.r() as f32 + 0.7152 * c.g() as f32 + 0.0722 * c.b() as f32
+    }
+
+    /// Text/status colors of a palette, by name
+    fn text_colors(p: &Palette) -> Vec<(&'static str, egui::Color32)> {
+        vec![
+            ("command_icon", p.command_icon),
+            ("button_icon", p.button_icon),
+            ("status_message", p.status_message),
+            ("status_text", p.status_text),
+            ("preset_label", p.preset_label),
+            ("error_text", p.error_text),
+        ]
+    }
+
+    #[test]
+    fn test_high_contrast_is_brighter() {
+        let normal = Palette::new("dark", false, None);
+        let high = Palette::new("dark", true, None);
+        assert_eq!(normal, Palette::default());
+        for (name, n, h) in [
+            ("button_icon", normal.button_icon, high.button_icon),
+            ("status_text", normal.status_text, high.status_text),
+            ("preset_label", normal.preset_label, high.preset_label),
+            ("running_icon", normal.running_icon, high.running_icon),
+            ("disabled_icon", normal.disabled_icon, high.disabled_icon),
+            ("queued_icon", normal.queued_icon, high.queued_icon),
+            (
+                "success_underline",
+                normal.success_underline,
+                high.success_underline,
+            ),
+            (
+                "error_underline",
+                normal.error_underline,
+                high.error_underline,
+            ),
+            ("error_text", normal.error_text, high.error_text),
+        ] {
+            assert_ne!(n, h, "{}", name);
+            assert!(luminance(h) > luminance(n), "{}", name);
+        }
+        assert!(high.accent_width > normal.accent_width);
+        assert!(high.underline_width > normal.underline_width);
+    }
+
+    #[test]
+    fn test_configured_colors_override_defaults() {
+        let colors = ColorConfig {
+            running: Some("#0072B2".to_string()),
+            success: Some("009E73".to_string()),
+            error: Some("orange".to_string()),
+        };
+        let palette = Palette::LIGHT.with_colors(&colors);
+        assert_eq!(palette.running_icon, egui::Color32::from_rgb(0, 114, 178));
+        assert_eq!(
+            palette.success_underline,
+            egui::Color32::from_rgb(0, 158, 115)
+        );
+        assert_eq!(palette.error_underline, Palette::LIGHT.error_underline);
+        assert_eq!(palette.queued_icon, Palette::LIGHT.queued_icon);
+
+        // Nothing configured: the theme's colors
+        assert_eq!(
+            Palette::DARK.with_colors(&ColorConfig::default()),
+            Palette::DARK
+        );
+    }
+
+    #[test]
+    fn test_palette_per_theme() {
+        assert_eq!(Palette::new("dark", false, None), Palette::DARK);
+        assert_eq!(Palette::new("light", false, None), Palette::LIGHT);
+        assert_eq!(
+            Palette::new("light", true, None),
+            Palette::LIGHT_HIGH_CONTRAST
+        );
+        assert_eq!(Palette::new("bogus", false, None), Palette::DARK);
+
+        // "system" follows the OS appearance, defaulting to dark
+        assert_eq!(
+            Palette::new("system", false, Some(egui::Theme::Light)),
+            Palette::LIGHT
+        );
+        assert_eq!(
+            Palette::new("system", false, Some(egui::Theme::Dark)),
+            Palette::DARK
+        );
+        assert_eq!(Palette::new("system", false, None), Palette::DARK);
+        // The OS appearance is ignored for explicit themes
+        assert_eq!(
+            Palette::new("dark", false, Some(egui::Theme::Light)),
+            Palette::DARK
+        );
+    }
+
+    #[test]
+    fn test_text_contrasts_with_background() {
+        for palette in [
+            Palette::DARK,
+            Palette::DARK_HIGH_CONTRAST,
+            Palette::LIGHT,
+            Palette::LIGHT_HIGH_CONTRAST,
+        ] {
+            let bg = luminance(palette.base_bg);
+            for (name, color) in text_colors(&palette) {
+                assert!((luminance(color) - bg).abs() > 100.0, "{}", name);
+            }
+        }
+        // High contrast widens the gap on a light background too
+        let bg = luminance(Palette::LIGHT.base_bg);
+        for ((name, normal), (_, high)) in text_colors(&Palette::LIGHT)
+            .into_iter()
+            .zip(text_colors(&Palette::LIGHT_HIGH_CONTRAST))
+        {
+            assert!(
+                (luminance(high) - bg).abs() >= (luminance(normal) - bg).abs(),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_category_color() {
+        let categories = HashMap::from([
+            ("deploy".to_string(), "#EF4444".to_string()),
+            ("broken".to_string(), "red".to_string()),
+        ]);
+        let fallback = Palette::DARK.preset_label;
+        assert_eq!(
+            category_color("Deploy", &categories, fallback),
+            egui::Color32::from_rgb(0xef, 0x44, 0x44)
+        );
+        assert_eq!(category_color("test", &categories, fallback), fallback);
+        assert_eq!(category_color("broken", &categories, fallback), fallback);
+    }
+
+    #[test]
+    fn test_vary_color_by_path_applies_hue_offset() {
+        let base = egui::Color32::from_rgb(200, 60, 60);
+        let plain = vary_color_by_path(base, "/work/app", 0);
+        assert_eq!(plain, vary_color_by_path(base, "/work/app", 0));
+
+        // Offsets give stable colors; some differ from the plain one
+        let offset = vary_color_by_path(base, "/work/app", 7);
+        assert_eq!(offset, vary_color_by_path(base, "/work/app", 7));
+        assert!((1..=5).any(|n| vary_color_by_path(base, "/work/app", n) != plain));
+
+        // Only the hue moves
+        let [r, g, b, _] = offset.to_array();
+        let (_, s, v) = rgb_to_hsv(r, g, b);
+        let (_, base_s, base_v) = rgb_to_hsv(200, 60, 60);
+        assert!((s - base_s).abs() < 0.02 && (v - base_v).abs() < 0.02);
+    }
+}
@@ -0,0 +1,131 @@
+//! Time-based fades between two states
+
+use std::time::{Duration, Instant};
+
+/// Linear fade of a value in [0.0, 1.0] toward a target over a fixed duration
+#[derive(Debug, Clone)]
+pub struct Fade {
+    from: f32,
+    target: f32,
+    started: Instant,
+    duration: Duration,
+}
+
+impl Fade {
+    /// Create a fade resting at `value`
+    pub fn new(value: f32, duration: Duration, now: Instant) -> Self {
+        Self {
+            from: value,
+            target: value,
+            started: now,
+            duration,
+        }
+    }
+
+    /// Change the target, continuing from the current value
+    pub fn set_target(&mut self, target: f32, now: Instant) {
+        if target != self.target {
+            self.from = self.value(now);
+            self.target = target;
+            self.started = now;
+        }
+    }
+
+    /// Current value
+    pub fn value(&self, now: Instant) -> f32 {
+        if self.duration.is_zero() {
+            return self.target;
+        }
+        let t = (now.saturating_duration_since(self.started).as_secs_f32()
+            / self.duration.as_secs_f32())
+        .min(1.0);
+        self.from + (self.target - self.from) * t
+    }
+
+    /// Current value eased in and out (smoothstep), for gentler transitions
+    pub fn eased_value(&self, now: Instant) -> f32 {
+        let t = self.value(now);
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    /// Returns true while the value is still moving toward the target
+    pub fn is_animating(&self, now: Instant) -> bool {
+        self.value(now) != self.target
+    }
+}
+
+/// Linear interpolation between `a` (t = 0) and `b` (t = 1)
+pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DURATION: Duration = Duration::from_millis(200);
+
+    #[test]
+    fn test_fade_out_and_back() {
+        let t0 = Instant::now();
+        let mut fade = Fade::new(1.0, DURATION, t0);
+        assert_eq!(fade.value(t0), 1.0);
+        assert!(!fade.is_animating(t0));
+
+        // Blur: fade toward 0
+        fade.set_target(0.0, t0);
+        assert!((fade.value(t0 + Duration::from_millis(100)) - 0.5).abs() < 1e-4);
+        assert_eq!(fade.value(t0 + DURATION), 0.0);
+        assert!(fade.is_animating(t0 + Duration::from_millis(100)));
+        assert!(!fade.is_animating(t0 + DURATION));
+
+        // Refocus halfway through a fade continues from the current value
+        let t1 = t0 + DURATION;
+        fade.set_target(1.0, t1);
+        let mid = t1 + Duration::from_millis(50);
+        fade.set_target(0.0, mid);
+        assert!((fade.value(mid) - 0.25).abs() < 1e-4);
+        assert!(fade.value(mid + Duration::from_millis(100)) < 0.25);
+    }
+
+    #[test]
+    fn test_hover_opacity_eases_in_and_out() {
+        let (opacity, hover_opacity) = (0.4, 1.0);
+        let t0 = Instant::now();
+        let mut hover = Fade::new(0.0, DURATION, t0);
+        let at = |hover: &Fade, ms: u64| {
+            lerp(
+                opacity,
+                hover_opacity,
+                hover.eased_value(t0 + Duration::from_millis(ms)),
+            )
+        };
+        assert_eq!(at(&hover, 0), 0.4);
+
+        // Pointer enters: slow start, fast middle, slow finish
+        hover.set_target(1.0, t0);
+        let (early, mid, late) = (at(&hover, 20), at(&hover, 100), at(&hover, 180));
+        assert!(early - 0.4 < 0.6 * 0.1 / 2.0);
+        assert!((mid - 0.7).abs() < 1e-4);
+        assert!(1.0 - late < 0.6 * 0.1 / 2.0);
+        assert_eq!(at(&hover, 200), 1.0);
+
+        // Pointer leaves, then returns halfway: reverses from there
+        let t1 = t0 + Duration::from_millis(200);
+        hover.set_target(0.0, t1);
+        assert!((at(&hover, 300) - 0.7).abs() < 1e-4);
+        hover.set_target(1.0, t1 + Duration::from_millis(100));
+        assert!((at(&hover, 300) - 0.7).abs() < 1e-4);
+        assert!(at(&hover, 350) > 0.7);
+        assert!(hover.is_animating(t0 + Duration::from_millis(450)));
+        assert_eq!(at(&hover, 500), 1.0);
+    }
+
+    #[test]
+    fn test_blur_opacity_interpolation() {
+        let (blur, focused) = (0.3, 0.9);
+        assert_eq!(lerp(blur, focused, 1.0), 0.9);
+        assert_eq!(lerp(blur, focused, 0.0), 0.3);
+        assert!((lerp(blur, focused, 0.5) - 0.6).abs() < 1e-6);
+    }
+}
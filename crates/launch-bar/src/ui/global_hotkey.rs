@@ -0,0 +1,162 @@
+//! System-wide hotkeys (`global_hotkey`) through the `global-hotkey` crate
+//!
+//! Supported on Windows, macOS and X11; the manager must be created on the
+//! main thread, where eframe runs its event loop.
+
+use std::sync::mpsc::{self, Receiver};
+
+use eframe::egui::{self, Key, KeyboardShortcut};
+use global_hotkey::hotkey::{Code, HotKey, Modifiers, CMD_OR_CTRL};
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+
+/// Registered system-wide hotkeys and the commands they run
+pub struct GlobalHotkeys {
+    manager: GlobalHotKeyManager,
+    registered: Vec<(HotKey, usize)>,
+    events: Receiver<GlobalHotKeyEvent>,
+}
+
+impl GlobalHotkeys {
+    /// Start listening for hotkeys, waking `ctx` when one is pressed
+    ///
+    /// Returns None with a warning where the platform can't register them
+    /// (e.g. Wayland). Create it once: the crate's event handler is global.
+    pub fn new(ctx: &egui::Context) -> Option<Self> {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            eprintln!("[warn] Global hotkeys only fire for X11 apps under Wayland");
+        }
+        let manager = match GlobalHotKeyManager::new() {
+            Ok(manager) => manager,
+            Err(e) => {
+                eprintln!("[warn] Global hotkeys unavailable: {}", e);
+                return None;
+            }
+        };
+        let (tx, events) = mpsc::channel();
+        let ctx = ctx.clone();
+        // The bar may be unfocused and idle, so wake it to handle the press
+        GlobalHotKeyEvent::set_event_handler(Some(move |event| {
+            if tx.send(event).is_ok() {
+                ctx.request_repaint();
+            }
+        }));
+        Some(Self {
+            manager,
+            registered: Vec::new(),
+            events,
+        })
+    }
+
+    /// Replace the registered hotkeys with `bindings` (from `global_hotkey_bindings`)
+    ///
+    /// Combos the OS or another app already holds are skipped with a warning.
+    pub fn register(&mut self, bindings: &[(KeyboardShortcut, usize)]) {
+        for (hotkey, _) in self.registered.drain(..) {
+            let _ = self.manager.unregister(hotkey);
+        }
+        for &(shortcut, index) in bindings {
+            let Some(hotkey) = to_hotkey(shortcut) else {
+                eprintln!(
+                    "[warn] Key {:?} can't be used as a global hotkey",
+                    shortcut.logical_key
+                );
+                continue;
+            };
+            match self.manager.register(hotkey) {
+                Ok(()) => self.registered.push((hotkey, index)),
+                Err(e) => eprintln!("[warn] Global hotkey {}: {}", hotkey, e),
+            }
+        }
+    }
+
+    /// Commands whose hotkey was pressed since the last call
+    pub fn pressed(&self) -> Vec<usize> {
+        self.events
+            .try_iter()
+            .filter(|event| event.state == HotKeyState::Pressed)
+            .filter_map(|event| {
+                self.registered
+                    .iter()
+                    .find(|(hotkey, _)| hotkey.id() == event.id)
+                    .map(|&(_, index)| index)
+            })
+            .collect()
+    }
+}
+
+impl Drop for GlobalHotkeys {
+    fn drop(&mut self) {
+        for (hotkey, _) in self.registered.drain(..) {
+            let _ = self.manager.unregister(hotkey);
+        }
+    }
+}
+
+/// The crate's hotkey for an egui shortcut (`cmd` is Cmd on macOS, Ctrl elsewhere)
+fn to_hotkey(shortcut: KeyboardShortcut) -> Option<HotKey> {
+    let modifiers = shortcut.modifiers;
+    let mut mods = Modifiers::empty();
+    for (on, modifier) in [
+        (modifiers.command || modifiers.mac_cmd, CMD_OR_CTRL),
+        (modifiers.ctrl, Modifiers::CONTROL),
+        (modifiers.alt, Modifiers::ALT),
+        (modifiers.shift, Modifiers::SHIFT),
+    ] {
+        if on {
+            mods |= modifier;
+        }
+    }
+    Some(HotKey::new(Some(mods), key_code(shortcut.logical_key)?))
+}
+
+/// Physical key code for an egui key; None for keys without one (`?`, `Copy`, ...)
+fn key_code(key: Key) -> Option<Code> {
+    let name = format!("{:?}", key);
+    let name = match key {
+        Key::OpenBracket => "BracketLeft".to_string(),
+        Key::CloseBracket => "BracketRight".to_string(),
+        Key::Backtick => "Backquote".to_string(),
+        Key::Equals => "Equal".to_string(),
+        // Letters are `A`..`Z`, digits `Num0`..`Num9`
+        _ if name.len() == 1 => format!("Key{}", name),
+        _ => match name.strip_prefix("Num") {
+            Some(digit) => format!("Digit{}", digit),
+            None => name,
+        },
+    };
+    name.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::hotkey::parse_hotkey;
+
+    #[test]
+    fn test_to_hotkey() {
+        let hotkey = |spec: &str| to_hotkey(parse_hotkey(spec).unwrap());
+        assert_eq!(
+            hotkey("ctrl+alt+d"),
+            Some(HotKey::new(
+                Some(Modifiers::CONTROL | Modifiers::ALT),
+                Code::KeyD
+            ))
+        );
+        assert_eq!(
+            hotkey("cmd+shift+5"),
+            Some(HotKey::new(
+                Some(CMD_OR_CTRL | Modifiers::SHIFT),
+                Code::Digit5
+            ))
+        );
+        assert_eq!(
+            hotkey("alt+F12"),
+            Some(HotKey::new(Some(Modifiers::ALT), Code::F12))
+        );
+        assert_eq!(
+            hotkey("ctrl+["),
+            Some(HotKey::new(Some(Modifiers::CONTROL), Code::BracketLeft))
+        );
+        assert_eq!(hotkey("ctrl+space").map(|h| h.key), Some(Code::Space));
+    }
+}
@@ -0,0 +1,217 @@
+//! Keyboard shortcuts bound to commands (`hotkey = "cmd+shift+d"`, `key = "3"`,
+//! `global_hotkey = "ctrl+alt+d"`)
+
+use eframe::egui::{Key, KeyboardShortcut, Modifiers};
+
+use crate::config::CommandConfig;
+
+/// Parse a hotkey like `cmd+shift+d`, `ctrl+alt+F5` or `alt+space`
+///
+/// `cmd` is Cmd on macOS and Ctrl elsewhere; `ctrl`, `alt` (`option`) and
+/// `shift` are literal. Names are case-insensitive.
+pub fn parse_hotkey(spec: &str) -> Result<KeyboardShortcut, String> {
+    let mut modifiers = Modifiers::NONE;
+    let mut key = None;
+    for part in spec.split('+').map(str::trim) {
+        match part.to_ascii_lowercase().as_str() {
+            "cmd" | "command" | "mod" => modifiers.command = true,
+            "ctrl" | "control" => modifiers.ctrl = true,
+            "alt" | "option" => modifiers.alt = true,
+            "shift" => modifiers.shift = true,
+            "" => return Err(format!("empty key in hotkey '{}'", spec)),
+            name => {
+                if key.is_some() {
+                    return Err(format!("hotkey '{}' has more than one key", spec));
+                }
+                key = Some(parse_key(name).ok_or_else(|| format!("unknown key '{}'", part))?);
+            }
+        }
+    }
+    let key = key.ok_or_else(|| format!("hotkey '{}' has no key", spec))?;
+    Ok(KeyboardShortcut::new(modifiers, key))
+}
+
+/// egui key for a lowercase key name (`d`, `f5`, `space`, `enter`, ...)
+fn parse_key(name: &str) -> Option<Key> {
+    let mut chars = name.chars();
+    let capitalized: String = chars
+        .next()
+        .map(|first| first.to_ascii_uppercase())
+        .into_iter()
+        .chain(chars)
+        .collect();
+    Key::from_name(name).or_else(|| Key::from_name(&capitalized))
+}
+
+/// Shortcuts of `commands` with the index they run, most specific first
+///
+/// Invalid and duplicate hotkeys are skipped with a warning (the first
+/// command keeps a duplicated combo).
+pub fn hotkey_bindings(commands: &[CommandConfig]) -> Vec<(KeyboardShortcut, usize)> {
+    bindings(commands, "Hotkey", |cmd| cmd.hotkey.as_deref())
+}
+
+/// System-wide shortcuts (`global_hotkey`) of `commands` with the index they run
+///
+/// Skips invalid and duplicate combos like `hotkey_bindings`; registering
+/// them is up to `ui::global_hotkey` (the `global-hotkey` feature).
+pub fn global_hotkey_bindings(commands: &[CommandConfig]) -> Vec<(KeyboardShortcut, usize)> {
+    bindings(commands, "Global hotkey", |cmd| {
+        cmd.global_hotkey.as_deref()
+    })
+}
+
+fn bindings(
+    commands: &[CommandConfig],
+    label: &str,
+    spec_of: impl Fn(&CommandConfig) -> Option<&str>,
+) -> Vec<(KeyboardShortcut, usize)> {
+    let mut bindings: Vec<(KeyboardShortcut, usize)> = Vec::new();
+    for (index, cmd) in commands.iter().enumerate() {
+        let Some(spec) = spec_of(cmd) else {
+            continue;
+        };
+        match parse_hotkey(spec) {
+            Ok(shortcut) => {
+                if let Some(&(_, first)) = bindings.iter().find(|(s, _)| *s == shortcut) {
+                    eprintln!(
+                        "[warn] {} '{}' of '{}' is already used by '{}'",
+                        label, spec, cmd.name, commands[first].name
+                    );
+                } else {
+                    bindings.push((shortcut, index));
+                }
+            }
+            Err(e) => eprintln!("[warn] Command '{}': {}", cmd.name, e),
+        }
+    }
+    // Shortcuts match logically (extra Shift/Alt ignored), so try Cmd+Shift+D before Cmd+D
+    bindings.sort_by_key(|(shortcut, _)| std::cmp::Reverse(modifier_count(shortcut.modifiers)));
+    bindings
+}
+
+/// Command run by typing `pressed` with no modifier
+///
+/// A command whose `key` is `pressed` wins; otherwise `1`-`9` pick the command
+/// at that position, unless it was given a `key` of its own.
+pub fn key_command(commands: &[CommandConfig], pressed: char) -> Option<usize> {
+    if let Some(index) = commands.iter().position(|cmd| cmd.key == Some(pressed)) {
+        return Some(index);
+    }
+    let index = match pressed.to_digit(10)? {
+        0 => return None,
+        digit => digit as usize - 1,
+    };
+    commands
+        .get(index)
+        .filter(|cmd| cmd.key.is_none())
+        .map(|_| index)
+}
+
+fn modifier_count(modifiers: Modifiers) -> usize {
+    [
+        modifiers.command,
+        modifiers.ctrl,
+        modifiers.alt,
+        modifiers.shift,
+    ]
+    .into_iter()
+    .filter(|&m| m)
+    .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(name: &str, hotkey: Option<&str>) -> CommandConfig {
+        let mut cmd: CommandConfig =
+            toml::from_str(&format!("name = \"{}\"\ncmd = \"true\"", name)).unwrap();
+        cmd.hotkey = hotkey.map(str::to_string);
+        cmd
+    }
+
+    #[test]
+    fn test_parse_hotkey() {
+        assert_eq!(
+            parse_hotkey("cmd+shift+d").unwrap(),
+            KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::D)
+        );
+        assert_eq!(
+            parse_hotkey("Ctrl + Alt + F5").unwrap(),
+            KeyboardShortcut::new(Modifiers::CTRL | Modifiers::ALT, Key::F5)
+        );
+        assert_eq!(
+            parse_hotkey("option+space").unwrap(),
+            KeyboardShortcut::new(Modifiers::ALT, Key::Space)
+        );
+        assert!(parse_hotkey("cmd+shift").is_err());
+        assert!(parse_hotkey("cmd+a+b").is_err());
+        assert!(parse_hotkey("cmd+nope").is_err());
+        assert!(parse_hotkey("cmd++").is_err());
+    }
+
+    #[test]
+    fn test_key_command() {
+        let mut commands = vec![
+            command("Build", None),
+            command("Test", None),
+            command("Deploy", None),
+        ];
+        // Positional 1-9 without explicit keys
+        assert_eq!(key_command(&commands, '1'), Some(0));
+        assert_eq!(key_command(&commands, '3'), Some(2));
+        assert_eq!(key_command(&commands, '4'), None);
+        assert_eq!(key_command(&commands, '0'), None);
+        assert_eq!(key_command(&commands, 'x'), None);
+
+        commands[2].key = Some('1');
+        commands[0].key = Some('b');
+        // Explicit keys win over positions; remapped commands leave their slot
+        assert_eq!(key_command(&commands, '1'), Some(2));
+        assert_eq!(key_command(&commands, 'b'), Some(0));
+        assert_eq!(key_command(&commands, '2'), Some(1));
+        assert_eq!(key_command(&commands, '3'), None);
+    }
+
+    #[test]
+    fn test_hotkey_bindings() {
+        let commands = vec![
+            command("Build", Some("cmd+b")),
+            command("Test", None),
+            command("Deploy", Some("cmd+shift+d")),
+            command("Broken", Some("cmd+?!")),
+            command("Duplicate", Some("Cmd+B")),
+        ];
+        let bindings = hotkey_bindings(&commands);
+        assert_eq!(
+            bindings,
+            vec![
+                (
+                    KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::D),
+                    2
+                ),
+                (KeyboardShortcut::new(Modifiers::COMMAND, Key::B), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_global_hotkey_bindings() {
+        let mut commands = vec![
+            command("Build", Some("cmd+b")),
+            command("Deploy", None),
+            command("Test", None),
+        ];
+        commands[1].global_hotkey = Some("ctrl+alt+d".to_string());
+        commands[2].global_hotkey = Some("Ctrl+Alt+D".to_string());
+        // Focus-only `hotkey`s are not registered system-wide
+        assert_eq!(
+            global_hotkey_bindings(&commands),
+            vec![(
+                KeyboardShortcut::new(Modifiers::CTRL | Modifiers::ALT, Key::D),
+                1
+            )]
+        );
+    }
+}
@@ -1,10 +1,82 @@
 //! Icon mapping utilities
 
+use eframe::egui;
 use egui_cha_ds::icons;
 
-/// Get icon symbol from name
+/// Font family registered by `egui_cha_ds::setup_fonts`
+pub const ICON_FAMILY: &str = "icons";
+
+/// Shown when a name has no letter or digit to fall back to
+const FALLBACK_GLYPH: &str = "\u{2022}";
+
+/// Whether the icon font family is registered (checked and logged once per context)
+///
+/// Only valid once the context has run a frame.
+pub fn has_icon_font(ctx: &egui::Context) -> bool {
+    let id = egui::Id::new("launch_bar_has_icon_font");
+    if let Some(available) = ctx.data(|d| d.get_temp::<bool>(id)) {
+        return available;
+    }
+    let family = egui::FontFamily::Name(ICON_FAMILY.into());
+    let available = ctx.fonts(|f| f.definitions().families.contains_key(&family));
+    if !available {
+        eprintln!(
+            "[warn] Icon font family '{}' is not registered; showing letters instead",
+            ICON_FAMILY
+        );
+    }
+    ctx.data_mut(|d| d.insert_temp(id, available));
+    available
+}
+
+/// Text drawn instead of an icon: the first letter or digit of `name`, uppercased
+pub fn fallback_glyph(name: &str) -> String {
+    name.chars()
+        .find(|c| c.is_alphanumeric())
+        .map(|c| c.to_uppercase().collect())
+        .unwrap_or_else(|| FALLBACK_GLYPH.to_string())
+}
+
+/// Icon text in the icon font, or a glyph derived from `name` if the font is missing
+pub fn icon_text(ctx: &egui::Context, icon: &str, name: &str) -> egui::RichText {
+    if has_icon_font(ctx) {
+        egui::RichText::new(icon).family(egui::FontFamily::Name(ICON_FAMILY.into()))
+    } else {
+        egui::RichText::new(fallback_glyph(name))
+    }
+}
+
+/// Longest `icon` (in chars) drawn as-is; allows emoji with modifiers/joiners
+const MAX_LITERAL_ICON_CHARS: usize = 8;
+
+/// Text for a command button: an emoji/unicode `icon` as written, else the
+/// mapped icon (`play` if none)
+pub fn command_icon_text(ctx: &egui::Context, icon: Option<&str>, name: &str) -> egui::RichText {
+    match icon {
+        // Default font family, which has emoji; the icon font would show tofu
+        Some(literal) if is_literal_icon(literal) => egui::RichText::new(literal),
+        icon => icon_text(ctx, icon.map_or(icons::PLAY, get_icon), name),
+    }
+}
+
+/// Whether `icon` is drawn as written: a short non-ASCII string that isn't an alias
+fn is_literal_icon(icon: &str) -> bool {
+    !icon.is_ascii() && icon.chars().count() <= MAX_LITERAL_ICON_CHARS && !is_builtin_icon(icon)
+}
+
+/// Whether `name` is one of the icon aliases (see `available_icons`)
+pub fn is_builtin_icon(name: &str) -> bool {
+    builtin_icon(name).is_some()
+}
+
+/// Get icon symbol from name (`play` for unknown names)
 pub fn get_icon(name: &str) -> &'static str {
-    match name.to_lowercase().as_str() {
+    builtin_icon(name).unwrap_or(icons::PLAY)
+}
+
+/// Icon symbol for an alias
+fn builtin_icon(name: &str) -> Option<&'static str> {
+    let icon = match name.to_lowercase().as_str() {
         "house" | "home" => icons::HOUSE,
         "arrow_left" | "left" => icons::ARROW_LEFT,
         "arrow_right" | "right" => icons::ARROW_RIGHT,
@@ -57,8 +129,9 @@ pub fn get_icon(name: &str) -> &'static str {
         "package" | "cube" => icons::STACK,
         "terminal" | "console" => icons::MONITOR_PLAY,
         "code" => icons::FILE,
-        _ => icons::PLAY,
-    }
+        _ => return None,
+    };
+    Some(icon)
 }
 
 /// Get list of available icon names for documentation
@@ -97,3 +170,40 @@ pub fn available_icons() -> Vec<&'static str> {
         "package/cube",
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback_glyph_uses_first_letter() {
+        assert_eq!(fallback_glyph("build"), "B");
+        assert_eq!(fallback_glyph("  42 go"), "4");
+        assert_eq!(fallback_glyph("[dev] server"), "D");
+        assert_eq!(fallback_glyph("\u{e9}lan"), "\u{c9}");
+    }
+
+    #[test]
+    fn test_literal_icons() {
+        // Alias
+        assert!(is_builtin_icon("Build"));
+        assert!(!is_literal_icon("build"));
+        assert_eq!(get_icon("build"), icons::WRENCH);
+        // Emoji, including a ZWJ sequence
+        assert!(is_literal_icon("\u{1f680}"));
+        assert!(is_literal_icon("\u{1f469}\u{200d}\u{1f4bb}"));
+        assert!(!is_builtin_icon("\u{1f680}"));
+        // Unknown ASCII names still map to the default icon
+        assert!(!is_literal_icon("rocket"));
+        assert!(!is_builtin_icon("rocket"));
+        assert_eq!(get_icon("rocket"), icons::PLAY);
+        // Long non-ASCII text isn't an icon
+        assert!(!is_literal_icon("d\u{e9}ploiement rapide"));
+    }
+
+    #[test]
+    fn test_fallback_glyph_without_letters() {
+        assert_eq!(fallback_glyph(""), FALLBACK_GLYPH);
+        assert_eq!(fallback_glyph("-> !"), FALLBACK_GLYPH);
+    }
+}
@@ -0,0 +1,112 @@
+//! Preset colors taken from an image (`base_color = "image:logo.png"`)
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use eframe::egui;
+
+/// `base_color` prefix naming an image whose dominant color is used
+pub const IMAGE_COLOR_PREFIX: &str = "image:";
+
+/// Colors already computed per image path (None when the image couldn't be read)
+fn cache() -> &'static Mutex<HashMap<PathBuf, Option<egui::Color32>>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, Option<egui::Color32>>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Dominant color of the image at `path` (relative to `dir`), computed once per path
+pub fn image_color(path: &str, dir: &Path) -> Option<egui::Color32> {
+    let path = dir.join(path);
+    let mut cache = cache().lock().unwrap_or_else(PoisonError::into_inner);
+    *cache.entry(path).or_insert_with_key(|path| {
+        let color = dominant_color(path);
+        if color.is_none() && cfg!(feature = "image") {
+            eprintln!("[warn] base_color: cannot read image {}", path.display());
+        } else if color.is_none() {
+            eprintln!("[warn] base_color: image colors need the 'image' feature");
+        }
+        color
+    })
+}
+
+/// Most common color of an image's visible pixels
+///
+/// Pixels are grouped into coarse color buckets (3 bits per channel) and the
+/// average of the largest bucket is returned, so anti-aliased edges and
+/// gradients don't pull the result toward gray.
+#[cfg(feature = "image")]
+pub fn dominant_color(path: &Path) -> Option<egui::Color32> {
+    let image = image::open(path).ok()?.into_rgba8();
+    // Per bucket: pixel count and channel sums
+    let mut buckets: HashMap<u16, (u64, [u64; 3])> = HashMap::new();
+    for pixel in image.pixels() {
+        let [r, g, b, a] = pixel.0;
+        if a < 128 {
+            continue;
+        }
+        let key = (u16::from(r >> 5) << 6) | (u16::from(g >> 5) << 3) | u16::from(b >> 5);
+        let (count, sums) = buckets.entry(key).or_default();
+        *count += 1;
+        for (sum, channel) in sums.iter_mut().zip([r, g, b]) {
+            *sum += u64::from(channel);
+        }
+    }
+    let (count, [r, g, b]) = buckets.into_values().max_by_key(|&(count, _)| count)?;
+    let mean = |sum: u64| (sum / count) as u8;
+    Some(egui::Color32::from_rgb(mean(r), mean(g), mean(b)))
+}
+
+/// Reading images needs the `image` feature
+#[cfg(not(feature = "image"))]
+pub fn dominant_color(_path: &Path) -> Option<egui::Color32> {
+    None
+}
+
+#[cfg(all(test, feature = "image"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dominant_color_of_solid_image() {
+        let dir = std::env::temp_dir().join(format!("launch-bar-image-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("logo.png");
+        image::RgbaImage::from_pixel(8, 8, image::Rgba([200, 30, 40, 255]))
+            .save(&path)
+            .unwrap();
+
+        assert_eq!(
+            dominant_color(&path),
+            Some(egui::Color32::from_rgb(200, 30, 40))
+        );
+        assert_eq!(
+            image_color("logo.png", &dir),
+            Some(egui::Color32::from_rgb(200, 30, 40))
+        );
+        assert_eq!(dominant_color(&dir.join("missing.png")), None);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_dominant_color_ignores_transparent_and_minority_pixels() {
+        let dir = std::env::temp_dir().join(format!("launch-bar-image2-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("logo.png");
+        let mut image = image::RgbaImage::from_pixel(4, 4, image::Rgba([0, 0, 0, 0]));
+        for x in 0..4 {
+            image.put_pixel(x, 0, image::Rgba([20, 120, 220, 255]));
+            image.put_pixel(x, 1, image::Rgba([20, 120, 220, 255]));
+        }
+        image.put_pixel(0, 2, image::Rgba([255, 255, 255, 255]));
+        image.save(&path).unwrap();
+
+        assert_eq!(
+            dominant_color(&path),
+            Some(egui::Color32::from_rgb(20, 120, 220))
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,285 @@
+//! Window layout helpers
+
+use eframe::egui;
+
+/// Window size of the collapsed compact-mode dot
+pub const COMPACT_SIZE: egui::Vec2 = egui::vec2(24.0, 24.0);
+
+/// Side length of a command button
+pub const BUTTON_SIZE: f32 = 40.0;
+
+/// Width of the leading space before the first button
+const ROW_LEAD: f32 = 24.0;
+
+/// Height of the content (title bar, buttons, status line) inside the padding
+const CONTENT_HEIGHT: f32 = 76.0;
+
+/// Title bar buttons shown in every preset: close, minimize, corner, compact,
+/// recolor, inbox (once a background result arrives), diagnostics, config,
+/// reload and restart
+const TITLE_BAR_BUTTONS: usize = 10;
+
+/// Width of a title bar button including the gap before the next one
+const TITLE_BUTTON_WIDTH: f32 = 30.0;
+
+/// Room left of the title bar buttons for the preset and branch labels
+const TITLE_LABEL_WIDTH: f32 = 40.0;
+
+/// Number of title bar buttons to make room for (none when `title_bar = "hide"`)
+///
+/// The preset switch button is only shown with more than one preset.
+pub fn title_bar_buttons(title_bar: &str, num_presets: usize) -> usize {
+    if title_bar == "hide" {
+        0
+    } else {
+        TITLE_BAR_BUTTONS + usize::from(num_presets > 1)
+    }
+}
+
+/// Compute the full bar window size for a number of commands
+///
+/// The window is at least wide enough for `title_buttons` title bar buttons
+/// (see `title_bar_buttons`), so a bar with few commands doesn't push them
+/// over the preset label.
+pub fn bar_size(
+    num_commands: usize,
+    title_buttons: usize,
+    padding: f32,
+    spacing: f32,
+) -> egui::Vec2 {
+    let num_commands = num_commands.max(1) as f32;
+    let row = num_commands * (BUTTON_SIZE + spacing) + ROW_LEAD;
+    let title = match title_buttons {
+        0 => 0.0,
+        n => n as f32 * TITLE_BUTTON_WIDTH + TITLE_LABEL_WIDTH,
+    };
+    egui::vec2(
+        row.max(title) + 2.0 * padding,
+        CONTENT_HEIGHT + 2.0 * padding,
+    )
+}
+
+/// Height of the title bar strip at the top of the panel
+pub const TITLE_BAR_HEIGHT: f32 = 20.0;
+
+/// Title bar strip at the top of the panel
+fn title_region(panel: egui::Rect) -> egui::Rect {
+    egui::Rect::from_min_max(
+        panel.min,
+        egui::pos2(panel.right(), panel.top() + TITLE_BAR_HEIGHT),
+    )
+}
+
+/// Regions that take pointer input: the title bar and the buttons
+pub fn interactive_regions(
+    panel: egui::Rect,
+    row: egui::Rect,
+    buttons_right: f32,
+) -> [egui::Rect; 2] {
+    let buttons = egui::Rect::from_min_max(row.min, egui::pos2(buttons_right, row.bottom()));
+    [title_region(panel), buttons]
+}
+
+/// Panel regions that start a window drag without covering the button row
+///
+/// Returns the title bar strip, the row space right of the last button, and
+/// everything below the row (status line).
+pub fn drag_regions(panel: egui::Rect, row: egui::Rect, buttons_right: f32) -> Vec<egui::Rect> {
+    let title = title_region(panel);
+    let row_rest = egui::Rect::from_min_max(
+        egui::pos2(buttons_right, row.top()),
+        egui::pos2(panel.right(), row.bottom()),
+    );
+    let below = egui::Rect::from_min_max(egui::pos2(panel.left(), row.bottom()), panel.max);
+    [title, row_rest, below]
+        .into_iter()
+        .filter(|r| r.is_positive())
+        .collect()
+}
+
+/// Where the window is placed at startup (`window.start_position`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartPosition {
+    /// The position saved for the directory (or wherever the OS puts it)
+    Saved,
+    Corner,
+    Center,
+    Cursor,
+}
+
+impl StartPosition {
+    /// Parse a setting value, warning on unknown values
+    pub fn parse(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "saved" => Self::Saved,
+            "corner" => Self::Corner,
+            "center" => Self::Center,
+            "cursor" => Self::Cursor,
+            other => {
+                eprintln!("[warn] Unknown start_position '{}', using 'saved'", other);
+                Self::Saved
+            }
+        }
+    }
+}
+
+/// Bottom-right corner position, clear of the dock/taskbar
+pub fn corner_position(monitor: egui::Vec2, window: egui::Vec2) -> egui::Pos2 {
+    egui::pos2(monitor.x - window.x - 20.0, monitor.y - window.y - 110.0)
+}
+
+/// Viewport command placing the window at startup
+///
+/// `cursor` is the pointer in screen coordinates, if known; without it
+/// `Cursor` falls back to centering. `Saved` is handled by the position restore.
+pub fn start_command(
+    start: StartPosition,
+    monitor: egui::Vec2,
+    window: egui::Vec2,
+    cursor: Option<egui::Pos2>,
+) -> Option<egui::ViewportCommand> {
+    let center = ((monitor - window) / 2.0).to_pos2();
+    let pos = match start {
+        StartPosition::Saved => return None,
+        StartPosition::Corner => corner_position(monitor, window),
+        StartPosition::Center => center,
+        StartPosition::Cursor => match cursor {
+            Some(cursor) => {
+                let max = (monitor - window).max(egui::Vec2::ZERO);
+                (cursor - window / 2.0).clamp(egui::Pos2::ZERO, max.to_pos2())
+            }
+            None => center,
+        },
+    };
+    Some(egui::ViewportCommand::OuterPosition(pos))
+}
+
+/// Index of the button whose rect contains `pos` (e.g. where files were dropped)
+pub fn button_at(buttons: &[egui::Rect], pos: egui::Pos2) -> Option<usize> {
+    buttons.iter().position(|rect| rect.contains(pos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_button_at() {
+        let buttons: Vec<_> = (0..3)
+            .map(|i| {
+                let left = 24.0 + i as f32 * (BUTTON_SIZE + 16.0);
+                egui::Rect::from_min_size(
+                    egui::pos2(left, 30.0),
+                    egui::vec2(BUTTON_SIZE, BUTTON_SIZE),
+                )
+            })
+            .collect();
+        assert_eq!(button_at(&buttons, egui::pos2(30.0, 40.0)), Some(0));
+        assert_eq!(
+            button_at(&buttons, egui::pos2(24.0 + 2.0 * 56.0 + 39.0, 69.0)),
+            Some(2)
+        );
+        // Gaps between buttons, the title bar and an empty row hit nothing
+        assert_eq!(button_at(&buttons, egui::pos2(24.0 + 48.0, 40.0)), None);
+        assert_eq!(button_at(&buttons, egui::pos2(30.0, 10.0)), None);
+        assert_eq!(button_at(&[], egui::pos2(30.0, 40.0)), None);
+    }
+
+    #[test]
+    fn test_bar_size_defaults() {
+        // Matches the original fixed layout (12pt margin, 56pt per button)
+        assert_eq!(bar_size(5, 0, 12.0, 16.0), egui::vec2(328.0, 100.0));
+        // At least one button's worth of room
+        assert_eq!(bar_size(0, 0, 12.0, 16.0), bar_size(1, 0, 12.0, 16.0));
+    }
+
+    #[test]
+    fn test_bar_size_fits_title_bar() {
+        let buttons = title_bar_buttons("auto", 2);
+        assert_eq!(buttons, 11);
+        assert_eq!(title_bar_buttons("show", 1), 10);
+        assert_eq!(title_bar_buttons("hide", 2), 0);
+
+        // 11 * 30 + 40 + 2 * 12, wider than one command
+        assert_eq!(bar_size(1, buttons, 12.0, 16.0), egui::vec2(394.0, 100.0));
+        // Many commands are wider than the title bar
+        assert_eq!(bar_size(8, buttons, 12.0, 16.0), bar_size(8, 0, 12.0, 16.0));
+        // A hidden title bar needs no room
+        assert_eq!(bar_size(1, 0, 12.0, 16.0), egui::vec2(104.0, 100.0));
+    }
+
+    #[test]
+    fn test_bar_size_custom_spacing_and_padding() {
+        // 3 * (40 + 4) + 24 + 2 * 6
+        assert_eq!(bar_size(3, 0, 6.0, 4.0), egui::vec2(168.0, 88.0));
+        // 2 * (40 + 24) + 24 + 2 * 20
+        assert_eq!(bar_size(2, 0, 20.0, 24.0), egui::vec2(192.0, 116.0));
+    }
+
+    #[test]
+    fn test_drag_regions_skip_buttons() {
+        let panel = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(200.0, 80.0));
+        let row = egui::Rect::from_min_max(egui::pos2(0.0, 24.0), egui::pos2(200.0, 64.0));
+        let buttons = egui::Rect::from_min_max(egui::pos2(4.0, 24.0), egui::pos2(100.0, 64.0));
+
+        let regions = drag_regions(panel, row, buttons.right());
+        assert_eq!(regions.len(), 3);
+        assert!(regions.iter().all(|r| !r.intersects(buttons.shrink(0.5))));
+        assert!(regions.iter().any(|r| r.contains(egui::pos2(150.0, 40.0))));
+        assert!(regions.iter().any(|r| r.contains(egui::pos2(50.0, 10.0))));
+        assert!(regions.iter().any(|r| r.contains(egui::pos2(50.0, 70.0))));
+    }
+
+    #[test]
+    fn test_drag_regions_full_row_has_no_row_region() {
+        let panel = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(200.0, 80.0));
+        let row = egui::Rect::from_min_max(egui::pos2(0.0, 24.0), egui::pos2(200.0, 64.0));
+
+        assert_eq!(drag_regions(panel, row, 200.0).len(), 2);
+    }
+
+    #[test]
+    fn test_start_position_parse() {
+        assert_eq!(StartPosition::parse("Corner"), StartPosition::Corner);
+        assert_eq!(StartPosition::parse("cursor"), StartPosition::Cursor);
+        assert_eq!(StartPosition::parse("bogus"), StartPosition::Saved);
+    }
+
+    #[test]
+    fn test_start_command_variants() {
+        let monitor = egui::vec2(1000.0, 800.0);
+        let window = egui::vec2(200.0, 100.0);
+        let place = |start, cursor| start_command(start, monitor, window, cursor);
+
+        assert_eq!(place(StartPosition::Saved, None), None);
+        assert_eq!(
+            place(StartPosition::Corner, None),
+            Some(egui::ViewportCommand::OuterPosition(egui::pos2(
+                780.0, 590.0
+            )))
+        );
+        assert_eq!(
+            place(StartPosition::Center, None),
+            Some(egui::ViewportCommand::OuterPosition(egui::pos2(
+                400.0, 350.0
+            )))
+        );
+        assert_eq!(
+            place(StartPosition::Cursor, Some(egui::pos2(500.0, 60.0))),
+            Some(egui::ViewportCommand::OuterPosition(egui::pos2(
+                400.0, 10.0
+            )))
+        );
+        // Clamped to the monitor, and centered when the pointer is unknown
+        assert_eq!(
+            place(StartPosition::Cursor, Some(egui::pos2(990.0, 790.0))),
+            Some(egui::ViewportCommand::OuterPosition(egui::pos2(
+                800.0, 700.0
+            )))
+        );
+        assert_eq!(
+            place(StartPosition::Cursor, None),
+            place(StartPosition::Center, None)
+        );
+    }
+}
@@ -1,9 +1,29 @@
 //! UI module for Launch Bar
 
+pub mod accent;
+pub mod click_through;
 pub mod colors;
+pub mod fade;
+#[cfg(feature = "global-hotkey")]
+pub mod global_hotkey;
+pub mod hotkey;
 pub mod icons;
+pub mod image_color;
+pub mod layout;
+pub mod repaint;
 pub mod widgets;
 
-pub use colors::{palette, parse_hex_color, vary_color_by_path};
-pub use icons::{available_icons, get_icon};
-pub use widgets::title_bar_button;
+pub use accent::{result_accent, result_accent_fading};
+pub use click_through::{is_over_empty, ClickThrough, CLICK_THROUGH_RECHECK};
+pub use colors::{category_color, preset_color, vary_color_by_path, Palette};
+pub use fade::{lerp, Fade};
+#[cfg(feature = "global-hotkey")]
+pub use global_hotkey::GlobalHotkeys;
+pub use hotkey::{global_hotkey_bindings, hotkey_bindings, key_command};
+pub use icons::{available_icons, command_icon_text};
+pub use layout::{
+    bar_size, button_at, corner_position, drag_regions, interactive_regions, start_command,
+    title_bar_buttons, StartPosition, COMPACT_SIZE, TITLE_BAR_HEIGHT,
+};
+pub use repaint::{next_repaint, Activity};
+pub use widgets::{command_button, paint_badge, title_bar_button};
@@ -0,0 +1,96 @@
+//! Repaint scheduling: idle checks, process polling and animation frames
+
+use std::time::Duration;
+
+/// Interval between idle repaints (file changes, background results)
+pub const IDLE_REPAINT: Duration = Duration::from_millis(500);
+
+/// What needs the next frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Activity {
+    /// Commands or scripts are running and must be polled
+    pub running: bool,
+    /// A fade is in progress
+    pub animating: bool,
+    /// Time left until the file-change highlight ends
+    pub highlight_left: Option<Duration>,
+}
+
+/// Shortest allowed gap between frames for `max_fps` (0 is treated as 1)
+pub fn frame_interval(max_fps: u32) -> Duration {
+    Duration::from_secs(1) / max_fps.max(1)
+}
+
+/// Delay until the next repaint
+///
+/// Running commands are checked every `poll_interval` and fades run at
+/// `max_fps`; a highlight only needs a frame when it ends. No repaint is
+/// scheduled sooner than one `max_fps` frame.
+pub fn next_repaint(activity: Activity, poll_interval: Duration, max_fps: u32) -> Duration {
+    let frame = frame_interval(max_fps);
+    let mut delay = IDLE_REPAINT;
+    if activity.running {
+        delay = delay.min(poll_interval);
+    }
+    if activity.animating {
+        delay = delay.min(frame);
+    }
+    if let Some(left) = activity.highlight_left {
+        delay = delay.min(left);
+    }
+    delay.max(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLL: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn test_idle_uses_idle_interval() {
+        assert_eq!(next_repaint(Activity::default(), POLL, 30), IDLE_REPAINT);
+    }
+
+    #[test]
+    fn test_running_polls_without_animation_rate() {
+        let running = Activity {
+            running: true,
+            ..Default::default()
+        };
+        assert_eq!(next_repaint(running, POLL, 30), POLL);
+        // A low FPS cap limits polling too
+        assert_eq!(next_repaint(running, POLL, 5), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_animating_runs_at_max_fps() {
+        let animating = Activity {
+            animating: true,
+            running: true,
+            ..Default::default()
+        };
+        assert_eq!(next_repaint(animating, POLL, 20), Duration::from_millis(50));
+        // 0 is treated as 1 FPS
+        assert_eq!(next_repaint(animating, POLL, 0), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_highlighting_repaints_when_it_ends() {
+        let highlighting = Activity {
+            highlight_left: Some(Duration::from_secs(3)),
+            ..Default::default()
+        };
+        assert_eq!(next_repaint(highlighting, POLL, 30), IDLE_REPAINT);
+        let ending = Activity {
+            highlight_left: Some(Duration::from_millis(120)),
+            ..Default::default()
+        };
+        assert_eq!(next_repaint(ending, POLL, 30), Duration::from_millis(120));
+        let ended = Activity {
+            highlight_left: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        assert_eq!(next_repaint(ended, POLL, 10), Duration::from_millis(100));
+    }
+}
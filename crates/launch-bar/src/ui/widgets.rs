@@ -2,16 +2,69 @@
 
 use eframe::egui;
 
-use super::colors::palette;
+use super::colors::{parse_hex_color, Palette};
+use super::icons::icon_text;
+use super::layout::BUTTON_SIZE;
 
 /// Create a title bar button with consistent styling
-pub fn title_bar_button(ui: &mut egui::Ui, icon: &str, tooltip: &str) -> egui::Response {
-    let icon_text = egui::RichText::new(icon)
-        .family(egui::FontFamily::Name("icons".into()))
+pub fn title_bar_button(
+    ui: &mut egui::Ui,
+    palette: &Palette,
+    icon: &str,
+    tooltip: &str,
+) -> egui::Response {
+    let icon_text = icon_text(ui.ctx(), icon, tooltip)
         .size(14.0)
-        .color(palette::BUTTON_ICON);
+        .color(palette.button_icon);
     let button = egui::Button::new(icon_text)
         .fill(egui::Color32::TRANSPARENT)
         .min_size(egui::vec2(20.0, 20.0));
     ui.add(button).on_hover_text(tooltip)
 }
+
+/// Paint a small count badge (white text on `palette.badge_bg`) centered at `center`
+pub fn paint_badge(
+    painter: &egui::Painter,
+    palette: &Palette,
+    center: egui::Pos2,
+    radius: f32,
+    text: &str,
+) {
+    painter.circle_filled(center, radius, palette.badge_bg);
+    painter.text(
+        center,
+        egui::Align2::CENTER_CENTER,
+        text,
+        egui::FontId::proportional(radius * 9.0 / 7.0),
+        egui::Color32::WHITE,
+    );
+}
+
+/// Create a command button, filled with the command's `background` color if any
+pub fn command_button<'a>(icon_text: egui::RichText, background: Option<&str>) -> egui::Button<'a> {
+    egui::Button::new(icon_text)
+        .fill(button_fill(background))
+        .min_size(egui::vec2(BUTTON_SIZE, BUTTON_SIZE))
+}
+
+/// Fill color for a command button (transparent unless `background` is a valid hex color)
+fn button_fill(background: Option<&str>) -> egui::Color32 {
+    background
+        .and_then(parse_hex_color)
+        .unwrap_or(egui::Color32::TRANSPARENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_button_fill() {
+        assert_eq!(
+            button_fill(Some("#7F1D1D")),
+            egui::Color32::from_rgb(0x7f, 0x1d, 0x1d)
+        );
+        assert_eq!(button_fill(None), egui::Color32::TRANSPARENT);
+        assert_eq!(button_fill(Some("dark red")), egui::Color32::TRANSPARENT);
+    }
+}
@@ -0,0 +1,118 @@
+//! Opt-in update check (`launch-bar --check-updates [URL]`, `http` feature)
+//!
+//! Nothing is fetched unless the flag is given.
+
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use semver::Version;
+
+/// Version of this build
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Where the latest version is looked up when no URL is given
+pub const DEFAULT_UPDATE_URL: &str = "https://crates.io/api/v1/crates/launch-bar";
+
+/// Give up on the lookup after this long
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Parse a semantic version, allowing surrounding whitespace and a leading `v`
+fn parse_version(text: &str) -> Option<Version> {
+    let text = text.trim();
+    Version::parse(text.strip_prefix('v').unwrap_or(text)).ok()
+}
+
+/// Latest version from a response body
+///
+/// Accepts the crates.io API response (`crate.max_stable_version`) or a
+/// plain-text body holding just the version.
+pub fn latest_version(body: &str) -> Option<Version> {
+    if let Ok(json) = serde_json::from_str::<serde_json::Value>(body) {
+        if let Some(version) = json.pointer("/crate/max_stable_version") {
+            return parse_version(version.as_str()?);
+        }
+    }
+    parse_version(body)
+}
+
+/// Result line for `--check-updates` (build metadata is ignored)
+pub fn update_message(current: &Version, latest: &Version) -> String {
+    if latest.cmp_precedence(current) == Ordering::Greater {
+        format!(
+            "Update available: {} -> {} (cargo install launch-bar)",
+            current, latest
+        )
+    } else {
+        format!("launch-bar {} is up to date", current)
+    }
+}
+
+/// GET `url` as text
+fn fetch(url: &str) -> Result<String, String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(FETCH_TIMEOUT))
+        .build()
+        .into();
+    agent
+        .get(url)
+        .header("User-Agent", format!("launch-bar/{}", CURRENT_VERSION))
+        .call()
+        .and_then(|mut response| response.body_mut().read_to_string())
+        .map_err(|e| format!("cannot fetch {}: {}", url, e))
+}
+
+/// Compare this build with the latest version at `url`, returning the message
+pub fn check_updates(url: &str) -> Result<String, String> {
+    let current = parse_version(CURRENT_VERSION)
+        .ok_or_else(|| format!("invalid current version {}", CURRENT_VERSION))?;
+    let body = fetch(url)?;
+    let latest = latest_version(&body).ok_or_else(|| format!("no version found at {}", url))?;
+    Ok(update_message(&current, &latest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(text: &str) -> Version {
+        parse_version(text).unwrap()
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(version(" v0.1.3\n").to_string(), "0.1.3");
+        assert!(parse_version(CURRENT_VERSION).is_some());
+        assert_eq!(parse_version("1.2"), None);
+        assert_eq!(parse_version("01.2.3"), None);
+        assert_eq!(parse_version("latest"), None);
+    }
+
+    #[test]
+    fn test_latest_version_and_message() {
+        let crates_io = r#"{"crate": {"name": "launch-bar", "max_stable_version": "0.2.0"}}"#;
+        assert_eq!(latest_version(crates_io), Some(version("0.2.0")));
+        assert_eq!(latest_version("v0.1.4\n"), Some(version("0.1.4")));
+        assert_eq!(latest_version("<html>"), None);
+
+        assert_eq!(
+            update_message(&version("0.1.3"), &version("0.2.0")),
+            "Update available: 0.1.3 -> 0.2.0 (cargo install launch-bar)"
+        );
+        assert_eq!(
+            update_message(&version("0.1.9"), &version("0.1.10")),
+            "Update available: 0.1.9 -> 0.1.10 (cargo install launch-bar)"
+        );
+        assert_eq!(
+            update_message(&version("0.1.3"), &version("0.1.3")),
+            "launch-bar 0.1.3 is up to date"
+        );
+        assert_eq!(
+            update_message(&version("0.2.0"), &version("0.2.0-rc.1")),
+            "launch-bar 0.2.0 is up to date"
+        );
+        assert_eq!(
+            update_message(&version("0.2.0"), &version("0.2.0+build.7")),
+            "launch-bar 0.2.0 is up to date"
+        );
+    }
+}